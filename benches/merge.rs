@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use serde::{Deserialize, Serialize};
+use serfig::collectors::from_self;
+use serfig::Builder;
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+struct BenchConfig {
+    name: String,
+    entries: BTreeMap<String, i64>,
+}
+
+/// Build a config with `n` keys, values are offset by `seed`.
+fn fixture(n: usize, seed: i64) -> BenchConfig {
+    BenchConfig {
+        name: format!("layer-{seed}"),
+        entries: (0..n)
+            .map(|i| (format!("key_{i}"), i as i64 + seed))
+            .collect(),
+    }
+}
+
+fn bench_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge");
+
+    group.bench_function("10k_keys_3_layers", |b| {
+        b.iter_batched(
+            || {
+                Builder::default()
+                    .collect(from_self(fixture(10_000, 1)))
+                    .collect(from_self(fixture(10_000, 2)))
+                    .collect(from_self(fixture(10_000, 3)))
+            },
+            |builder| {
+                let _ = builder
+                    .build_with(fixture(10_000, 0))
+                    .expect("build must succeed");
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish()
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+#[serde(default)]
+struct SmallConfig {
+    name: String,
+    limit: u32,
+    enabled: bool,
+}
+
+fn bench_small(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small");
+
+    // Builds per request like plugins, the default tree is serialized on
+    // every build unless cached.
+    for cached in [false, true] {
+        let name = if cached { "cached_default" } else { "default" };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let builder = Builder::default().collect(from_self(SmallConfig {
+                    limit: 10,
+                    ..Default::default()
+                }));
+                let builder = if cached {
+                    builder.cache_default()
+                } else {
+                    builder
+                };
+                let _: SmallConfig = builder.build().expect("build must succeed");
+            })
+        });
+    }
+
+    group.finish()
+}
+
+criterion_group!(benches, bench_merge, bench_small);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+#![no_main]
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use serde::{Deserialize, Serialize};
+use serfig::collectors::from_reader;
+use serfig::parsers::Jsonc;
+use serfig::{Builder, MergeConfig, MergeStrategy, SeqPolicy};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Config {
+    name: String,
+    port: u16,
+    tags: Vec<String>,
+    limits: HashMap<String, i64>,
+    level: Option<Level>,
+    extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Level {
+    Debug,
+    Info(u8),
+    Custom { name: String },
+}
+
+// Merging arbitrary layers must return errors instead of panicking.
+fuzz_target!(|data: &[u8]| {
+    let Some((&flags, data)) = data.split_first() else {
+        return;
+    };
+    let (a, b) = data.split_at(data.len() / 2);
+
+    let cfg = MergeConfig::default()
+        .strategy(if flags & 1 == 0 {
+            MergeStrategy::Deep
+        } else {
+            MergeStrategy::Replace
+        })
+        .seq_policy(if flags & 2 == 0 {
+            SeqPolicy::Replace
+        } else {
+            SeqPolicy::Append
+        })
+        .fold_case(flags & 4 != 0);
+
+    let _ = Builder::<Config>::default()
+        .with_merge_config(cfg)
+        .collect(from_reader(Jsonc, Cursor::new(a.to_vec())))
+        .collect(from_reader(Jsonc, Cursor::new(b.to_vec())))
+        .build();
+});
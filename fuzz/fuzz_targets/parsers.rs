@@ -0,0 +1,31 @@
+#![no_main]
+
+use std::collections::HashMap;
+
+use libfuzzer_sys::fuzz_target;
+use serde::Deserialize;
+use serfig::parsers::{DotEnv, Jsonc, Lossy, Toml};
+use serfig::Parser;
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+struct Config {
+    name: String,
+    port: u16,
+    tags: Vec<String>,
+    limits: HashMap<String, i64>,
+    extra: Option<serde_json::Value>,
+}
+
+fn check(mut p: impl Parser, data: &[u8]) {
+    let _ = p.parse_value(data);
+    let _ = p.parse::<Config>(data);
+}
+
+// Every parser must return errors instead of panicking on any input.
+fuzz_target!(|data: &[u8]| {
+    check(Toml, data);
+    check(DotEnv, data);
+    check(Jsonc, data);
+    check(Lossy::new(Toml), data);
+});
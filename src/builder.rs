@@ -1,16 +1,70 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::rc::Rc;
+
 use anyhow::{anyhow, Result};
-use log::{debug, warn};
+use log::{debug, error, warn};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_bridge::{into_value, FromValue};
+use serde_bridge::Value;
+
+use crate::check::BuildCheck;
+use crate::collectors::env::EnvMapping;
+use crate::collectors::expand_path;
+use crate::collectors::{
+    defer, AsyncCollector, Collector, DynAsyncCollector, Grouped, IntoCollector, Overrides, Slot,
+    SourceDescriptor, Trust, Trusted,
+};
+use crate::constraint::{Annotation, Constraint, Rule, Violations};
+use crate::de::{self, Track};
+use crate::explain::{Detail, ExplainEntry, Explanation};
+use crate::interpolate::Interpolator;
+use crate::path::{KeyPath, Segment};
+use crate::plan::{BuildPlan, PlannedSource, TrustPolicy};
+use crate::report::{BuildReport, LayerStats, ReportedKey};
+use crate::snapshot::Snapshot;
+use crate::value::{
+    default_value, flatten, get, get_mut, is_sensitive, merge, parse_str, to_value, MergeConfig,
+    REDACTED,
+};
 
-use crate::collectors::{Collector, IntoCollector};
-use crate::value::{merge, merge_with_default};
+type Coercion = Box<dyn Fn(Value) -> Result<Value>>;
+type ErrorObserver = Rc<dyn Fn(&anyhow::Error)>;
 
 /// Builder will collect values from different collectors and merge into the final value.
 #[derive(Default)]
 pub struct Builder<V: DeserializeOwned + Serialize> {
     collectors: Vec<Box<dyn Collector<V>>>,
+    constraints: Vec<Constraint<V>>,
+    coercions: Vec<(KeyPath, Coercion)>,
+    /// Fields holding paths relative to the file they come from.
+    relative_paths: Vec<KeyPath>,
+    /// Fields that override previous layers whenever set, even to default.
+    explicit_paths: Vec<KeyPath>,
+    interpolator: Option<Interpolator>,
+    merge_config: MergeConfig,
+    audit_keys: bool,
+    trust_policies: Vec<(KeyPath, Trust)>,
+    /// Skip layers that failed instead of returning error.
+    lenient: bool,
+    invalid_paths: Vec<anyhow::Error>,
+    /// Produce the default value tree without serializing `V::default()`.
+    default_value: Option<fn() -> Result<Value>>,
+    /// Async collectors and the slots of their placeholder layers.
+    pending: Vec<(Box<dyn DynAsyncCollector>, Slot)>,
+    /// Values set by [`Builder::set`], merged after all collectors.
+    overrides: Vec<(KeyPath, Override)>,
+    /// Called with errors [`Builder::build_or_default`] recovered from.
+    error_observers: Vec<ErrorObserver>,
+}
+
+/// Value set by [`Builder::set`] or [`Builder::set_str`].
+enum Override {
+    Value(Value),
+    /// Parsed as the type of the field at build.
+    Str(String),
 }
 
 impl<V> Builder<V>
@@ -21,137 +75,1740 @@ where
     pub fn new() -> Builder<V> {
         Self {
             collectors: Vec::new(),
+            constraints: Vec::new(),
+            coercions: Vec::new(),
+            relative_paths: Vec::new(),
+            explicit_paths: Vec::new(),
+            interpolator: None,
+            merge_config: MergeConfig::default(),
+            audit_keys: false,
+            trust_policies: Vec::new(),
+            lenient: false,
+            invalid_paths: Vec::new(),
+            default_value: None,
+            pending: Vec::new(),
+            overrides: Vec::new(),
+            error_observers: Vec::new(),
+        }
+    }
+
+    /// Add collectors into builder.
+    ///
+    /// This is a lazy operation that no real IO happens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, from_file, from_self};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    ///     b: String,
+    ///     c: i64,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_env())
+    ///         .collect(from_file(Toml, "config.toml"))
+    ///         .collect(from_self(TestConfig::default()));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn collect(mut self, c: impl IntoCollector<V>) -> Self {
+        self.collectors.push(c.into_collector());
+        self
+    }
+
+    /// Add collectors into builder with given trust level instead of the
+    /// one declared by the collector.
+    ///
+    /// This is a lazy operation that no real IO happens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, Trust};
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     exec_path: String,
+    /// }
+    ///
+    /// let builder: Builder<TestConfig> = Builder::default()
+    ///     .collect_with_trust(from_env(), Trust::Remote)
+    ///     .require_trust("exec_path", Trust::User);
+    /// ```
+    pub fn collect_with_trust(mut self, c: impl IntoCollector<V>, trust: Trust) -> Self
+    where
+        V: 'static,
+    {
+        self.collectors.push(Box::new(Trusted {
+            inner: c.into_collector(),
+            trust,
+        }));
+        self
+    }
+
+    /// Add an [`AsyncCollector`] into builder, the value will be merged at
+    /// the position it's added.
+    ///
+    /// Builders with async collectors must be built by
+    /// [`Builder::build_async`].
+    ///
+    /// This is a lazy operation that no real IO happens.
+    pub fn collect_async(mut self, c: impl AsyncCollector<V> + 'static) -> Self
+    where
+        V: 'static,
+    {
+        let (c, slot, deferred) = defer(c);
+        self.pending.push((c, slot));
+        self.collectors.push(Box::new(deferred));
+        self
+    }
+
+    /// Await all async collectors in order and keep their results for
+    /// the following build.
+    async fn resolve_async(&mut self) {
+        for (c, slot) in &mut self.pending {
+            let v = c.collect_boxed().await;
+            *slot.borrow_mut() = Some(v);
+        }
+    }
+
+    /// Add collectors as a logical group like `site overrides`.
+    ///
+    /// Collectors are applied in the order they are added to the group.
+    /// Errors, reports and provenance will refer to sources with the
+    /// group name like `site overrides / file: site.toml`.
+    ///
+    /// This is a lazy operation that no real IO happens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, from_file};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// let builder: Builder<TestConfig> = Builder::default()
+    ///     .group("site overrides", |g| {
+    ///         g.collect(from_file(Toml, "site.toml"))
+    ///             .collect(from_file(Toml, "rack.toml"))
+    ///     })
+    ///     .collect(from_env());
+    ///
+    /// assert_eq!(
+    ///     builder.sources()[1].to_string(),
+    ///     "site overrides / file: rack.toml"
+    /// );
+    /// ```
+    pub fn group(mut self, name: &str, f: impl FnOnce(Group<V>) -> Group<V>) -> Self
+    where
+        V: 'static,
+    {
+        let g = f(Group {
+            collectors: Vec::new(),
+        });
+        self.collectors.extend(
+            g.collectors
+                .into_iter()
+                .map(|inner| -> Box<dyn Collector<V>> {
+                    Box::new(Grouped {
+                        inner,
+                        group: name.to_string(),
+                    })
+                }),
+        );
+        self
+    }
+
+    /// Add a layer that loads env vars declared in a mapping file.
+    ///
+    /// The mapping file is a TOML document (or JSON if the extension is
+    /// `.json`) that maps env var names to config paths, so env vars can be
+    /// renamed without code changes. A missing mapping file will be ignored.
+    ///
+    /// This is a lazy operation that no real IO happens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_env;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // env-mapping.toml contains `PLATFORM_A = "a"`.
+    ///     let builder = Builder::default()
+    ///         .collect(from_env())
+    ///         .with_env_mapping_file("env-mapping.toml");
+    ///     let t: TestConfig = builder.build()?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_env_mapping_file(self, path: &str) -> Self
+    where
+        V: Debug + 'static,
+    {
+        self.collect(EnvMapping::new(path))
+    }
+
+    /// Returns the ordered list of sources this builder will consult.
+    ///
+    /// This is a lazy operation that no real IO happens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, from_file};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// let builder: Builder<TestConfig> = Builder::default()
+    ///     .collect(from_file(Toml, "config.toml"))
+    ///     .collect(from_env());
+    ///
+    /// for s in builder.sources() {
+    ///     println!("{s}");
+    /// }
+    /// ```
+    pub fn sources(&self) -> Vec<SourceDescriptor> {
+        self.collectors.iter().map(|c| c.describe()).collect()
+    }
+
+    /// Add a constraint that must be held by the built value.
+    ///
+    /// All constraints will be checked after build, and violations will
+    /// be reported together.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     min: i64,
+    ///     max: i64,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_str(Toml, "min = 10"))
+    ///         .constraint(|c: &TestConfig| c.min <= c.max, "min must be <= max");
+    ///
+    ///     assert!(builder.build().is_err());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn constraint(mut self, f: impl Fn(&V) -> bool + 'static, msg: &str) -> Self {
+        self.constraints
+            .push(Constraint::Fn(Box::new(f), msg.to_string()));
+        self
+    }
+
+    /// Require `dep` to be set if `path` has been set.
+    ///
+    /// Paths are [`KeyPath`]s like `tls.cert`. A path is treated as set
+    /// if its value differs from the default value.
+    pub fn requires<P, D>(mut self, path: P, dep: D) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+        D: TryInto<KeyPath>,
+        D::Error: Into<anyhow::Error>,
+    {
+        if let (Some(path), Some(dep)) = (self.key_path(path), self.key_path(dep)) {
+            self.constraints.push(Constraint::Requires(path, dep));
+        }
+        self
+    }
+
+    /// Forbid `path` and `other` to be set at the same time.
+    ///
+    /// Paths are [`KeyPath`]s like `tls.cert`. A path is treated as set
+    /// if its value differs from the default value.
+    pub fn conflicts<P, O>(mut self, path: P, other: O) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+        O: TryInto<KeyPath>,
+        O::Error: Into<anyhow::Error>,
+    {
+        if let (Some(path), Some(other)) = (self.key_path(path), self.key_path(other)) {
+            self.constraints.push(Constraint::Conflicts(path, other));
+        }
+        self
+    }
+
+    /// Require the number at `path` to be within `range` like `1..=65535`.
+    ///
+    /// The rule is checked like other constraints and listed by
+    /// [`Builder::annotations`]. Missing or null values are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     port: u32,
+    ///     level: String,
+    /// }
+    ///
+    /// let builder: Builder<TestConfig> = Builder::default()
+    ///     .collect(from_str(Toml, "port = 70000\nlevel = \"inf\""))
+    ///     .range("port", 1..=65535)
+    ///     .one_of("level", ["debug", "info", "warn"]);
+    ///
+    /// let err = builder.build().unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "config constraints violated:
+    ///   - `port` must be in 1..=65535, got 70000
+    ///   - `level` must be one of `debug`, `info`, `warn`, got `inf`, did you mean `info`?"
+    /// );
+    /// ```
+    pub fn range<P>(mut self, path: P, range: impl RangeBounds<i64>) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        let min = match range.start_bound() {
+            Bound::Included(n) => Some(*n),
+            Bound::Excluded(n) => Some(n.saturating_add(1)),
+            Bound::Unbounded => None,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(n) => Some(*n),
+            Bound::Excluded(n) => Some(n.saturating_sub(1)),
+            Bound::Unbounded => None,
+        };
+        if let Some(path) = self.key_path(path) {
+            self.constraints
+                .push(Constraint::Rule(path, Rule::Range { min, max }));
+        }
+        self
+    }
+
+    /// Require the string at `path` to be one of `values`.
+    ///
+    /// See [`Builder::range`] for examples.
+    pub fn one_of<P>(mut self, path: P, values: impl IntoIterator<Item = impl Into<String>>) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        if let Some(path) = self.key_path(path) {
+            self.constraints
+                .push(Constraint::Rule(path, Rule::OneOf(values)));
+        }
+        self
+    }
+
+    /// Returns rules declared by [`Builder::range`] and [`Builder::one_of`],
+    /// so schemas, docs or sample configs can be generated from them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     port: u16,
+    /// }
+    ///
+    /// let builder: Builder<TestConfig> = Builder::default().range("port", 1..=65535);
+    ///
+    /// for a in builder.annotations() {
+    ///     println!("# {}: {}", a.path, a.rule);
+    ///     println!("{}", a.rule.to_json_schema());
+    /// }
+    /// ```
+    pub fn annotations(&self) -> Vec<Annotation> {
+        self.constraints
+            .iter()
+            .filter_map(|c| match c {
+                Constraint::Rule(path, rule) => Some(Annotation {
+                    path: path.to_string(),
+                    rule: rule.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Describe the configured pipeline without collecting anything.
+    ///
+    /// The plan lists sources in merge order together with policies and
+    /// merge config, so applications can log how config will be loaded
+    /// and compare it across environments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, from_file};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     port: u16,
+    /// }
+    ///
+    /// let builder: Builder<TestConfig> = Builder::default()
+    ///     .collect(from_file(Toml, "config.toml"))
+    ///     .collect(from_env())
+    ///     .range("port", 1..=65535);
+    ///
+    /// let plan = builder.describe();
+    /// assert_eq!(plan.sources.len(), 2);
+    /// println!("{plan}");
+    /// ```
+    pub fn describe(&self) -> BuildPlan {
+        BuildPlan {
+            sources: self
+                .collectors
+                .iter()
+                .map(|c| PlannedSource {
+                    source: c.describe(),
+                    trust: c.trust(),
+                })
+                .chain((!self.overrides.is_empty()).then(|| PlannedSource {
+                    source: SourceDescriptor::new("override"),
+                    trust: Trust::User,
+                }))
+                .collect(),
+            merge: self.merge_config.clone(),
+            coercions: self.coercions.iter().map(|(p, _)| p.to_string()).collect(),
+            resolve_paths: self.relative_paths.iter().map(|p| p.to_string()).collect(),
+            explicit_paths: self.explicit_paths.iter().map(|p| p.to_string()).collect(),
+            trust_policies: self
+                .trust_policies
+                .iter()
+                .map(|(path, trust)| TrustPolicy {
+                    path: path.to_string(),
+                    trust: *trust,
+                })
+                .collect(),
+            rules: self.annotations(),
+            constraints: self.constraints.len(),
+            interpolate: self.interpolator.is_some(),
+            audit_keys: self.audit_keys,
+        }
+    }
+
+    /// Require fields under `path` to be set only by sources with at
+    /// least `trust`, like forbidding remote sources to set `exec_path`.
+    ///
+    /// The lowest trust of all sources that set a field is checked, so a
+    /// field set by a remote source is rejected even if overridden later.
+    /// Violations are reported together with constraints.
+    pub fn require_trust<P>(mut self, path: P, trust: Trust) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        if let Some(path) = self.key_path(path) {
+            self.trust_policies.push((path, trust));
+        }
+        self
+    }
+
+    /// Convert the raw value at `path` before deserializing into `V`.
+    ///
+    /// This allows custom formats like byte sizes or durations without
+    /// newtype wrappers. Paths are [`KeyPath`]s like `limits.max_memory`,
+    /// and coercions are applied in the order they are added.
+    ///
+    /// Invalid paths make build fail before any collector is consulted.
+    ///
+    /// Only collectors that support [`Collector::collect_raw`] like
+    /// [`from_file`][crate::collectors::from_file] will be coerced, other
+    /// collectors are collected as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::anyhow;
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::{Builder, Value};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     max_memory: u64,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_str(Toml, r#"max_memory = "512MiB""#))
+    ///         .coerce("max_memory", |v| match v {
+    ///             Value::Str(s) => {
+    ///                 let n: u64 = s.trim_end_matches("MiB").parse()?;
+    ///                 Ok(Value::U64(n * 1024 * 1024))
+    ///             }
+    ///             v => Ok(v),
+    ///         });
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.max_memory, 512 * 1024 * 1024);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn coerce<P>(mut self, path: P, f: impl Fn(Value) -> Result<Value> + 'static) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        if let Some(path) = self.key_path(path) {
+            self.coercions.push((path, Box::new(f)));
         }
+        self
+    }
+
+    /// Resolve relative paths in the string field at `path` like
+    /// `tls.cert` against the directory of the file it comes from.
+    ///
+    /// Sequences of strings are resolved element by element. Only layers
+    /// loaded from files like [`from_file`][crate::collectors::from_file]
+    /// are resolved, values from other sources and absolute paths are
+    /// kept as is. So moving a config directory together with the files
+    /// it references won't break them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_file;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     cert: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // /etc/app/config.toml contains `cert = "certs/app.pem"`.
+    ///     let builder = Builder::default()
+    ///         .collect(from_file(Toml, "/etc/app/config.toml"))
+    ///         .resolve_paths("cert");
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.cert, "/etc/app/certs/app.pem");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn resolve_paths<P>(mut self, path: P) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        if let Some(path) = self.key_path(path) {
+            self.relative_paths.push(path);
+        }
+        self
+    }
+
+    /// Take the field at `path` from every layer that sets it, even if
+    /// the value equals to default.
+    ///
+    /// By default, values equal to default are treated as unset, so a
+    /// later layer can't reset `timeout = 0` back if `0` is the default.
+    /// Prefer `Option<Duration>` or `Option<u64>` for such fields, where
+    /// `None` is unset and `Some(0)` is always explicit. Use this for
+    /// fields that can't be changed to `Option`.
+    ///
+    /// Only collectors that support [`Collector::collect_raw`] like
+    /// [`from_file`][crate::collectors::from_file] can tell whether a
+    /// field is set, other collectors are merged as usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     // `0` disables the timeout.
+    ///     timeout_secs: u64,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_str(Toml, "timeout_secs = 30"))
+    ///         .collect(from_str(Toml, "timeout_secs = 0"))
+    ///         .explicit("timeout_secs");
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.timeout_secs, 0);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn explicit<P>(mut self, path: P) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        if let Some(path) = self.key_path(path) {
+            self.explicit_paths.push(path);
+        }
+        self
+    }
+
+    /// Set the field at `path` like `database.pool_size` to `value`,
+    /// overriding all layers.
+    ///
+    /// Values are merged as the last layer no matter when they are set,
+    /// even if they equal to default. So applications can apply flags or
+    /// hard-coded overrides without crafting a document. Paths with
+    /// sequence indexes are not supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct Database {
+    ///     pool_size: u32,
+    ///     url: String,
+    /// }
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     database: Database,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .set("database.pool_size", 32)
+    ///         .collect(from_str(Toml, "[database]\npool_size = 8\nurl = \"db\""));
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.database.pool_size, 32);
+    ///     assert_eq!(t.database.url, "db");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set<P, T>(mut self, path: P, value: T) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+        T: Serialize,
+    {
+        let path = match self.override_path(path) {
+            Some(path) => path,
+            None => return self,
+        };
+        match to_value(&value) {
+            Ok(v) => self.overrides.push((path, Override::Value(v))),
+            Err(err) => self.invalid_paths.push(anyhow!("set `{path}`: {err}")),
+        }
+        self
+    }
+
+    /// Set the field at `path` to `value` parsed as the type of the
+    /// field like [`Builder::set`], ideal for `--set key=value` options.
+    ///
+    /// The type is taken from the default value of the field, so
+    /// `"8080"` becomes a number for `port: u16` but stays a string for
+    /// `name: String`. Sequences are split by `,`. Types of fields
+    /// without a typed default like `Option<u16>` are inferred from the
+    /// string. Invalid values are returned as errors by build.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct Server {
+    ///     port: u16,
+    ///     hosts: Vec<String>,
+    /// }
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     server: Server,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // Parsed from `--set server.port=8080 --set server.hosts=a,b`.
+    ///     let builder = Builder::default()
+    ///         .set_str("server.port", "8080")
+    ///         .set_str("server.hosts", "a,b");
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.server.port, 8080);
+    ///     assert_eq!(t.server.hosts, vec!["a", "b"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_str<P>(mut self, path: P, value: &str) -> Self
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        if let Some(path) = self.override_path(path) {
+            self.overrides
+                .push((path, Override::Str(value.to_string())));
+        }
+        self
+    }
+
+    /// Parse `path` of an override, sequence indexes are rejected.
+    fn override_path<P>(&mut self, path: P) -> Option<KeyPath>
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        let path = self.key_path(path)?;
+        if path
+            .segments()
+            .iter()
+            .any(|s| matches!(s, Segment::Index(_)))
+        {
+            self.invalid_paths
+                .push(anyhow!("set `{path}`: sequence indexes are not supported"));
+            return None;
+        }
+        Some(path)
+    }
+
+    /// Add values set by [`Builder::set`] as the last layer, they are
+    /// explicit so values equal to default still take effect.
+    ///
+    /// Strings set by [`Builder::set_str`] are parsed as the type of
+    /// their fields in `default`.
+    fn push_overrides(&mut self, default: &Value) -> Result<()> {
+        if self.overrides.is_empty() {
+            return Ok(());
+        }
+        let overrides = std::mem::take(&mut self.overrides)
+            .into_iter()
+            .map(|(path, v)| {
+                let v = match v {
+                    Override::Value(v) => v,
+                    Override::Str(s) => parse_str(&s, get(default, &path))
+                        .map_err(|err| anyhow!("set `{path}` to {s:?}: {err}"))?,
+                };
+                Ok((path, v))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.explicit_paths
+            .extend(overrides.iter().map(|(path, _)| path.clone()));
+        self.collectors.push(Box::new(Overrides::new(overrides)));
+        Ok(())
+    }
+
+    /// Parse `path` and record the error to be returned by build.
+    fn key_path<P>(&mut self, path: P) -> Option<KeyPath>
+    where
+        P: TryInto<KeyPath>,
+        P::Error: Into<anyhow::Error>,
+    {
+        match path.try_into() {
+            Ok(path) => Some(path),
+            Err(err) => {
+                self.invalid_paths.push(err.into());
+                None
+            }
+        }
+    }
+
+    /// Returns the first invalid path passed to this builder.
+    fn check_paths(&mut self) -> Result<()> {
+        match self.invalid_paths.drain(..).next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Set how layers are merged, see [`MergeConfig`] for all options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::{Builder, MergeConfig, SeqPolicy};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     plugins: Vec<String>,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_str(Toml, r#"plugins = ["a"]"#))
+    ///         .collect(from_str(Toml, r#"plugins = ["b"]"#))
+    ///         .with_merge_config(MergeConfig::default().seq_policy(SeqPolicy::Append));
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.plugins, vec!["a", "b"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_merge_config(mut self, cfg: MergeConfig) -> Self {
+        self.merge_config = cfg;
+        self
+    }
+
+    /// Expand `${function:args}` expressions in string values after all
+    /// layers have been merged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::{Builder, Interpolator};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     data_dir: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_str(Toml, r#"data_dir = "${env:SERFIG_DATA:-/var}/lib""#))
+    ///         .interpolate(Interpolator::default());
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     assert_eq!(t.data_dir, "/var/lib");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn interpolate(mut self, interpolator: Interpolator) -> Self {
+        self.interpolator = Some(interpolator);
+        self
+    }
+
+    /// Record unknown keys and keys only accepted via `#[serde(alias)]`
+    /// in [`BuildReport`] returned by [`Builder::build_with_report`].
+    ///
+    /// Only collectors that support [`Collector::collect_raw`] like
+    /// [`from_file`][crate::collectors::from_file] will be audited.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     #[serde(alias = "address")]
+    ///     addr: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_str(Toml, "address = \"a\"\nport = 80"))
+    ///         .audit_keys();
+    ///
+    ///     let (_, report) = builder.build_with_report(TestConfig::default())?;
+    ///     assert_eq!(report.deprecated_keys[0].key, "address");
+    ///     assert_eq!(report.unknown_keys[0].key, "port");
+    ///     println!("{}", report.to_json()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn audit_keys(mut self) -> Self {
+        self.audit_keys = true;
+        self
+    }
+
+    /// Use input `default` as the default value to build.
+    ///
+    /// # Behavior
+    ///
+    /// Builder will ignore any errors happened during build, and only returns
+    /// errors if no valid value collected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, from_file, from_self};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    ///     b: String,
+    ///     c: i64,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_env());
+    ///
+    ///     let t = builder.build_with(TestConfig::default())?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_with(self, default: V) -> Result<V> {
+        self.build_with_report(default).map(|(v, _)| v)
+    }
+
+    /// Build like [`Builder::build_with`] and return a [`BuildReport`]
+    /// about entries skipped by collectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_env;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     port: u16,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default().collect(from_env().skip_invalid());
+    ///
+    ///     let (t, report) = builder.build_with_report(TestConfig::default())?;
+    ///     println!("{:?}", t);
+    ///     for s in report.skipped {
+    ///         println!("{}: {} skipped: {}", s.source, s.key, s.reason);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_with_report(self, default: V) -> Result<(V, BuildReport)> {
+        let mut report = BuildReport::default();
+        let default = to_value(&default)?;
+        self.build_inner(default, false, &mut report)
+            .map(|s| (s.value, s.report))
+    }
+
+    /// Build like [`Builder::build_with`] and return a [`Snapshot`] that
+    /// bundles the typed config with the merged value, where every field
+    /// comes from and the [`BuildReport`].
+    ///
+    /// See [`Snapshot`] for examples.
+    pub fn build_snapshot(self, default: V) -> Result<Snapshot<V>> {
+        let mut report = BuildReport::default();
+        let default = to_value(&default)?;
+        self.build_inner(default, true, &mut report)
+    }
+
+    /// Build the value, sources of fields are only tracked if `track` is set.
+    ///
+    /// `report` is filled as the build goes, so it's still available if
+    /// the build failed.
+    fn build_inner(
+        mut self,
+        default: Value,
+        track: bool,
+        report: &mut BuildReport,
+    ) -> Result<Snapshot<V>> {
+        self.check_paths()?;
+        self.push_overrides(&default)?;
+        let track = track || !self.trust_policies.is_empty();
+        let mut layers = Layers::new(default, track);
+        for mut c in std::mem::take(&mut self.collectors) {
+            self.merge_layer(c.as_mut(), &mut layers, report)?;
+        }
+        let result = self.finish(&mut layers)?;
+        let Layers {
+            default,
+            value,
+            sources,
+            taints,
+            ..
+        } = layers;
+
+        let mut violations: Vec<_> = self
+            .constraints
+            .iter()
+            .filter_map(|c| c.check(&result, &value, &default))
+            .collect();
+        for (path, required) in &self.trust_policies {
+            let prefix = path.to_string();
+            violations.extend(
+                taints
+                    .iter()
+                    .filter(|(p, t)| *t < required && is_under(p, &prefix))
+                    .map(|(p, t)| format!("`{p}` can't be set by {t} source")),
+            );
+        }
+        if !violations.is_empty() {
+            return Err(Violations(violations).into());
+        }
+
+        Ok(Snapshot {
+            value: result,
+            merged: value,
+            sources,
+            taints,
+            report: std::mem::take(report),
+        })
+    }
+
+    /// Collect the layer from `c` and merge it into `layers`.
+    ///
+    /// Failed layers are skipped in lenient mode, and the merged value is
+    /// re-deserialized after every layer to keep the last valid one.
+    fn merge_layer(
+        &self,
+        c: &mut dyn Collector<V>,
+        layers: &mut Layers<V>,
+        report: &mut BuildReport,
+    ) -> Result<()> {
+        // Record version before collecting, so changes during collect
+        // will be treated as stale.
+        report.sources.extend(c.version());
+        // Three way merge here to make sure we take the last non-default
+        // value.
+        let audit = self.audit_keys.then_some(&mut *report);
+        let mut present = Vec::new();
+        let collected =
+            if self.coercions.is_empty() && self.explicit_paths.is_empty() && audit.is_none() {
+                c.collect()
+            } else {
+                collect_coerced(
+                    c,
+                    &self.coercions,
+                    (&self.explicit_paths, &mut present),
+                    audit,
+                )
+            };
+        let mut collected = match collected {
+            Ok(v) => v,
+            Err(err) if self.lenient => {
+                warn!("skip layer {} failed: {err:?}", c.describe());
+                self.error_observers.iter().for_each(|f| f(&err));
+                layers.errors.push(format!("{}: {err}", c.describe()));
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        resolve_relative(
+            &self.relative_paths,
+            &layers.default,
+            &mut collected,
+            &c.describe(),
+        );
+        report
+            .layers
+            .push(layer_stats(&layers.defaults, &collected, c.describe()));
+        let forced = take_explicit(&present, &collected);
+        merge(
+            &self.merge_config,
+            &layers.default,
+            &mut layers.value,
+            collected,
+        );
+        apply_explicit(&mut layers.value, forced);
+        report.skipped.extend(c.skipped());
+        report.sources.extend(c.included());
+        if layers.track {
+            let current = flatten(&layers.value);
+            for (path, v) in &current {
+                if layers.leaves.get(path) != Some(v) {
+                    layers.sources.insert(path.clone(), c.describe());
+                    let trust = layers.taints.entry(path.clone()).or_insert(c.trust());
+                    *trust = (*trust).min(c.trust());
+                }
+            }
+            layers.leaves = current;
+        }
+
+        debug!("got value: {:?}", layers.value);
+        // Re-deserialize the value if we from_value correctly.
+        match de::from_value::<V>(layers.value.clone()) {
+            Ok(v) => layers.result = Some(v),
+            Err(e) => {
+                warn!("deserialize value {:?}: {:?}", layers.value, e);
+                layers.last_error = Some(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Expand the merged value and return the last valid value.
+    fn finish(&self, layers: &mut Layers<V>) -> Result<V> {
+        if let Some(interpolator) = &self.interpolator {
+            interpolator.expand_value(&mut layers.value)?;
+            layers.result = Some(de::from_value(layers.value.clone())?);
+        }
+
+        if layers.result.is_none() && !layers.errors.is_empty() {
+            return Err(anyhow!(
+                "all layers failed:\n  - {}",
+                layers.errors.join("\n  - ")
+            ));
+        }
+        layers
+            .result
+            .take()
+            .ok_or_else(|| match layers.last_error.take() {
+                Some(err) => anyhow!("no valid value to deserialize: {err}"),
+                None => anyhow!("no valid value to deserialize"),
+            })
+    }
+
+    /// Explain where every field of the built value comes from.
+    ///
+    /// Like [`Builder::build_with`], all collectors will be consumed and
+    /// values are interpolated, but constraints will not be checked. It
+    /// fails if the build would fail before constraints. Values of
+    /// sensitive keys like
+    /// `password` or `token` are redacted.
+    ///
+    /// With [`Detail::ChangedOnly`], fields that equal their defaults are
+    /// hidden and only counted to keep large reports readable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::{Builder, Detail};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    ///     b: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default().collect(from_str(Toml, r#"a = "x""#));
+    ///
+    ///     let e = builder.explain(TestConfig::default(), Detail::ChangedOnly)?;
+    ///     assert_eq!(e.to_string(), "a = \"x\" (str)\n(1 fields equal to default are hidden)\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn explain(mut self, default: V, detail: Detail) -> Result<Explanation> {
+        self.check_paths()?;
+        let default = to_value(&default)?;
+        self.push_overrides(&default)?;
+        let mut layers = Layers::new(default, true);
+        let mut report = BuildReport::default();
+        for mut c in std::mem::take(&mut self.collectors) {
+            self.merge_layer(c.as_mut(), &mut layers, &mut report)?;
+        }
+        self.finish(&mut layers)?;
+        let Layers {
+            defaults,
+            value,
+            mut sources,
+            ..
+        } = layers;
+
+        let mut explanation = Explanation::default();
+        for (path, v) in flatten(&value) {
+            if detail == Detail::ChangedOnly && defaults.get(&path) == Some(&v) {
+                explanation.hidden += 1;
+                continue;
+            }
+            explanation.entries.push(ExplainEntry {
+                value: if is_sensitive(&path) {
+                    REDACTED.to_string()
+                } else {
+                    v
+                },
+                source: sources.remove(&path),
+                path,
+            });
+        }
+        Ok(explanation)
+    }
+}
+
+/// State of layers merged so far by [`Builder::merge_layer`].
+struct Layers<V> {
+    default: Value,
+    defaults: BTreeMap<String, String>,
+    value: Value,
+    /// Track sources and trust of fields if set.
+    track: bool,
+    leaves: BTreeMap<String, String>,
+    sources: BTreeMap<String, SourceDescriptor>,
+    taints: BTreeMap<String, Trust>,
+    /// Errors of layers skipped in lenient mode.
+    errors: Vec<String>,
+    last_error: Option<de::Error>,
+    /// The last valid value.
+    result: Option<V>,
+}
+
+impl<V> Layers<V> {
+    fn new(default: Value, track: bool) -> Self {
+        Self {
+            defaults: flatten(&default),
+            value: default.clone(),
+            leaves: if track {
+                flatten(&default)
+            } else {
+                BTreeMap::new()
+            },
+            default,
+            track,
+            sources: BTreeMap::new(),
+            taints: BTreeMap::new(),
+            errors: Vec::new(),
+            last_error: None,
+            result: None,
+        }
+    }
+}
+
+/// Group of collectors created by [`Builder::group`].
+pub struct Group<V: DeserializeOwned + Serialize> {
+    collectors: Vec<Box<dyn Collector<V>>>,
+}
+
+impl<V> Group<V>
+where
+    V: DeserializeOwned + Serialize,
+{
+    /// Add collectors into group.
+    pub fn collect(mut self, c: impl IntoCollector<V>) -> Self {
+        self.collectors.push(c.into_collector());
+        self
+    }
+}
+
+/// Check if flattened `path` is `prefix` itself or a field under it.
+fn is_under(path: &str, prefix: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.') || rest.starts_with('['),
+        None => false,
+    }
+}
+
+/// Resolve relative paths at `paths` of `collected` against the directory
+/// of the file it comes from.
+///
+/// Values equal to default are skipped so they won't override other layers.
+fn resolve_relative(
+    paths: &[KeyPath],
+    default: &Value,
+    collected: &mut Value,
+    source: &SourceDescriptor,
+) {
+    let file = match (source.kind(), source.location()) {
+        ("file", Some(file)) if !paths.is_empty() => file,
+        _ => return,
+    };
+    let base = expand_path(file).unwrap_or_else(|_| file.to_string());
+    let base = match Path::new(&base).parent() {
+        Some(base) => base.to_path_buf(),
+        None => return,
+    };
+
+    let resolve = |v: &mut Value| {
+        if let Value::Str(s) = v {
+            if !s.is_empty() && Path::new(s.as_str()).is_relative() {
+                *s = base.join(&*s).to_string_lossy().to_string();
+            }
+        }
+    };
+    for path in paths {
+        let d = get(default, path);
+        let v = match get_mut(collected, path) {
+            Some(v) if Some(&*v) != d => v,
+            _ => continue,
+        };
+        match v {
+            Value::Seq(vs) => vs.iter_mut().for_each(resolve),
+            Value::Some(v) => resolve(v),
+            v => resolve(v),
+        }
+    }
+}
+
+/// Count fields of `collected` that differ from or equal to `defaults`.
+fn layer_stats(
+    defaults: &BTreeMap<String, String>,
+    collected: &Value,
+    source: SourceDescriptor,
+) -> LayerStats {
+    let (mut applied, mut skipped_as_default) = (0, 0);
+    for (path, v) in flatten(collected) {
+        if defaults.get(&path) == Some(&v) {
+            skipped_as_default += 1;
+        } else {
+            applied += 1;
+        }
+    }
+    let stats = LayerStats {
+        source,
+        applied,
+        skipped_as_default,
+    };
+    if stats.is_all_default() {
+        debug!(
+            "all fields of layer {} are skipped as default",
+            stats.source
+        );
+    }
+    stats
+}
+
+/// Take values at `paths` from `collected` to be applied after merge.
+fn take_explicit(paths: &[KeyPath], collected: &Value) -> Vec<(KeyPath, Value)> {
+    paths
+        .iter()
+        .filter_map(|p| get(collected, p).map(|v| (p.clone(), v.clone())))
+        .collect()
+}
+
+/// Overwrite values at given paths regardless of defaults.
+fn apply_explicit(value: &mut Value, forced: Vec<(KeyPath, Value)>) {
+    for (path, v) in forced {
+        if let Some(old) = get_mut(value, &path) {
+            *old = v;
+        }
+    }
+}
+
+/// Collect raw value from collector and apply coercions on it.
+///
+/// Paths in `explicit` that are set by the raw value are recorded into
+/// `present`. Unknown and deprecated keys will be recorded into `audit`
+/// if given.
+fn collect_coerced<V>(
+    c: &mut dyn Collector<V>,
+    coercions: &[(KeyPath, Coercion)],
+    (explicit, present): (&[KeyPath], &mut Vec<KeyPath>),
+    audit: Option<&mut BuildReport>,
+) -> Result<Value>
+where
+    V: DeserializeOwned + Serialize,
+{
+    let mut raw = match c.collect_raw()? {
+        Some(raw) => raw,
+        None => return c.collect(),
+    };
+    present.extend(explicit.iter().filter(|p| get(&raw, p).is_some()).cloned());
+
+    for (path, f) in coercions {
+        if let Some(v) = get_mut(&mut raw, path) {
+            let old = std::mem::replace(v, Value::Unit);
+            *v = f(old).map_err(|err| anyhow!("coerce `{path}` from {}: {err}", c.describe()))?;
+        }
+    }
+
+    let report = match audit {
+        Some(report) => report,
+        None => {
+            let v: V = de::from_value(raw)
+                .map_err(|err| anyhow!("deserialize {}: {err}", c.describe()))?;
+            return to_value(&v);
+        }
+    };
+
+    let mut track = Track::default();
+    let v: V = de::from_value_tracked(raw.clone(), &mut track)
+        .map_err(|err| anyhow!("deserialize {}: {err}", c.describe()))?;
+    let typed = to_value(&v)?;
+    let deprecated = de::aliases::<V>(&raw, &typed, &track.fields);
+
+    let source = c.describe();
+    let keys = |keys: Vec<String>| {
+        keys.into_iter().map(|key| ReportedKey {
+            source: source.clone(),
+            key,
+        })
+    };
+    report.unknown_keys.extend(keys(track.ignored));
+    report.deprecated_keys.extend(keys(deprecated));
+    Ok(typed)
+}
+
+impl<V> Builder<V>
+where
+    V: DeserializeOwned + Serialize + Default + 'static,
+{
+    /// Cache the serialized `V::default()` per type, so repeated
+    /// [`Builder::build`] calls don't serialize it every time.
+    ///
+    /// Useful for small configs built on hot paths like per request
+    /// plugins. `V::default()` must return the same value every time,
+    /// since it will only be called once in the process.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct PluginConfig {
+    ///     limit: u32,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     for _ in 0..3 {
+    ///         let t: PluginConfig = Builder::default()
+    ///             .collect(from_str(Toml, "limit = 10"))
+    ///             .cache_default()
+    ///             .build()?;
+    ///         assert_eq!(t.limit, 10);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn cache_default(mut self) -> Self {
+        self.default_value = Some(default_value::<V>);
+        self
+    }
+}
+
+impl<V> Builder<V>
+where
+    V: DeserializeOwned + Serialize + Default,
+{
+    /// If input value implements `Default`, we can use `build` instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, from_file, from_self};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    ///     b: String,
+    ///     c: i64,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_env())
+    ///         .collect(from_file(Toml, "config.toml"));
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build(self) -> Result<V> {
+        let mut report = BuildReport::default();
+        let default = self.default_tree()?;
+        self.build_inner(default, false, &mut report)
+            .map(|s| s.value)
+    }
+
+    /// Build like [`Builder::build`] and return the merged [`Value`]
+    /// instead of `V`, so tools can inspect, diff or re-serialize the
+    /// effective config.
+    ///
+    /// Fields not set by any layer hold their defaults. Constraints and
+    /// trust policies are still checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_str;
+    /// use serfig::parsers::{Parser, Toml};
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder: Builder<TestConfig> =
+    ///         Builder::default().collect(from_str(Toml, "port = 8080"));
+    ///
+    ///     let v = builder.build_value()?;
+    ///     let effective = String::from_utf8(Toml.export(&v)?)?;
+    ///     assert_eq!(effective, "host = \"\"\nport = 8080\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_value(self) -> Result<Value> {
+        let mut report = BuildReport::default();
+        let default = self.default_tree()?;
+        self.build_inner(default, false, &mut report)
+            .map(|s| s.merged)
+    }
+
+    /// Value tree of `V::default()`, cached if [`Builder::cache_default`]
+    /// is set.
+    fn default_tree(&self) -> Result<Value> {
+        match self.default_value {
+            Some(f) => f(),
+            None => to_value(&V::default()),
+        }
+    }
+
+    /// Build like [`Builder::build`] after awaiting async collectors
+    /// added by [`Builder::collect_async`].
+    ///
+    /// Sync collectors are still collected on the calling thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_env, AsyncCollector, SourceDescriptor};
+    /// use serfig::{Builder, Value};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// struct Etcd;
+    ///
+    /// impl AsyncCollector<TestConfig> for Etcd {
+    ///     async fn collect(&mut self) -> Result<Value> {
+    ///         // Read keys with an async etcd client here.
+    ///         Ok(Value::Map(Default::default()))
+    ///     }
+    ///
+    ///     fn describe(&self) -> SourceDescriptor {
+    ///         SourceDescriptor::new("etcd")
+    ///     }
+    /// }
+    ///
+    /// async fn load() -> Result<TestConfig> {
+    ///     Builder::default()
+    ///         .collect_async(Etcd)
+    ///         .collect(from_env())
+    ///         .build_async()
+    ///         .await
+    /// }
+    /// ```
+    pub async fn build_async(mut self) -> Result<V> {
+        self.resolve_async().await;
+        self.build()
     }
 
-    /// Add collectors into builder.
-    ///
-    /// This is a lazy operation that no real IO happens.
+    /// Add an observer that will be called with errors
+    /// [`Builder::build_or_default`] recovered from, like layers that failed
+    /// to collect or the build error before falling back to default.
     ///
     /// # Example
     ///
     /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
     /// use serde::{Deserialize, Serialize};
-    /// use serfig::collectors::{from_env, from_file, from_self};
+    /// use serfig::collectors::from_file;
     /// use serfig::parsers::Toml;
     /// use serfig::Builder;
     ///
     /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
     /// #[serde(default)]
-    /// struct TestConfig {
-    ///     a: String,
-    ///     b: String,
-    ///     c: i64,
+    /// struct TelemetryConfig {
+    ///     endpoint: String,
     /// }
     ///
-    /// fn main() -> anyhow::Result<()> {
-    ///     let builder = Builder::default()
-    ///         .collect(from_env())
-    ///         .collect(from_file(Toml, "config.toml"))
-    ///         .collect(from_self(TestConfig::default()));
+    /// let errors = Rc::new(RefCell::new(Vec::new()));
+    /// let observed = errors.clone();
+    /// let builder = Builder::default()
+    ///     .collect(from_file(Toml, "not-exist.toml"))
+    ///     .on_error(move |err| observed.borrow_mut().push(err.to_string()));
     ///
-    ///     Ok(())
-    /// }
+    /// let t: TelemetryConfig = builder.build_or_default();
+    /// assert_eq!(t, TelemetryConfig::default());
+    /// assert_eq!(errors.borrow().len(), 2);
     /// ```
-    pub fn collect(mut self, c: impl IntoCollector<V>) -> Self {
-        self.collectors.push(c.into_collector());
-        Self {
-            collectors: self.collectors,
-        }
+    pub fn on_error(mut self, f: impl Fn(&anyhow::Error) + 'static) -> Self {
+        self.error_observers.push(Rc::new(f));
+        self
     }
 
-    /// Use input `default` as the default value to build.
+    /// Build like [`Builder::build`] but never fail.
     ///
-    /// # Behavior
+    /// Layers that failed to collect are skipped with a warning. If the
+    /// build fails anyway, like all layers failed or constraints are
+    /// violated, the error will be logged and `V::default()` returned.
     ///
-    /// Builder will ignore any errors happened during build, and only returns
-    /// errors if no valid value collected.
+    /// Errors of skipped layers and the build are also passed to observers
+    /// added by [`Builder::on_error`].
+    ///
+    /// Useful for optional configs like telemetry where starting with
+    /// defaults is better than crashing.
     ///
     /// # Example
     ///
     /// ```
     /// use serde::{Deserialize, Serialize};
-    /// use serfig::collectors::{from_env, from_file, from_self};
+    /// use serfig::collectors::from_file;
     /// use serfig::parsers::Toml;
     /// use serfig::Builder;
     ///
     /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
     /// #[serde(default)]
-    /// struct TestConfig {
-    ///     a: String,
-    ///     b: String,
-    ///     c: i64,
+    /// struct TelemetryConfig {
+    ///     endpoint: String,
     /// }
     ///
-    /// fn main() -> anyhow::Result<()> {
-    ///     let builder = Builder::default()
-    ///         .collect(from_env());
+    /// let builder = Builder::default().collect(from_file(Toml, "not-exist.toml"));
     ///
-    ///     let t = builder.build_with(TestConfig::default())?;
-    ///     Ok(())
-    /// }
+    /// let t: TelemetryConfig = builder.build_or_default();
+    /// assert_eq!(t, TelemetryConfig::default());
     /// ```
-    pub fn build_with(self, default: V) -> Result<V> {
-        let mut result = None;
-        let default = into_value(default)?;
-        let mut value = default.clone();
-        for mut c in self.collectors {
-            // Merge will default to make sure every value here is from
-            // user input.
-            let collected_value = merge_with_default(default.clone(), c.collect()?);
-
-            // Three way merge here to make sure we take the last non-default
-            // value.
-            value = merge(default.clone(), value, collected_value);
-
-            debug!("got value: {:?}", value);
-            // Re-deserialize the value if we from_value correctly.
-            result = match V::from_value(value.clone()) {
-                Ok(v) => Some(v),
-                Err(e) => {
-                    warn!("deserialize value {:?}: {:?}", value, e);
-                    continue;
-                }
+    pub fn build_or_default(mut self) -> V {
+        self.lenient = true;
+        let observers = self.error_observers.clone();
+        match self.build() {
+            Ok(v) => v,
+            Err(err) => {
+                error!("build config failed, fall back to default: {err:?}");
+                observers.iter().for_each(|f| f(&err));
+                V::default()
             }
         }
-
-        result.ok_or_else(|| anyhow!("no valid value to deserialize",))
     }
-}
 
-impl<V> Builder<V>
-where
-    V: DeserializeOwned + Serialize + Default,
-{
-    /// If input value implements `Default`, we can use `build` instead.
+    /// Check config without starting the app, like in CI before deploying.
+    ///
+    /// Keys are audited like [`Builder::audit_keys`], the result is
+    /// classified by [`CheckStatus`][crate::CheckStatus]:
+    ///
+    /// - `Errors`: the build failed, like syntax errors, missing required
+    ///   fields or violated constraints.
+    /// - `Warnings`: unknown or deprecated keys are used, or entries are
+    ///   skipped by collectors.
+    /// - `Ok`: otherwise.
     ///
     /// # Example
     ///
-    /// ```no_run
+    /// ```
     /// use serde::{Deserialize, Serialize};
-    /// use serfig::collectors::{from_env, from_file, from_self};
+    /// use serfig::collectors::from_str;
     /// use serfig::parsers::Toml;
-    /// use serfig::Builder;
+    /// use serfig::{Builder, CheckStatus};
     ///
     /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
     /// #[serde(default)]
     /// struct TestConfig {
-    ///     a: String,
-    ///     b: String,
-    ///     c: i64,
+    ///     port: u16,
     /// }
     ///
     /// fn main() -> anyhow::Result<()> {
-    ///     let builder = Builder::default()
-    ///         .collect(from_env())
-    ///         .collect(from_file(Toml, "config.toml"));
+    ///     let builder: Builder<TestConfig> =
+    ///         Builder::default().collect(from_str(Toml, "port = 8080\nhost = \"a\""));
     ///
-    ///     let t = builder.build()?;
+    ///     let check = builder.check();
+    ///     assert_eq!(check.status, CheckStatus::Warnings);
+    ///     assert_eq!(check.exit_code(), 1);
+    ///     println!("{}", check.to_json()?);
     ///     Ok(())
     /// }
     /// ```
-    pub fn build(self) -> Result<V> {
-        self.build_with(V::default())
+    pub fn check(mut self) -> BuildCheck {
+        self.audit_keys = true;
+        let mut report = BuildReport::default();
+        let errors = match self
+            .default_tree()
+            .and_then(|default| self.build_inner(default, false, &mut report))
+        {
+            Ok(s) => {
+                report = s.report;
+                Vec::new()
+            }
+            Err(err) => match err.downcast::<Violations>() {
+                Ok(v) => v.0,
+                Err(err) => vec![format!("{err:#}")],
+            },
+        };
+        BuildCheck::new(
+            errors,
+            report.unknown_keys,
+            report.deprecated_keys,
+            report.skipped,
+        )
     }
 }
 
@@ -162,6 +1819,7 @@ mod tests {
     use super::*;
     use crate::collectors::*;
     use crate::parsers::Toml;
+    use crate::CheckStatus;
 
     #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
     #[serde(default)]
@@ -170,6 +1828,471 @@ mod tests {
         test_b: String,
     }
 
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigTls {
+        min: i64,
+        max: i64,
+        cert: String,
+        key: String,
+        socket: String,
+        port: u16,
+    }
+
+    #[test]
+    fn test_build_constraints() -> Result<()> {
+        let cfg = Builder::default()
+            .collect(from_str(
+                Toml,
+                r#"
+min = 10
+cert = "cert"
+socket = "/tmp/test.sock"
+port = 8080
+"#,
+            ))
+            .constraint(|c: &TestConfigTls| c.min <= c.max, "min must be <= max")
+            .requires("cert", "key")
+            .conflicts("socket", "port");
+
+        let err = cfg.build().expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            r#"config constraints violated:
+  - min must be <= max
+  - `cert` requires `key` to be set
+  - `socket` conflicts with `port`"#
+        );
+
+        let cfg = Builder::default()
+            .collect(from_str(Toml, r#"cert = "cert""#))
+            .collect(from_str(Toml, r#"key = "key""#))
+            .requires("cert", "key")
+            .conflicts("socket", "port");
+        let t: TestConfigTls = cfg.build()?;
+        assert_eq!(t.key, "key");
+
+        Ok(())
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigLimits {
+        max_memory: u64,
+        max_conns: u32,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigCoerce {
+        name: String,
+        limits: TestConfigLimits,
+    }
+
+    #[test]
+    fn test_build_coerce() -> Result<()> {
+        let cfg = Builder::default()
+            .collect(from_str(
+                Toml,
+                r#"
+name = "serfig"
+[limits]
+max_memory = "2KiB"
+max_conns = 10
+"#,
+            ))
+            .collect(from_self(TestConfigCoerce {
+                limits: TestConfigLimits {
+                    max_conns: 20,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }))
+            .coerce("limits.max_memory", |v| match v {
+                Value::Str(s) => {
+                    let n: u64 = s
+                        .strip_suffix("KiB")
+                        .ok_or_else(|| anyhow!("invalid size {s}"))?
+                        .parse()?;
+                    Ok(Value::U64(n * 1024))
+                }
+                v => Ok(v),
+            })
+            .coerce("not.exist", |_| Err(anyhow!("must not be called")));
+
+        let t: TestConfigCoerce = cfg.build()?;
+        assert_eq!(
+            t,
+            TestConfigCoerce {
+                name: "serfig".to_string(),
+                limits: TestConfigLimits {
+                    max_memory: 2048,
+                    max_conns: 20,
+                },
+            }
+        );
+
+        let cfg = Builder::default()
+            .collect(from_str(Toml, "[limits]\nmax_memory = \"2MiB\""))
+            .coerce("limits.max_memory", |v| match v {
+                Value::Str(s) => Err(anyhow!("invalid size {s}")),
+                v => Ok(v),
+            });
+        let err = cfg
+            .build_with(TestConfigCoerce::default())
+            .expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            "coerce `limits.max_memory` from str: invalid size 2MiB"
+        );
+
+        let cfg = Builder::default()
+            .collect(from_str(Toml, "[limits]\nmax_memory = 1"))
+            .coerce("limits..max_memory", Ok);
+        let err = cfg
+            .build_with(TestConfigCoerce::default())
+            .expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            "invalid key path `limits..max_memory`: key is empty"
+        );
+
+        Ok(())
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigPaths {
+        cert: String,
+        data_dirs: Vec<String>,
+        key: Option<String>,
+    }
+
+    #[test]
+    fn test_build_resolve_paths() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("serfig-resolve-paths-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+data_dirs = ["data", "/var/lib/app"]
+key = "key.pem"
+"#,
+        )?;
+
+        let t: TestConfigPaths = Builder::default()
+            .collect(from_str(Toml, r#"cert = "cert.pem""#))
+            .collect(from_file(Toml, path.to_str().unwrap()))
+            .resolve_paths("cert")
+            .resolve_paths("data_dirs")
+            .resolve_paths("key")
+            .build()?;
+        assert_eq!(
+            t,
+            TestConfigPaths {
+                cert: "cert.pem".to_string(),
+                data_dirs: vec![
+                    dir.join("data").to_string_lossy().to_string(),
+                    "/var/lib/app".to_string()
+                ],
+                key: Some(dir.join("key.pem").to_string_lossy().to_string()),
+            }
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigTimeout {
+        timeout_secs: u64,
+        idle_timeout_secs: Option<u64>,
+        name: String,
+    }
+
+    #[test]
+    fn test_build_explicit() -> Result<()> {
+        let builder = || {
+            Builder::default()
+                .collect(from_str(Toml, "timeout_secs = 30\nidle_timeout_secs = 30"))
+                .collect(from_str(Toml, "timeout_secs = 0\nidle_timeout_secs = 0"))
+                .collect(from_str(Toml, r#"name = "a""#))
+        };
+
+        // Zero equals to default and is taken as unset, but `Some(0)` isn't.
+        let t: TestConfigTimeout = builder().build()?;
+        assert_eq!(t.timeout_secs, 30);
+        assert_eq!(t.idle_timeout_secs, Some(0));
+
+        let t: TestConfigTimeout = builder().explicit("timeout_secs").build()?;
+        assert_eq!(
+            t,
+            TestConfigTimeout {
+                timeout_secs: 0,
+                idle_timeout_secs: Some(0),
+                name: "a".to_string(),
+            }
+        );
+
+        let e = builder()
+            .explicit("timeout_secs")
+            .explain(TestConfigTimeout::default(), Detail::Full)?;
+        assert!(e.to_string().contains("timeout_secs = 0"), "{e}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() -> Result<()> {
+        let cfg = || {
+            Builder::default()
+                .collect(from_str(Toml, r#"cert = "cert""#))
+                .collect(from_str(Toml, "port = 8080"))
+                .collect(from_self(TestConfigTls {
+                    key: "secret".to_string(),
+                    port: 9090,
+                    ..Default::default()
+                }))
+        };
+
+        let e = cfg().explain(TestConfigTls::default(), Detail::ChangedOnly)?;
+        assert_eq!(e.hidden, 3);
+        assert_eq!(
+            e.to_string(),
+            r#"cert = "cert" (str)
+key = "secret" (self)
+port = 9090 (self)
+(3 fields equal to default are hidden)
+"#
+        );
+
+        let e = cfg().explain(TestConfigTls::default(), Detail::Full)?;
+        assert_eq!(e.hidden, 0);
+        assert_eq!(e.entries.len(), 6);
+        assert_eq!(e.entries[2].path, "max");
+        assert_eq!(e.entries[2].source, None);
+
+        let e = Builder::default()
+            .collect(from_str(
+                Toml,
+                r#"cert = "${env:SERFIG_EXPLAIN_CERT:-/etc/cert}""#,
+            ))
+            .interpolate(Interpolator::default())
+            .explain(TestConfigTls::default(), Detail::ChangedOnly)?;
+        assert_eq!(
+            e.to_string(),
+            "cert = \"/etc/cert\" (str)\n(5 fields equal to default are hidden)\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sources() {
+        let cfg: Builder<TestConfig> = Builder::default()
+            .collect(from_file(Toml, "config.toml"))
+            .collect(from_str(Toml, ""))
+            .collect(from_env())
+            .collect(from_self(TestConfig::default()));
+
+        assert_eq!(
+            cfg.sources(),
+            vec![
+                SourceDescriptor::new("file").with_location("config.toml"),
+                SourceDescriptor::new("str"),
+                SourceDescriptor::new("env"),
+                SourceDescriptor::new("self"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_async() {
+        use std::future::Future;
+        use std::pin::pin;
+        use std::task::{Context, Poll, Waker};
+
+        struct Remote(&'static str);
+
+        impl AsyncCollector<TestConfig> for Remote {
+            async fn collect(&mut self) -> Result<Value> {
+                crate::value::to_value(&BTreeMap::from([("test_b", self.0)]))
+            }
+
+            fn describe(&self) -> SourceDescriptor {
+                SourceDescriptor::new("remote")
+            }
+        }
+
+        fn block_on<F: Future>(f: F) -> F::Output {
+            let mut f = pin!(f);
+            let mut cx = Context::from_waker(Waker::noop());
+            loop {
+                if let Poll::Ready(v) = f.as_mut().poll(&mut cx) {
+                    return v;
+                }
+            }
+        }
+
+        let t: TestConfig = block_on(
+            Builder::default()
+                .collect(from_str(Toml, "test_a = \"a\"\ntest_b = \"b\""))
+                .collect_async(Remote("remote"))
+                .build_async(),
+        )
+        .expect("must success");
+        assert_eq!(t.test_a, "a");
+        assert_eq!(t.test_b, "remote");
+
+        let err = Builder::<TestConfig>::default()
+            .collect_async(Remote("remote"))
+            .build()
+            .expect_err("must fail");
+        assert!(format!("{err:#}").contains("build_async"), "{err:#}");
+    }
+
+    #[test]
+    fn test_set() -> Result<()> {
+        let builder = Builder::default()
+            .set("port", 0)
+            .set("cert", "override.pem")
+            .collect(from_str(Toml, "port = 8080\ncert = \"a.pem\"\nmin = 1"));
+        assert_eq!(
+            builder
+                .describe()
+                .sources
+                .last()
+                .map(|s| s.source.to_string()),
+            Some("override".to_string())
+        );
+        let (t, report): (TestConfigTls, _) = builder
+            .build_snapshot(TestConfigTls::default())
+            .map(|s| (s.value, s.report))?;
+        // Values equal to default still override.
+        assert_eq!(t.port, 0);
+        assert_eq!(t.cert, "override.pem");
+        assert_eq!(t.min, 1);
+        assert_eq!(report.layers.len(), 2);
+
+        let err = Builder::<TestConfigTls>::default()
+            .set("servers[0].port", 1)
+            .build()
+            .expect_err("must fail");
+        assert!(err.to_string().contains("sequence indexes"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_str() -> Result<()> {
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+        enum Level {
+            #[default]
+            Info,
+            Debug,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+        #[serde(default)]
+        struct TestSetStr {
+            port: u16,
+            name: String,
+            hosts: Vec<String>,
+            timeout: Option<u64>,
+            level: Level,
+            ratio: f64,
+        }
+
+        let t: TestSetStr = Builder::default()
+            .collect(from_str(Toml, "port = 80\nname = \"a\""))
+            .set_str("port", "8080")
+            .set_str("name", "123")
+            .set_str("hosts", "a, b")
+            .set_str("timeout", "30")
+            .set_str("level", "Debug")
+            .set_str("ratio", "0.5")
+            .build()?;
+        assert_eq!(
+            t,
+            TestSetStr {
+                port: 8080,
+                name: "123".to_string(),
+                hosts: vec!["a".to_string(), "b".to_string()],
+                timeout: Some(30),
+                level: Level::Debug,
+                ratio: 0.5,
+            }
+        );
+
+        let err = Builder::<TestSetStr>::default()
+            .set_str("port", "http")
+            .build()
+            .expect_err("must fail");
+        assert!(
+            err.to_string().starts_with("set `port` to \"http\": "),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_value() -> Result<()> {
+        let v = Builder::<TestConfigTls>::default()
+            .collect(from_str(Toml, "cert = \"a.pem\""))
+            .set("port", 8080)
+            .build_value()?;
+        let get = |path: &str| get(&v, &path.parse().expect("must be valid path")).cloned();
+        assert_eq!(get("cert"), Some(Value::Str("a.pem".to_string())));
+        assert_eq!(get("port"), Some(Value::U16(8080)));
+        assert_eq!(get("min"), Some(Value::I64(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe() {
+        let cfg: Builder<TestConfigTls> = Builder::default()
+            .collect(from_file(Toml, "config.toml"))
+            .collect_with_trust(from_env(), Trust::System)
+            .with_merge_config(MergeConfig::default().fold_case(true))
+            .range("port", 1..=65535)
+            .requires("cert", "key")
+            .require_trust("cert", Trust::System)
+            .explicit("port");
+
+        let plan = cfg.describe();
+        assert_eq!(
+            plan.sources,
+            vec![
+                PlannedSource {
+                    source: SourceDescriptor::new("file").with_location("config.toml"),
+                    trust: Trust::User,
+                },
+                PlannedSource {
+                    source: SourceDescriptor::new("env"),
+                    trust: Trust::System,
+                },
+            ]
+        );
+        assert_eq!(plan.rules.len(), 1);
+        assert_eq!(plan.constraints, 2);
+        assert_eq!(plan.explicit_paths, vec!["port".to_string()]);
+        assert_eq!(
+            plan.trust_policies,
+            vec![TrustPolicy {
+                path: "cert".to_string(),
+                trust: Trust::System,
+            }]
+        );
+
+        let rendered = plan.to_string();
+        assert!(rendered.contains("1. file: config.toml (user)"));
+        assert!(rendered.contains("merge: strategy=deep seq_policy=replace fold_case"));
+        assert!(plan
+            .to_json()
+            .expect("json")
+            .contains("\"fold_case\": true"));
+    }
+
     #[test]
     fn test_build() -> Result<()> {
         temp_env::with_vars(
@@ -311,6 +2434,41 @@ mod tests {
         Ok(())
     }
 
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(rename_all = "lowercase")]
+    enum TestLogLevel {
+        Debug,
+        #[default]
+        Info,
+        Warn,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigEnum {
+        level: TestLogLevel,
+        test_a: String,
+    }
+
+    #[test]
+    fn test_layered_build_enum() -> Result<()> {
+        // Unit variants are compared with the variant from `V::default()`,
+        // so later layers that leave the enum unset don't reset it.
+        let t: TestConfigEnum = Builder::default()
+            .collect(from_str(Toml, r#"level = "debug""#))
+            .collect(from_str(Toml, r#"test_a = "a""#))
+            .build()?;
+        assert_eq!(t.level, TestLogLevel::Debug);
+        assert_eq!(t.test_a, "a");
+
+        let t: TestConfigEnum = Builder::default()
+            .collect(from_str(Toml, r#"level = "debug""#))
+            .collect(from_str(Toml, r#"level = "warn""#))
+            .build()?;
+        assert_eq!(t.level, TestLogLevel::Warn);
+        Ok(())
+    }
+
     #[derive(Debug, Serialize, Default, Deserialize, PartialEq)]
     #[serde(default)]
     struct TestConfigVec {
@@ -364,4 +2522,186 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default, rename_all = "camelCase")]
+    struct TestConfigCamel {
+        from_toml: String,
+        from_jsonc: String,
+        from_dot_env: String,
+        from_env_map: String,
+        from_kv: String,
+        nested_server: TestConfigCamelServer,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default, rename_all = "camelCase")]
+    struct TestConfigCamelServer {
+        listen_port: u16,
+    }
+
+    #[test]
+    fn test_layered_rename_all() -> Result<()> {
+        let _ = env_logger::try_init();
+
+        let cfg = Builder::default()
+            .collect(from_str(
+                Toml,
+                "fromToml = \"toml\"\n[nestedServer]\nlistenPort = 80",
+            ))
+            .collect(from_str(crate::parsers::Jsonc, r#"{"fromJsonc": "jsonc"}"#))
+            .collect(from_str(crate::parsers::DotEnv, "FROM_DOT_ENV=dotenv"))
+            .collect(from_env_map(
+                [("FROM_ENV_MAP".to_string(), "env".to_string())].into(),
+            ))
+            .collect(from_kv(
+                std::collections::BTreeMap::from([("fromKv".to_string(), "kv".to_string())]),
+                "",
+            ))
+            .collect(from_self(TestConfigCamel::default()));
+        let t: TestConfigCamel = cfg.build()?;
+
+        assert_eq!(
+            t,
+            TestConfigCamel {
+                from_toml: "toml".to_string(),
+                from_jsonc: "jsonc".to_string(),
+                from_dot_env: "dotenv".to_string(),
+                from_env_map: "env".to_string(),
+                from_kv: "kv".to_string(),
+                nested_server: TestConfigCamelServer { listen_port: 80 },
+            }
+        );
+        Ok(())
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigExec {
+        name: String,
+        exec_path: String,
+    }
+
+    #[test]
+    fn test_build_trust() -> Result<()> {
+        let builder = || {
+            Builder::default()
+                .collect(from_str(Toml, r#"exec_path = "/bin/true""#))
+                .collect_with_trust(from_str(Toml, r#"name = "remote""#), Trust::Remote)
+        };
+
+        let s = builder()
+            .require_trust("exec_path", Trust::User)
+            .build_snapshot(TestConfigExec::default())?;
+        assert_eq!(s.trust("exec_path"), Some(Trust::User));
+        assert_eq!(s.trust("name"), Some(Trust::Remote));
+
+        let err = builder()
+            .collect_with_trust(from_str(Toml, r#"exec_path = "/bin/false""#), Trust::Remote)
+            .collect(from_str(Toml, r#"exec_path = "/bin/true""#))
+            .require_trust("exec_path", Trust::User)
+            .build()
+            .expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            "config constraints violated:\n  - `exec_path` can't be set by remote source"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_layer_stats() -> Result<()> {
+        let (_, report) = Builder::default()
+            .collect(from_str(Toml, r#"test_a = "a""#))
+            .collect(from_self(TestConfig::default()))
+            .build_with_report(TestConfig::default())?;
+
+        let stats: Vec<_> = report
+            .layers
+            .iter()
+            .map(|l| (l.source.kind().to_string(), l.applied, l.skipped_as_default))
+            .collect();
+        assert_eq!(
+            stats,
+            vec![("str".to_string(), 1, 1), ("self".to_string(), 0, 2)]
+        );
+        assert!(!report.layers[0].is_all_default());
+        assert!(report.layers[1].is_all_default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_or_default() {
+        let errors = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let observed = errors.clone();
+        let t: TestConfig = Builder::default()
+            .collect(from_file(Toml, "/not/exist/serfig.toml"))
+            .collect(from_str(Toml, r#"test_a = "a""#))
+            .on_error(move |err| observed.borrow_mut().push(err.to_string()))
+            .build_or_default();
+        assert_eq!(t.test_a, "a");
+        assert_eq!(errors.borrow().len(), 1);
+
+        let observed = errors.clone();
+        let t: TestConfig = Builder::default()
+            .collect(from_file(Toml, "/not/exist/serfig.toml"))
+            .on_error(move |err| observed.borrow_mut().push(err.to_string()))
+            .build_or_default();
+        assert_eq!(t, TestConfig::default());
+        assert_eq!(errors.borrow().len(), 3);
+        assert!(
+            errors.borrow()[2].contains("all layers failed"),
+            "{errors:?}"
+        );
+    }
+
+    #[test]
+    fn test_build_group() -> Result<()> {
+        let s = Builder::default()
+            .collect(from_str(Toml, r#"test_a = "a""#))
+            .group("site overrides", |g| {
+                g.collect(from_str(Toml, r#"test_b = "b""#))
+            })
+            .build_snapshot(TestConfig::default())?;
+        assert_eq!(
+            s.source("test_b").map(|s| s.to_string()),
+            Some("site overrides / str".to_string())
+        );
+        assert_eq!(
+            s.source("test_a").map(|s| s.to_string()),
+            Some("str".to_string())
+        );
+
+        let err = Builder::<TestConfig>::default()
+            .group("site overrides", |g| {
+                g.collect(from_file(Toml, "/not/exist/serfig.toml"))
+            })
+            .build()
+            .expect_err("must fail");
+        assert!(err.to_string().starts_with("group `site overrides`: "));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check() {
+        let check = Builder::<TestConfig>::default()
+            .collect(from_str(Toml, r#"test_a = "a""#))
+            .check();
+        assert_eq!(check.status, CheckStatus::Ok);
+
+        let check = Builder::<TestConfig>::default()
+            .collect(from_str(Toml, r#"test_c = "c""#))
+            .check();
+        assert_eq!(check.status, CheckStatus::Warnings);
+        assert_eq!(check.unknown_keys[0].key, "test_c");
+
+        let check = Builder::<TestConfigTls>::default()
+            .collect(from_str(Toml, r#"cert = "cert""#))
+            .requires("cert", "key")
+            .range("port", 1..)
+            .check();
+        assert_eq!(check.status, CheckStatus::Errors);
+        assert_eq!(check.exit_code(), 2);
+        assert_eq!(check.errors.len(), 2);
+    }
 }
@@ -0,0 +1,229 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::Skipped;
+use crate::de::{self, Track};
+use crate::report::ReportedKey;
+use crate::value::to_value;
+use crate::Parser;
+
+/// Report returned by [`check_file`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CheckReport {
+    /// Paths of keys that don't exist in the config type.
+    pub unknown_fields: Vec<String>,
+    /// Paths of keys that only accepted via `#[serde(alias)]`.
+    pub deprecated_fields: Vec<String>,
+    /// Errors like syntax errors and type mismatches.
+    pub errors: Vec<String>,
+}
+
+impl CheckReport {
+    /// Returns `true` if the file doesn't contain any problems.
+    pub fn is_ok(&self) -> bool {
+        self.unknown_fields.is_empty()
+            && self.deprecated_fields.is_empty()
+            && self.errors.is_empty()
+    }
+}
+
+/// Check a single config file against config type `V` without building.
+///
+/// IO errors will be returned directly, while problems inside the file
+/// will be reported via [`CheckReport`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let report = serfig::check_file::<TestConfig, _>(Toml, "config.toml")?;
+///     assert!(report.is_ok(), "{report:?}");
+///     Ok(())
+/// }
+/// ```
+pub fn check_file<V, P>(mut parser: P, path: impl AsRef<Path>) -> Result<CheckReport>
+where
+    V: DeserializeOwned + Serialize,
+    P: Parser,
+{
+    let bs = fs::read(path)?;
+    let mut report = CheckReport::default();
+
+    let raw: Value = match parser.parse(&bs) {
+        Ok(v) => v,
+        Err(err) => {
+            report.errors.push(err.to_string());
+            return Ok(report);
+        }
+    };
+
+    let mut track = Track::default();
+    match de::from_value_tracked::<V>(raw.clone(), &mut track) {
+        Ok(v) => {
+            let typed = to_value(&v)?;
+            report.deprecated_fields = de::aliases::<V>(&raw, &typed, &track.fields);
+        }
+        Err(err) => report.errors.push(err.to_string()),
+    }
+    report.unknown_fields = track.ignored;
+
+    Ok(report)
+}
+
+/// Overall result of [`Builder::check`][crate::Builder::check].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Config is valid and clean.
+    Ok,
+    /// Config can be built, but has unknown, deprecated or skipped keys.
+    Warnings,
+    /// Config can't be built or violates constraints.
+    Errors,
+}
+
+impl CheckStatus {
+    /// Exit code for CLIs: `0` for ok, `1` for warnings and `2` for errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Warnings => 1,
+            CheckStatus::Errors => 2,
+        }
+    }
+}
+
+/// Report returned by [`Builder::check`][crate::Builder::check].
+///
+/// The JSON rendered by [`BuildCheck::to_json`] is stable, so CI jobs can
+/// gate deployments on it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildCheck {
+    /// Overall status.
+    pub status: CheckStatus,
+    /// Build errors like syntax errors, missing required fields and
+    /// constraint violations, one per entry.
+    pub errors: Vec<String>,
+    /// Keys that don't exist in the config type.
+    pub unknown_keys: Vec<ReportedKey>,
+    /// Keys only accepted via `#[serde(alias)]`.
+    pub deprecated_keys: Vec<ReportedKey>,
+    /// Input entries skipped by collectors.
+    pub skipped: Vec<Skipped>,
+}
+
+impl BuildCheck {
+    pub(crate) fn new(
+        errors: Vec<String>,
+        unknown_keys: Vec<ReportedKey>,
+        deprecated_keys: Vec<ReportedKey>,
+        skipped: Vec<Skipped>,
+    ) -> Self {
+        let status = if !errors.is_empty() {
+            CheckStatus::Errors
+        } else if unknown_keys.is_empty() && deprecated_keys.is_empty() && skipped.is_empty() {
+            CheckStatus::Ok
+        } else {
+            CheckStatus::Warnings
+        };
+        Self {
+            status,
+            errors,
+            unknown_keys,
+            deprecated_keys,
+            skipped,
+        }
+    }
+
+    /// Exit code of [`BuildCheck::status`], see [`CheckStatus::exit_code`].
+    pub fn exit_code(&self) -> i32 {
+        self.status.exit_code()
+    }
+
+    /// Render check result as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        #[serde(alias = "address")]
+        addr: String,
+        port: u16,
+        tls: TestConfigTls,
+        #[serde(skip_serializing)]
+        token: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        proxy: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    #[serde(default)]
+    struct TestConfigTls {
+        enabled: bool,
+    }
+
+    fn check(name: &str, content: &str) -> CheckReport {
+        let path = std::env::temp_dir().join(format!("serfig-check-{name}.toml"));
+        fs::write(&path, content).expect("write temp file");
+        let report = check_file::<TestConfig, _>(Toml, &path).expect("check file");
+        fs::remove_file(&path).expect("remove temp file");
+        report
+    }
+
+    #[test]
+    fn test_check_file() {
+        let report = check(
+            "full",
+            r#"
+address = "127.0.0.1"
+port = 8080
+unknown = 1
+
+[tls]
+enabled = true
+cert = "/tmp/cert"
+"#,
+        );
+        assert_eq!(report.unknown_fields, vec!["unknown", "tls.cert"]);
+        assert_eq!(report.deprecated_fields, vec!["address"]);
+        assert!(report.errors.is_empty());
+
+        let report = check("mismatch", "port = 100000");
+        assert_eq!(
+            report.errors,
+            vec!["integer out of range for u16 at `port`"]
+        );
+
+        let report = check("syntax", "port = ");
+        assert_eq!(report.errors.len(), 1);
+
+        assert!(check("ok", "addr = \"localhost\"").is_ok());
+
+        // Fields skipped by `Serialize` are still known keys.
+        assert!(check("skipped", "token = \"secret\"\nproxy = \"\"").is_ok());
+    }
+}
@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{SourceDescriptor, Trust};
+use crate::value::to_value;
+use crate::{de, Collector};
+
+/// AsyncCollector collects a layer with async IO, like HTTP, etcd or
+/// vault clients running on tokio.
+///
+/// Add it via [`Builder::collect_async`][crate::Builder::collect_async]
+/// and build with [`Builder::build_async`][crate::Builder::build_async].
+/// Async collectors are awaited in order before other layers are
+/// collected, and merged at the position they were added.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use serfig::collectors::{AsyncCollector, SourceDescriptor};
+/// use serfig::Value;
+///
+/// struct Remote {
+///     url: String,
+/// }
+///
+/// impl<V: serde::de::DeserializeOwned + serde::Serialize> AsyncCollector<V> for Remote {
+///     async fn collect(&mut self) -> Result<Value> {
+///         // Fetch `self.url` with an async client here.
+///         Ok(Value::Map(Default::default()))
+///     }
+///
+///     fn describe(&self) -> SourceDescriptor {
+///         SourceDescriptor::new("remote").with_location(&self.url)
+///     }
+/// }
+/// ```
+pub trait AsyncCollector<V: DeserializeOwned + Serialize> {
+    /// Collect the layer, the value will be deserialized into `V` so
+    /// partial values like maps with only some keys are allowed.
+    fn collect(&mut self) -> impl Future<Output = Result<Value>>;
+
+    /// Describe the source for error messages and reports.
+    fn describe(&self) -> SourceDescriptor;
+
+    /// How much values from this source are trusted.
+    fn trust(&self) -> Trust {
+        Trust::Remote
+    }
+}
+
+/// Object safe version of [`AsyncCollector`].
+pub(crate) trait DynAsyncCollector {
+    fn collect_boxed(&mut self) -> Pin<Box<dyn Future<Output = Result<Value>> + '_>>;
+}
+
+/// Wrap an [`AsyncCollector`] so it can be stored along with others.
+struct Boxed<V, C> {
+    phantom: std::marker::PhantomData<V>,
+    inner: C,
+}
+
+impl<V, C> DynAsyncCollector for Boxed<V, C>
+where
+    V: DeserializeOwned + Serialize,
+    C: AsyncCollector<V>,
+{
+    fn collect_boxed(&mut self) -> Pin<Box<dyn Future<Output = Result<Value>> + '_>> {
+        Box::pin(self.inner.collect())
+    }
+}
+
+/// Slot filled by the async collector before build.
+pub(crate) type Slot = Rc<RefCell<Option<Result<Value>>>>;
+
+/// Split `c` into the async part to be awaited and the placeholder layer
+/// that reads its result.
+pub(crate) fn defer<V, C>(c: C) -> (Box<dyn DynAsyncCollector>, Slot, Deferred)
+where
+    V: DeserializeOwned + Serialize + 'static,
+    C: AsyncCollector<V> + 'static,
+{
+    let slot = Slot::default();
+    let deferred = Deferred {
+        slot: slot.clone(),
+        source: c.describe(),
+        trust: c.trust(),
+    };
+    let c = Boxed {
+        phantom: std::marker::PhantomData,
+        inner: c,
+    };
+    (Box::new(c), slot, deferred)
+}
+
+/// Placeholder layer of an [`AsyncCollector`].
+pub(crate) struct Deferred {
+    slot: Slot,
+    source: SourceDescriptor,
+    trust: Trust,
+}
+
+impl Deferred {
+    fn value(&self) -> Result<Value> {
+        match self.slot.borrow_mut().take() {
+            Some(v) => v,
+            None => Err(anyhow!(
+                "async source {} can only be collected by build_async",
+                self.source
+            )),
+        }
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> Collector<V> for Deferred {
+    fn collect(&mut self) -> Result<Value> {
+        let v: V = de::from_value(self.value()?)?;
+        to_value(&v)
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        self.value().map(Some)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.source.clone()
+    }
+
+    fn trust(&self) -> Trust {
+        self.trust
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt::Debug;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{
+    IntoCollector, Skipped, SourceDescriptor, SourceVersion, Trust,
+};
+use crate::value::{from_json, to_value};
+use crate::{de, Collector};
+
+/// Persist the last layer collected by `c` to `path`, and fall back to it
+/// when `c` fails, like a remote config service during an outage.
+///
+/// The cache is written as JSON after every successful collect, with
+/// mode `0600` on unix since layers may contain secrets. Falling back is
+/// logged as a warning, and the original error is returned if there is
+/// no usable cache.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{cached, from_file};
+/// use serfig::parsers::Toml;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(cached(
+///         // Shared config on a network mount.
+///         from_file(Toml, "/mnt/shared/myapp/config.toml"),
+///         "/var/cache/myapp/shared.json",
+///     ));
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub fn cached<V>(c: impl IntoCollector<V>, path: impl AsRef<Path>) -> Cached<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    Cached {
+        inner: c.into_collector(),
+        path: path.as_ref().to_path_buf(),
+    }
+}
+
+/// Collector that caches the layer of another collector on disk.
+///
+/// Created by [`cached`].
+pub struct Cached<V: DeserializeOwned + Serialize + Debug> {
+    inner: Box<dyn Collector<V>>,
+    path: PathBuf,
+}
+
+impl<V> Cached<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    /// Write `v` into the cache, failures are only logged since the
+    /// layer itself is fine.
+    fn store(&self, v: &Value) {
+        let write = || -> Result<()> {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let tmp = self.path.with_extension("tmp");
+            let mut opts = fs::File::options();
+            opts.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                opts.mode(0o600);
+            }
+            let mut f = opts.open(&tmp)?;
+            f.write_all(&serde_json::to_vec(v)?)?;
+            f.sync_all()?;
+            // Rename to replace the cache atomically, so a crash while
+            // writing never leaves a truncated cache.
+            fs::rename(&tmp, &self.path)?;
+            Ok(())
+        };
+        match write() {
+            Ok(()) => debug!("cached {} at {}", self.describe(), self.path.display()),
+            Err(err) => warn!(
+                "cache {} at {} failed: {err}",
+                self.describe(),
+                self.path.display()
+            ),
+        }
+    }
+
+    /// Load the cache or return `err` if it can't be used.
+    fn fallback(&self, err: anyhow::Error) -> Result<Value> {
+        let load = || -> Result<Value> {
+            let v: serde_json::Value = serde_json::from_slice(&fs::read(&self.path)?)?;
+            Ok(from_json(v))
+        };
+        match load() {
+            Ok(v) => {
+                warn!(
+                    "collect {} failed: {err:#}, fall back to cache {}",
+                    self.describe(),
+                    self.path.display()
+                );
+                Ok(v)
+            }
+            Err(cache_err) => Err(err.context(anyhow!(
+                "no usable cache at {}: {cache_err}",
+                self.path.display()
+            ))),
+        }
+    }
+}
+
+impl<V> Collector<V> for Cached<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        match self.inner.collect() {
+            Ok(v) => {
+                self.store(&v);
+                Ok(v)
+            }
+            Err(err) => {
+                let v: V = de::from_value(self.fallback(err)?)?;
+                to_value(&v)
+            }
+        }
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        match self.inner.collect_raw() {
+            Ok(Some(v)) => {
+                self.store(&v);
+                Ok(Some(v))
+            }
+            Ok(None) => Ok(None),
+            Err(err) => self.fallback(err).map(Some),
+        }
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.inner.describe()
+    }
+
+    fn skipped(&mut self) -> Vec<Skipped> {
+        self.inner.skipped()
+    }
+
+    fn version(&self) -> Option<SourceVersion> {
+        self.inner.version()
+    }
+
+    fn included(&mut self) -> Vec<SourceVersion> {
+        self.inner.included()
+    }
+
+    fn trust(&self) -> Trust {
+        self.inner.trust()
+    }
+}
+
+impl<V> IntoCollector<V> for Cached<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+    use crate::collectors::from_fn;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        workers: usize,
+        name: String,
+    }
+
+    #[test]
+    fn test_cached() {
+        let dir = std::env::temp_dir().join("serfig-cached");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("remote.json");
+
+        let mut calls = 0;
+        let mut c = cached(
+            from_fn(move || {
+                calls += 1;
+                if calls > 1 {
+                    return Err(anyhow!("service unavailable"));
+                }
+                Ok(TestConfig {
+                    workers: 8,
+                    name: "remote".to_string(),
+                })
+            }),
+            &path,
+        );
+
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.workers, 8);
+        assert!(path.exists());
+
+        // Source is down, fall back to the cache.
+        let t = TestConfig::from_value(c.collect().expect("must fall back")).expect("from value");
+        assert_eq!(t.workers, 8);
+        assert_eq!(t.name, "remote");
+
+        // No cache, the original error is returned.
+        fs::remove_file(&path).expect("must remove");
+        let err = c.collect().expect_err("must fail");
+        assert!(
+            format!("{err:#}").contains("service unavailable"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor};
+use crate::value::to_value;
+use crate::{snake, Collector};
+
+/// Load config from command line arguments without clap.
+///
+/// Arguments like `--key=value` and `--section.key value` map to nested
+/// fields, `-` in keys is replaced by `_`. An argument without value like
+/// `--verbose` is treated as `true`, repeated keys map to a sequence.
+/// Positional arguments are ignored and `--` ends parsing.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_args, from_env};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     // Run like `app --workers=8`.
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_args());
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn from_args<V>() -> Cli<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    Cli {
+        phantom: PhantomData,
+        pairs: parse_args(std::env::args().skip(1)),
+        invalid: Vec::new(),
+    }
+}
+
+/// Load config from `key=value` pairs like `docker run -e`, handy for
+/// `--set` style overrides collected by the application.
+///
+/// Keys like `server.port` map to nested fields, `-` in keys is replaced
+/// by `_`. Values are parsed like env values, comma separated values map
+/// to a sequence. Later pairs override earlier ones with the same key,
+/// and pairs without `=` fail the collecting.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_pairs};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     server: Server,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     // Collected by the application like `app --set server.port=8080`.
+///     let overrides = vec!["server.port=8080"];
+///
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_pairs(overrides));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.server.port, 8080);
+///     Ok(())
+/// }
+/// ```
+pub fn from_pairs<V>(pairs: impl IntoIterator<Item = impl AsRef<str>>) -> Cli<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    let mut parsed: Vec<(String, String)> = Vec::new();
+    let mut invalid = Vec::new();
+    for pair in pairs {
+        let Some((key, value)) = pair.as_ref().split_once('=') else {
+            invalid.push(pair.as_ref().to_string());
+            continue;
+        };
+        let key = snake::to_snake_case(key.trim()).replace('.', "_");
+        parsed.retain(|(k, _)| k != &key);
+        parsed.push((key, value.to_string()));
+    }
+
+    Cli {
+        phantom: PhantomData,
+        pairs: parsed,
+        invalid,
+    }
+}
+
+/// Parse `--key=value` and `--key value` pairs from args.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        let Some(arg) = arg.strip_prefix("--") else {
+            continue;
+        };
+        let (key, value) = match arg.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => match args.next_if(|v| !v.starts_with("--")) {
+                Some(value) => (arg.to_string(), value),
+                None => (arg.to_string(), "true".to_string()),
+            },
+        };
+
+        let key = snake::to_snake_case(&key).replace('.', "_");
+        match pairs.iter_mut().find(|(k, _)| k == &key) {
+            Some((_, v)) => {
+                v.push(',');
+                v.push_str(&value);
+            }
+            None => pairs.push((key, value)),
+        }
+    }
+    pairs
+}
+
+/// Load config from arguments parsed by [clap](https://docs.rs/clap).
+///
+/// Only arguments given on the command line are collected, clap's
+/// default values and env fallbacks won't override other layers.
+/// Argument ids map to fields with `-` replaced by `_`, and `.` in ids
+/// means nested fields like `server.port`. Multiple values map to a
+/// sequence.
+///
+/// Requires feature `clap`.
+///
+/// # Examples
+///
+/// ```
+/// use clap::{Arg, Command};
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_clap, from_env};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let matches = Command::new("app")
+///         .arg(Arg::new("workers").long("workers"))
+///         .get_matches_from(["app", "--workers", "8"]);
+///
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_clap(&matches));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.workers, 8);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "clap")]
+pub fn from_clap<V>(matches: &::clap::ArgMatches) -> Cli<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    let pairs = matches
+        .ids()
+        .map(|id| id.as_str())
+        .filter(|id| matches.value_source(id) == Some(::clap::parser::ValueSource::CommandLine))
+        .filter_map(|id| {
+            let values: Vec<_> = matches
+                .get_raw(id)?
+                .map(|v| v.to_string_lossy().into_owned())
+                .collect();
+            Some((snake::to_snake_case(id).replace('.', "_"), values.join(",")))
+        })
+        .collect();
+
+    Cli {
+        phantom: PhantomData,
+        pairs,
+        invalid: Vec::new(),
+    }
+}
+
+/// Collector that loads config from command line arguments.
+///
+/// Created by [`from_args`], [`from_pairs`] or `from_clap`.
+#[derive(Debug)]
+pub struct Cli<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    /// Pairs of `_` separated path and raw value.
+    pairs: Vec<(String, String)>,
+    /// Inputs that can't be parsed as pairs.
+    invalid: Vec<String>,
+}
+
+impl<V> Collector<V> for Cli<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        if let Some(pair) = self.invalid.first() {
+            return Err(anyhow!("invalid pair `{pair}`, expected `key=value`"));
+        }
+        let v: V = snake::from_iter(self.pairs.clone())
+            .map_err(|err| anyhow!("deserialize command line arguments: {err}"))?;
+        debug!("value parsed from command line: {:?}", v);
+        to_value(&v)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("cli")
+    }
+}
+
+impl<V> IntoCollector<V> for Cli<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "clap")]
+    use ::clap::{Arg, ArgAction, Command};
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestServer {
+        port: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        verbose: bool,
+        workers: usize,
+        tags: Vec<String>,
+        server: TestServer,
+    }
+
+    #[test]
+    fn test_from_args() {
+        let args = [
+            "--verbose",
+            "--workers=8",
+            "input.txt",
+            "--tags",
+            "a",
+            "--tags=b",
+            "--server.port",
+            "80",
+            "--",
+            "--workers=16",
+        ];
+        let mut c: Cli<TestConfig> = Cli {
+            phantom: PhantomData,
+            pairs: parse_args(args.map(String::from)),
+            invalid: Vec::new(),
+        };
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                verbose: true,
+                workers: 8,
+                tags: vec!["a".to_string(), "b".to_string()],
+                server: TestServer { port: 80 },
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_pairs() {
+        let mut c: Cli<TestConfig> =
+            from_pairs(["workers=8", "tags=a,b", "server.port=80", "workers=16"]);
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                verbose: false,
+                workers: 16,
+                tags: vec!["a".to_string(), "b".to_string()],
+                server: TestServer { port: 80 },
+            }
+        );
+
+        let mut c: Cli<TestConfig> = from_pairs(["workers"]);
+        let err = c.collect().expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            "invalid pair `workers`, expected `key=value`"
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn test_from_clap() {
+        let matches = Command::new("app")
+            .arg(
+                Arg::new("verbose")
+                    .long("verbose")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(Arg::new("workers").long("workers").default_value("4"))
+            .arg(Arg::new("tags").long("tag").action(ArgAction::Append))
+            .arg(Arg::new("server.port").long("port"))
+            .get_matches_from([
+                "app",
+                "--verbose",
+                "--tag",
+                "a",
+                "--tag",
+                "b",
+                "--port",
+                "80",
+            ]);
+
+        let mut c: Cli<TestConfig> = from_clap(&matches);
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                verbose: true,
+                workers: 0,
+                tags: vec!["a".to_string(), "b".to_string()],
+                server: TestServer { port: 80 },
+            }
+        );
+    }
+}
@@ -1,8 +1,17 @@
-use anyhow::Result;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_bridge::Value;
 
+use crate::de;
+use crate::path::{KeyPath, Segment};
+use crate::value::to_value;
+
 /// Collector will collect a value which take `V` as template.
 ///
 /// Implementor SHOULD deserialize into `V` directly and then serialize
@@ -28,6 +37,353 @@ use serde_bridge::Value;
 /// ```
 pub trait Collector<V: DeserializeOwned + Serialize> {
     fn collect(&mut self) -> Result<Value>;
+
+    /// Collect the raw value as parsed from source without deserializing
+    /// into `V`, so that it can be adjusted before deserialization.
+    ///
+    /// Returns `None` if this collector doesn't support raw values, and
+    /// [`Collector::collect`] will be used instead.
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        Ok(None)
+    }
+
+    /// Describe the source this collector will consult without
+    /// performing any IO.
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("unknown")
+    }
+
+    /// Take entries skipped during the last collect, like env vars with
+    /// invalid values.
+    fn skipped(&mut self) -> Vec<Skipped> {
+        Vec::new()
+    }
+
+    /// Record the current version of the source, so that changes after
+    /// build can be detected.
+    ///
+    /// Returns `None` if this source can't be checked for changes.
+    fn version(&self) -> Option<SourceVersion> {
+        None
+    }
+
+    /// Take versions of other files read during the last collect, like
+    /// files included by the source.
+    fn included(&mut self) -> Vec<SourceVersion> {
+        Vec::new()
+    }
+
+    /// How much values from this source are trusted.
+    ///
+    /// Sources fetched over network should return [`Trust::Remote`].
+    fn trust(&self) -> Trust {
+        Trust::User
+    }
+}
+
+/// Trust level of a source, ordered from the lowest to the highest.
+///
+/// Builder tracks the lowest trust of sources that set every field, see
+/// [`Builder::require_trust`][crate::Builder::require_trust].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trust {
+    /// Sources fetched over network like urls or secret stores.
+    Remote,
+    /// Sources controlled by the user like env, args or user config files.
+    User,
+    /// Sources controlled by the system administrator like files under `/etc`.
+    System,
+}
+
+impl Display for Trust {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Trust::Remote => write!(f, "remote"),
+            Trust::User => write!(f, "user"),
+            Trust::System => write!(f, "system"),
+        }
+    }
+}
+
+/// Collector with its trust overridden.
+pub(crate) struct Trusted<V: DeserializeOwned + Serialize> {
+    pub(crate) inner: Box<dyn Collector<V>>,
+    pub(crate) trust: Trust,
+}
+
+impl<V: DeserializeOwned + Serialize> Collector<V> for Trusted<V> {
+    fn collect(&mut self) -> Result<Value> {
+        self.inner.collect()
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        self.inner.collect_raw()
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.inner.describe()
+    }
+
+    fn skipped(&mut self) -> Vec<Skipped> {
+        self.inner.skipped()
+    }
+
+    fn version(&self) -> Option<SourceVersion> {
+        self.inner.version()
+    }
+
+    fn included(&mut self) -> Vec<SourceVersion> {
+        self.inner.included()
+    }
+
+    fn trust(&self) -> Trust {
+        self.trust
+    }
+}
+
+/// SourceVersion records the modified time of a file source.
+///
+/// The path is resolved on every check, so symlink swaps like Kubernetes
+/// ConfigMap updates and rotated files are detected even if the new file
+/// has the same modified time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceVersion {
+    /// Source of the file.
+    pub source: SourceDescriptor,
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Modified time of the file, `None` if it doesn't exist or can't be
+    /// read.
+    pub modified: Option<SystemTime>,
+    /// Path of the file after resolving symlinks, `None` if it doesn't
+    /// exist.
+    pub real_path: Option<PathBuf>,
+    /// Inode of the file which changes if the file is replaced, always
+    /// `None` on non-unix platforms.
+    pub inode: Option<u64>,
+}
+
+impl SourceVersion {
+    /// Record the current modified time of file at `path`.
+    pub fn of_file(source: SourceDescriptor, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let (modified, real_path, inode) = stat(&path);
+        Self {
+            source,
+            path,
+            modified,
+            real_path,
+            inode,
+        }
+    }
+
+    /// Check if the file has been changed, created, removed or replaced
+    /// since recorded.
+    pub fn changed(&self) -> bool {
+        stat(&self.path) != (self.modified, self.real_path.clone(), self.inode)
+    }
+}
+
+/// Stat file at `path` following symlinks.
+fn stat(path: &Path) -> (Option<SystemTime>, Option<PathBuf>, Option<u64>) {
+    let meta = fs::metadata(path).ok();
+    let modified = meta.as_ref().and_then(|m| m.modified().ok());
+    #[cfg(unix)]
+    let inode = {
+        use std::os::unix::fs::MetadataExt;
+        meta.as_ref().map(|m| m.ino())
+    };
+    #[cfg(not(unix))]
+    let inode = None;
+    (modified, fs::canonicalize(path).ok(), inode)
+}
+
+/// Skipped describes an input entry ignored by a collector.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Skipped {
+    /// Source of the entry.
+    pub source: SourceDescriptor,
+    /// Key of the entry like the env var name.
+    pub key: String,
+    /// Why the entry has been skipped.
+    pub reason: String,
+}
+
+/// SourceDescriptor describes where a collector loads values from.
+///
+/// # Examples
+///
+/// ```
+/// use serfig::collectors::SourceDescriptor;
+///
+/// let s = SourceDescriptor::new("file").with_location("config.toml");
+/// assert_eq!(s.to_string(), "file: config.toml");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceDescriptor {
+    kind: String,
+    location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+}
+
+impl SourceDescriptor {
+    /// Create a new descriptor with given kind like `env` or `file`.
+    pub fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            location: None,
+            group: None,
+        }
+    }
+
+    /// Set the location of this source like the file path.
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+
+    /// Set the logical group this source belongs to, see
+    /// [`Builder::group`][crate::Builder::group].
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    /// Kind of this source.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Location of this source.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    /// Group of this source.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+}
+
+impl Display for SourceDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(group) = &self.group {
+            write!(f, "{group} / ")?;
+        }
+        match &self.location {
+            None => write!(f, "{}", self.kind),
+            Some(location) => write!(f, "{}: {}", self.kind, location),
+        }
+    }
+}
+
+/// Collector that belongs to a named group.
+pub(crate) struct Grouped<V: DeserializeOwned + Serialize> {
+    pub(crate) inner: Box<dyn Collector<V>>,
+    pub(crate) group: String,
+}
+
+impl<V: DeserializeOwned + Serialize> Collector<V> for Grouped<V> {
+    fn collect(&mut self) -> Result<Value> {
+        self.inner
+            .collect()
+            .map_err(|err| anyhow!("group `{}`: {err}", self.group))
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        self.inner
+            .collect_raw()
+            .map_err(|err| anyhow!("group `{}`: {err}", self.group))
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.inner.describe().with_group(&self.group)
+    }
+
+    fn skipped(&mut self) -> Vec<Skipped> {
+        let mut skipped = self.inner.skipped();
+        for s in skipped.iter_mut() {
+            s.source = s.source.clone().with_group(&self.group);
+        }
+        skipped
+    }
+
+    fn version(&self) -> Option<SourceVersion> {
+        let mut version = self.inner.version()?;
+        version.source = version.source.with_group(&self.group);
+        Some(version)
+    }
+
+    fn included(&mut self) -> Vec<SourceVersion> {
+        let mut included = self.inner.included();
+        for v in included.iter_mut() {
+            v.source = v.source.clone().with_group(&self.group);
+        }
+        included
+    }
+
+    fn trust(&self) -> Trust {
+        self.inner.trust()
+    }
+}
+
+/// Layer of values set by [`Builder::set`][crate::Builder::set].
+pub(crate) struct Overrides {
+    value: Value,
+}
+
+impl Overrides {
+    /// Build the layer from paths and values, later values win.
+    pub(crate) fn new(values: Vec<(KeyPath, Value)>) -> Self {
+        let mut value = Value::Map(Default::default());
+        for (path, v) in values {
+            insert(&mut value, path.segments(), v);
+        }
+        Self { value }
+    }
+}
+
+/// Insert `v` at `segments` under `m`, creating maps as needed.
+///
+/// Index segments are rejected by [`Builder::set`][crate::Builder::set]
+/// so they are ignored here.
+fn insert(m: &mut Value, segments: &[Segment], v: Value) {
+    match segments.split_first() {
+        None => *m = v,
+        Some((Segment::Key(k), rest)) => {
+            if !matches!(m, Value::Map(_)) {
+                *m = Value::Map(Default::default());
+            }
+            if let Value::Map(map) = m {
+                let entry = map
+                    .entry(Value::Str(k.clone()))
+                    .or_insert_with(|| Value::Map(Default::default()));
+                insert(entry, rest, v);
+            }
+        }
+        Some((Segment::Index(_), _)) => {}
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> Collector<V> for Overrides {
+    fn collect(&mut self) -> Result<Value> {
+        let v: V = de::from_value(self.value.clone())?;
+        to_value(&v)
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        Ok(Some(self.value.clone()))
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("override")
+    }
+
+    fn trust(&self) -> Trust {
+        Trust::User
+    }
 }
 
 /// It's recommended to implement `IntoCollector` so that it can be used
@@ -35,3 +391,9 @@ pub trait Collector<V: DeserializeOwned + Serialize> {
 pub trait IntoCollector<V: DeserializeOwned + Serialize> {
     fn into_collector(self) -> Box<dyn Collector<V>>;
 }
+
+impl<V: DeserializeOwned + Serialize> IntoCollector<V> for Box<dyn Collector<V>> {
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        self
+    }
+}
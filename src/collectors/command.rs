@@ -0,0 +1,273 @@
+use std::fmt::Debug;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::process::{Command as Process, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor};
+use crate::value::to_value;
+use crate::{de, Collector, Parser};
+
+/// Load config from stdout of an external command like
+/// `vault kv get -format=json secret/myapp`.
+///
+/// The command is run without a shell when collecting. It fails if the
+/// command exits with non-zero status or doesn't finish within the
+/// timeout (30 seconds by default), stderr is included in the error.
+/// Use [`Command::with_exit_policy`] to treat non-zero exits like a
+/// missing key as an empty layer instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_command, from_env};
+/// use serfig::parsers::Jsonc;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     db_password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_command(Jsonc, "my-secret-tool", ["get", "myapp"]));
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_command<V, P>(
+    parser: P,
+    cmd: &str,
+    args: impl IntoIterator<Item = impl Into<String>>,
+) -> Command<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    Command {
+        phantom: PhantomData,
+        parser,
+        cmd: cmd.to_string(),
+        args: args.into_iter().map(Into::into).collect(),
+        timeout: Duration::from_secs(30),
+        exit_policy: ExitPolicy::Fail,
+    }
+}
+
+/// ExitPolicy decides what to do if the command exits with non-zero status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExitPolicy {
+    /// Return an error with stderr of the command.
+    #[default]
+    Fail,
+    /// Log a warning and take it as an empty layer.
+    Skip,
+}
+
+/// Collector that loads config from stdout of an external command.
+#[derive(Debug)]
+pub struct Command<V: DeserializeOwned + Serialize + Debug, P: Parser> {
+    phantom: PhantomData<V>,
+    parser: P,
+    cmd: String,
+    args: Vec<String>,
+    timeout: Duration,
+    exit_policy: ExitPolicy,
+}
+
+impl<V, P> Command<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    /// Kill the command if it doesn't finish within `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Decide what to do if the command exits with non-zero status, like
+    /// `consul kv get` for a key that doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_command, ExitPolicy};
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     workers: usize,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default().collect(
+    ///         from_command(Toml, "consul", ["kv", "get", "app/config"])
+    ///             .with_exit_policy(ExitPolicy::Skip),
+    ///     );
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_exit_policy(mut self, policy: ExitPolicy) -> Self {
+        self.exit_policy = policy;
+        self
+    }
+
+    /// Run the command and return its stdout, returns `None` if it failed
+    /// and should be skipped.
+    fn run(&self) -> Result<Option<Vec<u8>>> {
+        let mut child = Process::new(&self.cmd)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| anyhow!("run command `{}`: {err}", self.cmd))?;
+
+        // Drain pipes in background so the command won't block on full pipes.
+        let drain = |r: Option<Box<dyn Read + Send>>| {
+            thread::spawn(move || {
+                let mut bs = Vec::new();
+                if let Some(mut r) = r {
+                    let _ = r.read_to_end(&mut bs);
+                }
+                bs
+            })
+        };
+        let stdout = drain(child.stdout.take().map(|r| Box::new(r) as _));
+        let stderr = drain(child.stderr.take().map(|r| Box::new(r) as _));
+
+        let start = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if start.elapsed() >= self.timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(anyhow!(
+                    "run command `{}`: timed out after {:?}",
+                    self.cmd,
+                    self.timeout
+                ));
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            let err = anyhow!(
+                "run command `{}`: {status}: {}",
+                self.cmd,
+                String::from_utf8_lossy(&stderr).trim()
+            );
+            return match self.exit_policy {
+                ExitPolicy::Fail => Err(err),
+                ExitPolicy::Skip => {
+                    warn!("{err}, skipped");
+                    Ok(None)
+                }
+            };
+        }
+        Ok(Some(stdout))
+    }
+
+    fn parse_raw(&mut self) -> Result<Value> {
+        let bs = match self.run()? {
+            Some(bs) => bs,
+            None => return Ok(Value::Map(Default::default())),
+        };
+        let v = self
+            .parser
+            .parse_value(&bs)
+            .map_err(|err| anyhow!("parse output of command `{}`: {err}", self.cmd))?;
+        debug!("value parsed from command `{}`: {:?}", self.cmd, v);
+        Ok(v)
+    }
+}
+
+impl<V, P> Collector<V> for Command<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let v: V = de::from_value(self.parse_raw()?)
+            .map_err(|err| anyhow!("deserialize output of command `{}`: {err}", self.cmd))?;
+        to_value(&v)
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        Ok(Some(self.parse_raw()?))
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        // Arguments could carry tokens, keep them out of explain and audit
+        // output.
+        SourceDescriptor::new("command").with_location(&self.cmd)
+    }
+}
+
+impl<V, P> IntoCollector<V> for Command<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+    P: Parser + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        name: String,
+    }
+
+    #[test]
+    fn test_from_command() {
+        let mut c: Command<TestConfig, _> = from_command(Toml, "echo", [r#"name = "serfig""#]);
+        assert_eq!(c.describe().to_string(), "command: echo");
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.name, "serfig");
+
+        let mut c: Command<TestConfig, _> =
+            from_command(Toml, "sh", ["-c", "echo failed >&2; exit 3"]);
+        let err = c.collect().expect_err("must fail");
+        assert!(err.to_string().ends_with("failed"), "{err}");
+
+        let mut c: Command<TestConfig, _> =
+            from_command(Toml, "sh", ["-c", "exit 3"]).with_exit_policy(ExitPolicy::Skip);
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t, TestConfig::default());
+
+        let mut c: Command<TestConfig, _> =
+            from_command(Toml, "sleep", ["10"]).with_timeout(Duration::from_millis(50));
+        let err = c.collect().expect_err("must fail");
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
+}
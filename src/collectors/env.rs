@@ -1,14 +1,21 @@
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::path::Path;
+use std::{env, fs, io};
 
-use anyhow::Result;
-use log::debug;
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_bridge::{IntoValue, Value};
+use serde_bridge::Value;
 
-use crate::collectors::collector::IntoCollector;
-use crate::Collector;
+use crate::collectors::collector::{IntoCollector, Skipped, SourceDescriptor};
+use crate::parsers::Toml;
+use crate::probe::enum_fields;
+use crate::snake;
+use crate::value::to_value;
+use crate::{Collector, Parser};
 
 /// load config from env.
 ///
@@ -42,16 +49,220 @@ where
     V: DeserializeOwned + Serialize + Debug,
 {
     Environment {
-        phantom: PhantomData::default(),
+        phantom: PhantomData,
+        snapshot: None,
+        prefix: None,
+        separator: "_".to_string(),
+        skip_invalid: false,
+        skipped: Vec::new(),
+    }
+}
+
+/// load config from env variables starting with `{prefix}_`.
+///
+/// The prefix is stripped before mapping to fields, so `MYAPP_PORT`
+/// maps to `port` with prefix `MYAPP`. The prefix is joined by the
+/// separator set by [`Environment::with_separator`]. Other variables like `PATH` or
+/// `HOME` are ignored. The prefix is case sensitive.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_env_prefixed;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     port: u16,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_env_prefixed("MYAPP"));
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn from_env_prefixed<V>(prefix: &str) -> Environment<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    Environment {
+        prefix: Some(prefix.to_string()),
+        ..from_env()
+    }
+}
+
+/// load config from a snapshot of env instead of the current env.
+///
+/// The snapshot is read in the same way as [`from_env`], which makes
+/// tests deterministic and allows loading saved env captures.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_env_map;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     port: u16,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let vars = HashMap::from([("PORT".to_string(), "8080".to_string())]);
+///     let builder = Builder::default()
+///         .collect(from_env_map(vars));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.port, 8080);
+///     Ok(())
+/// }
+/// ```
+pub fn from_env_map<V>(vars: HashMap<String, String>) -> Environment<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    let mut vars: Vec<_> = vars.into_iter().collect();
+    vars.sort();
+    Environment {
+        snapshot: Some(vars),
+        ..from_env()
     }
 }
 
 /// Collector that can load config from env.
 ///
-/// Created by [`from_env`].
+/// Created by [`from_env`], [`from_env_prefixed`] or [`from_env_map`].
 #[derive(Debug)]
 pub struct Environment<V: DeserializeOwned + Serialize + Debug> {
     phantom: PhantomData<V>,
+    /// Vars to read instead of the current env.
+    snapshot: Option<Vec<(String, String)>>,
+    prefix: Option<String>,
+    separator: String,
+    skip_invalid: bool,
+    skipped: Vec<Skipped>,
+}
+
+impl<V> Environment<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    /// Skip variables that can't be deserialized instead of failing
+    /// the whole env layer.
+    ///
+    /// Skipped variables are reported by
+    /// [`Builder::build_with_report`][crate::Builder::build_with_report].
+    pub fn skip_invalid(mut self) -> Self {
+        self.skip_invalid = true;
+        self
+    }
+
+    /// Use `sep` to separate nested fields in env names.
+    ///
+    /// The default separator `_` can't tell `DATABASE_POOL_SIZE` apart
+    /// from `database.pool.size`, use `__` to map `DATABASE__POOL_SIZE`
+    /// to `database.pool_size` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_env_prefixed;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct Database {
+    ///     pool_size: usize,
+    /// }
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     database: Database,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // Reads `MYAPP__DATABASE__POOL_SIZE`.
+    ///     let builder = Builder::default()
+    ///         .collect(from_env_prefixed("MYAPP").with_separator("__"));
+    ///     let t: TestConfig = builder.build()?;
+    ///
+    ///     println!("{:?}", t);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_separator(mut self, sep: &str) -> Self {
+        self.separator = sep.to_string();
+        self
+    }
+
+    /// Prefix joined with separator, or empty if not set.
+    fn var_prefix(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{prefix}{}", self.separator),
+            None => String::new(),
+        }
+    }
+
+    /// Returns selected vars as `(name, key, value)`, `key` is the name
+    /// without prefix and separated by `_` for serde-env.
+    fn vars(&self) -> Vec<(String, String, String)> {
+        let prefix = self.var_prefix();
+        let vars = match &self.snapshot {
+            Some(vars) => vars.clone(),
+            None => env::vars().collect(),
+        };
+        vars.into_iter()
+            .filter_map(|(name, v)| {
+                let key = name.strip_prefix(&prefix)?;
+                let key = if self.separator == "_" {
+                    key.to_string()
+                } else {
+                    key.split(self.separator.as_str())
+                        .collect::<Vec<_>>()
+                        .join("_")
+                };
+                Some((name, key, v))
+            })
+            .collect()
+    }
+
+    /// Parse vars one by one, keeping only those that still deserialize.
+    ///
+    /// Returns `None` if `V` can't be deserialized without any var, in
+    /// which case offending vars can't be told apart.
+    fn collect_tolerant(&mut self, vars: Vec<(String, String, String)>) -> Option<V> {
+        let mut accepted: Vec<(String, String)> = Vec::new();
+        snake::from_iter::<_, _, V>(accepted.clone()).ok()?;
+
+        for (name, k, v) in vars {
+            accepted.push((k, v));
+            if let Err(err) = snake::from_iter::<_, _, V>(accepted.clone()) {
+                accepted.pop();
+                warn!("skip invalid env {}: {}", name, err);
+                self.skipped.push(Skipped {
+                    source: self.describe(),
+                    key: name,
+                    reason: err.to_string(),
+                });
+            }
+        }
+
+        snake::from_iter(accepted).ok()
+    }
 }
 
 impl<V> Collector<V> for Environment<V>
@@ -59,12 +270,94 @@ where
     V: DeserializeOwned + Serialize + Debug,
 {
     fn collect(&mut self) -> Result<Value> {
-        let v: V = serde_env::from_env()?;
+        self.skipped.clear();
+
+        let vars = self.vars();
+        let pairs: Vec<(String, String)> = vars
+            .iter()
+            .map(|(_, k, v)| (k.clone(), v.clone()))
+            .collect();
+        let (prefix, sep) = (self.var_prefix(), self.separator.clone());
+        let explain = |err| explain_env_error::<V>(err, &prefix, &sep, pairs.clone());
+        let v: V = match snake::from_iter(pairs.clone()) {
+            Ok(v) => v,
+            Err(err) if self.skip_invalid => match self.collect_tolerant(vars) {
+                Some(v) => v,
+                None => return Err(explain(err)),
+            },
+            Err(err) => return Err(explain(err)),
+        };
         debug!("value parsed from env: {:?}", v);
-        Ok(v.into_value()?)
+        to_value(&v)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        match &self.prefix {
+            Some(_) => {
+                SourceDescriptor::new("env").with_location(&format!("{}*", self.var_prefix()))
+            }
+            None => SourceDescriptor::new("env"),
+        }
+    }
+
+    fn skipped(&mut self) -> Vec<Skipped> {
+        std::mem::take(&mut self.skipped)
     }
 }
 
+/// Explain the env error with the offending variable if possible.
+///
+/// serde-env doesn't tell us which variable failed, so we check all
+/// enum fields of `V` to find out the variable that doesn't match any
+/// variant. `prefix` has been stripped from `vars` and will be added
+/// back in the message, and nested fields are joined by `sep`.
+fn explain_env_error<V: DeserializeOwned>(
+    err: serde_env::Error,
+    prefix: &str,
+    sep: &str,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> anyhow::Error {
+    let vars: HashMap<String, String> = vars
+        .into_iter()
+        .map(|(k, v)| (k.to_lowercase(), v))
+        .collect();
+
+    for field in enum_fields::<V>() {
+        let key = field.path.replace('.', "_");
+        let value = match vars.get(&key.to_lowercase()) {
+            Some(v) if !v.is_empty() => v,
+            _ => continue,
+        };
+        if field.variants.contains(&value.as_str()) {
+            continue;
+        }
+
+        let mut msg = format!(
+            "env {}{}={:?} is not a valid variant of `{}`, expected one of: {}",
+            prefix,
+            field.path.replace('.', sep).to_uppercase(),
+            value,
+            field.name,
+            field
+                .variants
+                .iter()
+                .map(|v| format!("`{v}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if let Some(v) = field
+            .variants
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(value))
+        {
+            msg.push_str(&format!(" (did you mean `{v}`?)"));
+        }
+        return anyhow!(msg);
+    }
+
+    err.into()
+}
+
 impl<V> IntoCollector<V> for Environment<V>
 where
     V: DeserializeOwned + Serialize + Debug + 'static,
@@ -74,6 +367,93 @@ where
     }
 }
 
+/// Collector that load config from env vars declared in a mapping file.
+///
+/// The mapping file is a TOML or JSON document that maps env var names
+/// to dot separated config paths:
+///
+/// ```toml
+/// PLATFORM_DB_ADDR = "database.addr"
+/// ```
+///
+/// Created by [`Builder::with_env_mapping_file`][crate::Builder::with_env_mapping_file].
+#[derive(Debug)]
+pub(crate) struct EnvMapping<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    path: String,
+}
+
+impl<V> EnvMapping<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    pub(crate) fn new(path: &str) -> Self {
+        Self {
+            phantom: PhantomData,
+            path: path.to_string(),
+        }
+    }
+
+    /// Load mappings from env var to config path.
+    ///
+    /// A missing mapping file means there is nothing to override.
+    fn mappings(&self) -> Result<BTreeMap<String, String>> {
+        let bs = match fs::read(&self.path) {
+            Ok(bs) => bs,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!("env mapping file {} not found, skip", self.path);
+                return Ok(BTreeMap::new());
+            }
+            Err(err) => return Err(anyhow!("read env mapping file {}: {err}", self.path)),
+        };
+
+        let is_json = Path::new(&self.path)
+            .extension()
+            .is_some_and(|v| v.eq_ignore_ascii_case("json"));
+        let mappings = if is_json {
+            serde_json::from_slice(&bs).map_err(anyhow::Error::from)
+        } else {
+            Toml.parse(&bs)
+        };
+        mappings.map_err(|err| anyhow!("parse env mapping file {}: {err}", self.path))
+    }
+}
+
+impl<V> Collector<V> for EnvMapping<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let pairs: Vec<(String, String)> = self
+            .mappings()?
+            .into_iter()
+            .filter_map(|(key, path)| {
+                env::var(&key)
+                    .ok()
+                    .map(|value| (path.replace('.', "_"), value))
+            })
+            .collect();
+
+        let v: V = snake::from_iter(pairs.clone())
+            .map_err(|err| explain_env_error::<V>(err, "", "_", pairs))?;
+        debug!("value parsed from mapped env: {:?}", v);
+        to_value(&v)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("env").with_location(&self.path)
+    }
+}
+
+impl<V> IntoCollector<V> for EnvMapping<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use log::debug;
@@ -108,4 +488,211 @@ mod tests {
             )
         })
     }
+
+    #[test]
+    fn test_env_map() {
+        let _ = env_logger::try_init();
+
+        temp_env::with_vars(vec![("serfig_test_str", Some("from_env"))], || {
+            let vars = HashMap::from([("SERFIG_TEST_STR".to_string(), "from_map".to_string())]);
+            let mut c: Environment<TestStruct> = from_env_map(vars);
+
+            let t =
+                TestStruct::from_value(c.collect().expect("must success")).expect("must success");
+            assert_eq!(t.test_str, "from_map");
+        })
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum TestLevel {
+        Debug,
+        Info,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestLog {
+        level: TestLevel,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestEnum {
+        serfig_test_log: TestLog,
+    }
+
+    #[test]
+    fn test_env_invalid_variant() {
+        let _ = env_logger::try_init();
+
+        temp_env::with_vars(vec![("SERFIG_TEST_LOG_LEVEL", Some("Info"))], || {
+            let mut c: Environment<TestEnum> = from_env();
+
+            let err = c.collect().expect_err("must fail");
+            assert_eq!(
+                err.to_string(),
+                "env SERFIG_TEST_LOG_LEVEL=\"Info\" is not a valid variant of `TestLevel`, \
+                expected one of: `debug`, `info` (did you mean `info`?)"
+            )
+        })
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestPrefixed {
+        port: u16,
+        name: String,
+    }
+
+    #[test]
+    fn test_env_prefixed() {
+        let _ = env_logger::try_init();
+
+        temp_env::with_vars(
+            vec![
+                ("SERFIG_PREFIXED_PORT", Some("8080")),
+                ("NAME", Some("unrelated")),
+                ("PORT", Some("invalid")),
+            ],
+            || {
+                let mut c: Environment<TestPrefixed> = from_env_prefixed("SERFIG_PREFIXED");
+                let v = c.collect().expect("must success");
+                let t = TestPrefixed::from_value(v).expect("must success");
+                assert_eq!(
+                    t,
+                    TestPrefixed {
+                        port: 8080,
+                        name: "".to_string(),
+                    }
+                );
+            },
+        )
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestPool {
+        pool_size: usize,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestSeparator {
+        database: TestPool,
+    }
+
+    #[test]
+    fn test_env_separator() {
+        let _ = env_logger::try_init();
+
+        temp_env::with_vars(
+            vec![
+                ("SERFIG_SEP__DATABASE__POOL_SIZE", Some("8")),
+                ("SERFIG_SEP_DATABASE_POOL_SIZE", Some("4")),
+            ],
+            || {
+                let mut c: Environment<TestSeparator> =
+                    from_env_prefixed("SERFIG_SEP").with_separator("__");
+                assert_eq!(c.describe().to_string(), "env: SERFIG_SEP__*");
+
+                let v = c.collect().expect("must success");
+                let t = TestSeparator::from_value(v).expect("must success");
+                assert_eq!(t.database.pool_size, 8);
+            },
+        )
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestSkip {
+        #[serde(rename = "serfig_test_skip_name")]
+        name: String,
+        #[serde(rename = "serfig_test_skip_port")]
+        port: u16,
+    }
+
+    #[test]
+    fn test_env_skip_invalid() {
+        let _ = env_logger::try_init();
+
+        temp_env::with_vars(
+            vec![
+                ("serfig_test_skip_name", Some("serfig")),
+                ("serfig_test_skip_port", Some("abc")),
+            ],
+            || {
+                let mut c: Environment<TestSkip> = from_env();
+                c.collect().expect_err("must fail without skip_invalid");
+
+                let mut c: Environment<TestSkip> = from_env().skip_invalid();
+                let v = c.collect().expect("must success");
+                let t = TestSkip::from_value(v).expect("must success");
+                assert_eq!(
+                    t,
+                    TestSkip {
+                        name: "serfig".to_string(),
+                        port: 0,
+                    }
+                );
+
+                let skipped = c.skipped();
+                assert_eq!(skipped.len(), 1);
+                assert_eq!(skipped[0].key, "serfig_test_skip_port");
+                assert!(c.skipped().is_empty());
+            },
+        )
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestDatabase {
+        addr: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestMapping {
+        database: TestDatabase,
+    }
+
+    #[test]
+    fn test_env_mapping() {
+        let _ = env_logger::try_init();
+
+        let path = env::temp_dir().join("serfig-env-mapping.toml");
+        fs::write(
+            &path,
+            r#"
+SERFIG_TEST_PLATFORM_DB_ADDR = "database.addr"
+SERFIG_TEST_PLATFORM_DB_PORT = "database.port"
+"#,
+        )
+        .expect("write mapping file");
+
+        temp_env::with_vars(
+            vec![
+                ("SERFIG_TEST_PLATFORM_DB_ADDR", Some("127.0.0.1")),
+                ("SERFIG_TEST_PLATFORM_DB_PORT", Some("5432")),
+            ],
+            || {
+                let mut c: EnvMapping<TestMapping> = EnvMapping::new(path.to_str().unwrap());
+                let t = TestMapping::from_value(c.collect().expect("must success"))
+                    .expect("must success");
+
+                assert_eq!(
+                    t,
+                    TestMapping {
+                        database: TestDatabase {
+                            addr: "127.0.0.1".to_string(),
+                            port: 5432,
+                        }
+                    }
+                )
+            },
+        );
+
+        let mut c: EnvMapping<TestMapping> = EnvMapping::new("/not/exist/mapping.toml");
+        let t = TestMapping::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t, TestMapping::default());
+    }
 }
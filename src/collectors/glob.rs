@@ -0,0 +1,344 @@
+use std::fmt::Debug;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor};
+use crate::path::{KeyPath, Segment};
+use crate::value::{merge, to_value, MergeConfig};
+use crate::{de, Collector, Parser};
+
+/// Load config from all files matching a glob pattern like
+/// `config/**/*.toml`.
+///
+/// Matched files are merged in lexicographic order of their paths, later
+/// files override earlier ones, so `00-base.toml` can be overridden by
+/// `10-site.toml`. No matches is the same as an empty file.
+///
+/// Use [`Glob::each_file_as_element`] or [`Glob::each_file_as_entry`] for
+/// layouts like `rules.d/*.toml` where every file defines one object.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_glob};
+/// use serfig::parsers::Toml;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_glob(Toml, "config/**/*.toml"))
+///         .collect(from_env());
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_glob<V, P>(parser: P, pattern: &str) -> Glob<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    Glob {
+        phantom: PhantomData,
+        parser,
+        pattern: pattern.to_string(),
+        layout: Layout::Merged,
+    }
+}
+
+/// Layout decides how matched files are combined.
+#[derive(Debug)]
+enum Layout {
+    /// Files are merged into one value.
+    Merged,
+    /// Every file is an element of the sequence at path.
+    Elements(String),
+    /// Every file is an entry of the map at path keyed by file stem.
+    Entries(String),
+}
+
+/// Collector that loads config from files matching a glob pattern.
+#[derive(Debug)]
+pub struct Glob<V: DeserializeOwned + Serialize + Debug, P: Parser> {
+    phantom: PhantomData<V>,
+    parser: P,
+    pattern: String,
+    layout: Layout,
+}
+
+impl<V, P> Glob<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    /// Take every matched file as one element of the sequence at `path`
+    /// like `rules`, in lexicographic order of file paths.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_glob;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct Rule {
+    ///     pattern: String,
+    ///     action: String,
+    /// }
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     rules: Vec<Rule>,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // Every file in `rules.d` defines one rule.
+    ///     let builder = Builder::default()
+    ///         .collect(from_glob(Toml, "rules.d/*.toml").each_file_as_element("rules"));
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn each_file_as_element(mut self, path: &str) -> Self {
+        self.layout = Layout::Elements(path.to_string());
+        self
+    }
+
+    /// Take every matched file as one entry of the map at `path` like
+    /// `backends`, keyed by the file name without extension.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_glob;
+    /// use serfig::parsers::Toml;
+    /// use serfig::Builder;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct Backend {
+    ///     addr: String,
+    /// }
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     backends: HashMap<String, Backend>,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // `backends.d/primary.toml` is loaded as `backends["primary"]`.
+    ///     let builder = Builder::default()
+    ///         .collect(from_glob(Toml, "backends.d/*.toml").each_file_as_entry("backends"));
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn each_file_as_entry(mut self, path: &str) -> Self {
+        self.layout = Layout::Entries(path.to_string());
+        self
+    }
+
+    /// Returns matched paths in the order they are merged.
+    fn paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = glob::glob(&self.pattern)
+            .map_err(|err| anyhow!("invalid glob pattern `{}`: {err}", self.pattern))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| anyhow!("expand glob pattern `{}`: {err}", self.pattern))?;
+        paths.retain(|p| p.is_file());
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn parse_raw(&mut self) -> Result<Value> {
+        let mut value = Value::Map(IndexMap::new());
+        let mut elements = Vec::new();
+        let mut entries = IndexMap::new();
+        for path in self.paths()? {
+            let bs =
+                fs::read(&path).map_err(|err| anyhow!("read file {}: {err}", path.display()))?;
+            let v = self
+                .parser
+                .parse_value(&bs)
+                .map_err(|err| anyhow!("parse file {}: {err}", path.display()))?;
+            debug!("value parsed from {}: {:?}", path.display(), v);
+            match &self.layout {
+                // Files have no default, every value in later files wins.
+                Layout::Merged => merge(&MergeConfig::default(), &Value::Unit, &mut value, v),
+                Layout::Elements(_) => elements.push(v),
+                Layout::Entries(_) => {
+                    let stem = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    entries.insert(Value::Str(stem), v);
+                }
+            }
+        }
+
+        let (target, leaf) = match &self.layout {
+            Layout::Merged => return Ok(value),
+            Layout::Elements(target) => (target, Value::Seq(elements)),
+            Layout::Entries(target) => (target, Value::Map(entries)),
+        };
+        let target: KeyPath = target.parse()?;
+        target
+            .segments()
+            .iter()
+            .rev()
+            .try_fold(leaf, |v, s| match s {
+                Segment::Key(k) => Ok(Value::Map(IndexMap::from([(Value::Str(k.clone()), v)]))),
+                Segment::Index(_) => Err(anyhow!(
+                    "invalid target `{target}` of glob pattern `{}`: index is not supported",
+                    self.pattern
+                )),
+            })
+    }
+}
+
+impl<V, P> Collector<V> for Glob<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let v: V = de::from_value(self.parse_raw()?)
+            .map_err(|err| anyhow!("deserialize files matching `{}`: {err}", self.pattern))?;
+        to_value(&v)
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        Ok(Some(self.parse_raw()?))
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("glob").with_location(&self.pattern)
+    }
+}
+
+impl<V, P> IntoCollector<V> for Glob<V, P>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+    P: Parser + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestServer {
+        addr: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        name: String,
+        server: TestServer,
+    }
+
+    #[test]
+    fn test_from_glob() {
+        let dir = std::env::temp_dir().join("serfig-glob");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a/b")).expect("create dir");
+        fs::write(
+            dir.join("a/00-base.toml"),
+            "name = \"base\"\n[server]\naddr = \"127.0.0.1\"\nport = 80",
+        )
+        .expect("write file");
+        fs::write(dir.join("a/b/10-site.toml"), "[server]\nport = 8080").expect("write file");
+        fs::write(dir.join("a/ignored.json"), "{}").expect("write file");
+
+        let mut c: Glob<TestConfig, _> = from_glob(Toml, &format!("{}/**/*.toml", dir.display()));
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                name: "base".to_string(),
+                server: TestServer {
+                    addr: "127.0.0.1".to_string(),
+                    port: 8080,
+                },
+            }
+        );
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigRules {
+        rules: Vec<TestServer>,
+        backends: BTreeMap<String, TestServer>,
+    }
+
+    #[test]
+    fn test_from_glob_each_file() {
+        let dir = std::env::temp_dir().join("serfig-glob-each-file");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create dir");
+        fs::write(dir.join("b.toml"), "port = 2").expect("write file");
+        fs::write(dir.join("a.toml"), "addr = \"a\"\nport = 1").expect("write file");
+        let pattern = format!("{}/*.toml", dir.display());
+        let server = |addr: &str, port| TestServer {
+            addr: addr.to_string(),
+            port,
+        };
+
+        let mut c: Glob<TestConfigRules, _> =
+            from_glob(Toml, &pattern).each_file_as_element("rules");
+        let t =
+            TestConfigRules::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.rules, vec![server("a", 1), server("", 2)]);
+
+        let mut c: Glob<TestConfigRules, _> =
+            from_glob(Toml, &pattern).each_file_as_entry("backends");
+        let t =
+            TestConfigRules::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t.backends,
+            BTreeMap::from([
+                ("a".to_string(), server("a", 1)),
+                ("b".to_string(), server("", 2))
+            ])
+        );
+
+        let mut c: Glob<TestConfigRules, _> =
+            from_glob(Toml, &pattern).each_file_as_element("rules[0]");
+        let err = c.collect().expect_err("must fail");
+        assert!(err.to_string().contains("index is not supported"), "{err}");
+    }
+}
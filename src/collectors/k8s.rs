@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor, Trust};
+use crate::collectors::tls;
+use crate::value::to_value;
+use crate::{snake, Collector};
+
+/// Directory of the service account mounted into pods.
+const SERVICE_ACCOUNT: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Load config from a Kubernetes ConfigMap via the Kubernetes API.
+///
+/// Data keys of the ConfigMap like `server.port` or `max-workers` map to
+/// fields like env values, comma separated values map to a sequence.
+///
+/// The API server is found in the following order:
+///
+/// - The kubeconfig set by [`ConfigMap::with_kubeconfig`].
+/// - The in-cluster service account if env `KUBERNETES_SERVICE_HOST` is set.
+/// - The kubeconfig at env `KUBECONFIG` or `~/.kube/config`.
+///
+/// Kubeconfig users can authenticate with tokens or client certificates,
+/// exec plugins are not supported.
+///
+/// Requires feature `k8s`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_k8s_configmap};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_k8s_configmap("default", "myapp"))
+///         .collect(from_env());
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_k8s_configmap<V>(namespace: &str, name: &str) -> ConfigMap<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    ConfigMap {
+        phantom: PhantomData,
+        namespace: namespace.to_string(),
+        name: name.to_string(),
+        kubeconfig: None,
+    }
+}
+
+/// Collector that loads config from a Kubernetes ConfigMap.
+#[derive(Debug)]
+pub struct ConfigMap<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    namespace: String,
+    name: String,
+    kubeconfig: Option<PathBuf>,
+}
+
+impl<V> ConfigMap<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    /// Connect with the current context of kubeconfig at `path`.
+    pub fn with_kubeconfig(mut self, path: impl AsRef<Path>) -> Self {
+        self.kubeconfig = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    fn cluster(&self) -> Result<Cluster> {
+        if let Some(path) = &self.kubeconfig {
+            return Cluster::from_kubeconfig(path);
+        }
+        if let Ok(host) = env::var("KUBERNETES_SERVICE_HOST") {
+            return Cluster::in_cluster(&host);
+        }
+        let path = match env::var_os("KUBECONFIG").filter(|v| !v.is_empty()) {
+            Some(v) => env::split_paths(&v)
+                .next()
+                .ok_or_else(|| anyhow!("env `KUBECONFIG` is empty"))?,
+            None => env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".kube").join("config"))
+                .ok_or_else(|| anyhow!("no kubeconfig found"))?,
+        };
+        Cluster::from_kubeconfig(&path)
+    }
+}
+
+/// Connection to a Kubernetes API server.
+struct Cluster {
+    server: String,
+    token: Option<String>,
+    /// PEM encoded certificate authority.
+    ca: Option<Vec<u8>>,
+    /// PEM encoded client certificate and key.
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Cluster {
+    fn in_cluster(host: &str) -> Result<Cluster> {
+        let port = env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+        let host = if host.contains(':') {
+            format!("[{host}]")
+        } else {
+            host.to_string()
+        };
+        let dir = Path::new(SERVICE_ACCOUNT);
+        let token = fs::read_to_string(dir.join("token"))
+            .map_err(|err| anyhow!("read service account token: {err}"))?;
+        let ca = fs::read(dir.join("ca.crt"))
+            .map_err(|err| anyhow!("read service account ca: {err}"))?;
+        Ok(Cluster {
+            server: format!("https://{host}:{port}"),
+            token: Some(token.trim().to_string()),
+            ca: Some(ca),
+            identity: None,
+        })
+    }
+
+    fn from_kubeconfig(path: &Path) -> Result<Cluster> {
+        let cfg: Kubeconfig = serde_yaml::from_slice(&fs::read(path)?)
+            .map_err(|err| anyhow!("parse kubeconfig {}: {err}", path.display()))?;
+        cfg.cluster(path.parent().unwrap_or(Path::new(".")))
+            .map_err(|err| anyhow!("load kubeconfig {}: {err}", path.display()))
+    }
+
+    fn agent(&self) -> Result<ureq::Agent> {
+        let identity = self
+            .identity
+            .as_ref()
+            .map(|(cert, key)| (cert.as_slice(), key.as_slice()));
+        tls::agent(self.ca.as_deref(), identity, None)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Kubeconfig {
+    current_context: Option<String>,
+    #[serde(default)]
+    contexts: Vec<Named<KubeContext>>,
+    #[serde(default)]
+    clusters: Vec<Named<KubeCluster>>,
+    #[serde(default)]
+    users: Vec<Named<KubeUser>>,
+}
+
+#[derive(Deserialize)]
+struct Named<T> {
+    name: String,
+    #[serde(rename = "context", alias = "cluster", alias = "user")]
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct KubeContext {
+    cluster: String,
+    user: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct KubeCluster {
+    server: String,
+    certificate_authority: Option<PathBuf>,
+    certificate_authority_data: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct KubeUser {
+    token: Option<String>,
+    #[serde(rename = "tokenFile")]
+    token_file: Option<PathBuf>,
+    client_certificate: Option<PathBuf>,
+    client_certificate_data: Option<String>,
+    client_key: Option<PathBuf>,
+    client_key_data: Option<String>,
+    exec: Option<serde_yaml::Value>,
+}
+
+impl Kubeconfig {
+    /// Resolve the cluster of current context, relative paths are joined
+    /// to `dir`.
+    fn cluster(self, dir: &Path) -> Result<Cluster> {
+        let current = self
+            .current_context
+            .ok_or_else(|| anyhow!("current-context is not set"))?;
+        let ctx = find(self.contexts, &current, "context")?;
+        let cluster = find(self.clusters, &ctx.cluster, "cluster")?;
+        let user = match &ctx.user {
+            Some(name) => find(self.users, name, "user")?,
+            None => KubeUser::default(),
+        };
+        if user.exec.is_some() {
+            return Err(anyhow!("exec auth plugins are not supported"));
+        }
+
+        let read = |data: Option<String>, path: Option<PathBuf>| -> Result<Option<Vec<u8>>> {
+            match (data, path) {
+                (Some(data), _) => Ok(Some(
+                    base64::engine::general_purpose::STANDARD.decode(data.trim())?,
+                )),
+                (None, Some(path)) => Ok(Some(fs::read(dir.join(path))?)),
+                (None, None) => Ok(None),
+            }
+        };
+        let token = match (user.token, user.token_file) {
+            (Some(token), _) => Some(token),
+            (None, Some(path)) => Some(fs::read_to_string(dir.join(path))?.trim().to_string()),
+            (None, None) => None,
+        };
+        let identity = match (
+            read(user.client_certificate_data, user.client_certificate)?,
+            read(user.client_key_data, user.client_key)?,
+        ) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (None, None) => None,
+            _ => return Err(anyhow!("client certificate and key must be set together")),
+        };
+
+        Ok(Cluster {
+            server: cluster.server.trim_end_matches('/').to_string(),
+            token,
+            ca: read(
+                cluster.certificate_authority_data,
+                cluster.certificate_authority,
+            )?,
+            identity,
+        })
+    }
+}
+
+fn find<T>(items: Vec<Named<T>>, name: &str, kind: &str) -> Result<T> {
+    items
+        .into_iter()
+        .find(|v| v.name == name)
+        .map(|v| v.value)
+        .ok_or_else(|| anyhow!("{kind} `{name}` is not found"))
+}
+
+#[derive(Deserialize)]
+struct ConfigMapObject {
+    #[serde(default)]
+    data: BTreeMap<String, String>,
+}
+
+impl<V> Collector<V> for ConfigMap<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let cluster = self.cluster()?;
+        let url = format!(
+            "{}/api/v1/namespaces/{}/configmaps/{}",
+            cluster.server, self.namespace, self.name
+        );
+        let mut req = cluster.agent()?.get(&url);
+        if let Some(token) = &cluster.token {
+            req = req.set("Authorization", &format!("Bearer {token}"));
+        }
+        let resp = req
+            .call()
+            .map_err(|err| anyhow!("read configmap {}/{}: {err}", self.namespace, self.name))?;
+        let obj: ConfigMapObject = serde_json::from_str(&resp.into_string()?)?;
+
+        let pairs: Vec<_> = obj
+            .data
+            .into_iter()
+            .map(|(k, v)| (snake::to_snake_case(&k).replace('.', "_"), v))
+            .collect();
+        let v: V = snake::from_iter(pairs).map_err(|err| {
+            anyhow!(
+                "deserialize configmap {}/{}: {err}",
+                self.namespace,
+                self.name
+            )
+        })?;
+        debug!("value parsed from configmap: {:?}", v);
+        to_value(&v)
+    }
+
+    fn trust(&self) -> Trust {
+        Trust::Remote
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("k8s")
+            .with_location(&format!("configmap/{}/{}", self.namespace, self.name))
+    }
+}
+
+impl<V> IntoCollector<V> for ConfigMap<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use serde_bridge::FromValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestServer {
+        port: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        max_workers: usize,
+        server: TestServer,
+    }
+
+    #[test]
+    fn test_from_k8s_configmap() {
+        let _ = env_logger::try_init();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            let body = r#"{"kind":"ConfigMap","data":{"max-workers":"8","server.port":"80"}}"#;
+            let (mut stream, _) = listener.accept().expect("must accept");
+            let mut buf = [0; 4096];
+            let n = stream.read(&mut buf).expect("must read");
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .expect("must write");
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+
+        let kubeconfig = env::temp_dir().join("serfig-k8s-kubeconfig.yaml");
+        fs::write(
+            &kubeconfig,
+            format!(
+                r#"
+apiVersion: v1
+kind: Config
+current-context: test
+contexts:
+- name: test
+  context:
+    cluster: test
+    user: test
+clusters:
+- name: test
+  cluster:
+    server: http://{addr}/
+users:
+- name: test
+  user:
+    token: test-token
+"#
+            ),
+        )
+        .expect("write kubeconfig");
+
+        let mut c: ConfigMap<TestConfig> =
+            from_k8s_configmap("default", "myapp").with_kubeconfig(&kubeconfig);
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                max_workers: 8,
+                server: TestServer { port: 80 },
+            }
+        );
+
+        let request = server.join().expect("server must exit");
+        assert!(request.starts_with("get /api/v1/namespaces/default/configmaps/myapp"));
+        assert!(request.contains("authorization: bearer test-token"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor};
+use crate::value::to_value;
+use crate::{snake, Collector};
+
+/// KvRead reads entries from a key-value store like sled or redb.
+///
+/// Implement it for the store used by the application to layer its
+/// settings over other sources with [`from_kv`].
+pub trait KvRead {
+    /// Scan all entries whose key starts with `prefix`.
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, String)>>;
+}
+
+impl KvRead for BTreeMap<String, String> {
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .range(prefix.to_string()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+}
+
+impl KvRead for HashMap<String, String> {
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .iter()
+            .filter(|(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+}
+
+/// Load config from entries under `prefix` in a key-value store.
+///
+/// The prefix is stripped from keys, and `/` or `.` in the rest like
+/// `server/port` map to nested fields. Values are parsed like env
+/// values, comma separated values map to a sequence.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_kv;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     // Settings persisted by the application.
+///     let store = BTreeMap::from([("app/workers".to_string(), "8".to_string())]);
+///
+///     let builder = Builder::default().collect(from_kv(store, "app/"));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.workers, 8);
+///     Ok(())
+/// }
+/// ```
+pub fn from_kv<V, S>(store: S, prefix: &str) -> Kv<V, S>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    S: KvRead,
+{
+    Kv {
+        phantom: PhantomData,
+        store,
+        prefix: prefix.to_string(),
+        source: SourceDescriptor::new("kv").with_location(&format!("{prefix}*")),
+    }
+}
+
+/// Load config from a map of dotted keys like `database.pool_size`.
+///
+/// Values are parsed according to the target fields like env values, so
+/// it's handy for programmatic overrides.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_file, from_map};
+/// use serfig::parsers::Toml;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct Database {
+///     pool_size: usize,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     database: Database,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let overrides = HashMap::from([("database.pool_size".to_string(), "10".to_string())]);
+///
+///     let builder = Builder::default().collect(from_map(overrides));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.database.pool_size, 10);
+///     Ok(())
+/// }
+/// ```
+pub fn from_map<V>(map: HashMap<String, String>) -> Kv<V, HashMap<String, String>>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    Kv {
+        phantom: PhantomData,
+        store: map,
+        prefix: String::new(),
+        source: SourceDescriptor::new("map"),
+    }
+}
+
+/// Collector that loads config from a key-value store.
+///
+/// Created by [`from_kv`] or [`from_map`].
+#[derive(Debug)]
+pub struct Kv<V: DeserializeOwned + Serialize + Debug, S: KvRead> {
+    phantom: PhantomData<V>,
+    store: S,
+    prefix: String,
+    source: SourceDescriptor,
+}
+
+impl<V, S> Collector<V> for Kv<V, S>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    S: KvRead,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let pairs: Vec<_> = self
+            .store
+            .scan(&self.prefix)
+            .map_err(|err| anyhow!("scan {}: {err}", self.source))?
+            .into_iter()
+            .filter_map(|(k, v)| {
+                let key = k.strip_prefix(&self.prefix)?;
+                Some((snake::to_snake_case(key).replace(['/', '.'], "_"), v))
+            })
+            .collect();
+
+        let v: V =
+            snake::from_iter(pairs).map_err(|err| anyhow!("deserialize {}: {err}", self.source))?;
+        debug!("value parsed from {}: {:?}", self.source, v);
+        to_value(&v)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.source.clone()
+    }
+}
+
+impl<V, S> IntoCollector<V> for Kv<V, S>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+    S: KvRead + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestServer {
+        port: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        max_workers: usize,
+        tags: Vec<String>,
+        server: TestServer,
+    }
+
+    #[test]
+    fn test_from_kv() {
+        let store = BTreeMap::from([
+            ("app/max_workers".to_string(), "8".to_string()),
+            ("app/tags".to_string(), "a,b".to_string()),
+            ("app/server/port".to_string(), "80".to_string()),
+            ("other/max_workers".to_string(), "16".to_string()),
+        ]);
+
+        let mut c: Kv<TestConfig, _> = from_kv(store, "app/");
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                max_workers: 8,
+                tags: vec!["a".to_string(), "b".to_string()],
+                server: TestServer { port: 80 },
+            }
+        );
+        assert_eq!(c.describe().to_string(), "kv: app/*");
+    }
+
+    #[test]
+    fn test_from_map() {
+        let map = HashMap::from([
+            ("max-workers".to_string(), "8".to_string()),
+            ("server.port".to_string(), "80".to_string()),
+        ]);
+
+        let mut c: Kv<TestConfig, _> = from_map(map);
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.max_workers, 8);
+        assert_eq!(t.server.port, 80);
+        assert_eq!(c.describe().to_string(), "map");
+    }
+}
@@ -11,10 +11,42 @@
 //! We are supports the following collectors:
 //!
 //! - [`from_env`]: Load from current environment.
+//! - [`from_env_prefixed`]: Load from env variables with given prefix.
+//! - [`from_env_map`]: Load from a snapshot of env.
 //! - [`from_file`]: Load from file with specific format like toml.
+//! - [`from_file_optional`]: Load from file like [`from_file`], skipped if missing.
+//! - `from_user_config`: Load from file under the platform config directory, requires feature `dirs`.
+//! - [`from_glob`]: Load from all files matching a glob pattern.
+//! - [`from_file_auto`]: Load from file with format decided by [`ParserRegistry`].
 //! - [`from_reader`]: Load from [`std::io::Read`] with specific format like toml.
+//! - `from_file_templated`: Load from file rendered as a template, requires feature `template`.
+//! - `from_age_file`: Load from an age encrypted file, requires feature `age`.
+//! - `from_ansible_vault_file`: Load from an Ansible Vault encrypted file, requires feature `ansible-vault`.
+//! - `from_url`: Load from remote url over http(s), requires feature `http`.
+//! - `from_opendal`: Load from storage services like S3 via OpenDAL, requires feature `opendal`.
+//! - `from_object_store`: Load from an object store of the `object_store` crate, requires feature `object_store`.
+//! - `from_nacos`: Load from the Nacos config center, requires feature `nacos`.
 //! - [`from_str`]: Load from string with specific format like toml.
+//! - [`from_embedded!`][crate::from_embedded]: Load from a file embedded into the binary at compile time.
+//! - [`from_bytes`]: Load from in-memory bytes like a decrypted blob.
+//! - `from_lua`: Load from a sandboxed lua script, requires feature `lua`.
+//! - `from_rhai`: Load from a rhai script, requires feature `rhai`.
+//! - `from_command`: Load from stdout of an external command, requires feature `command`.
+//! - [`from_args`]: Load from command line arguments like `--key=value`.
+//! - [`from_pairs`]: Load from `key=value` pairs like `--set` overrides.
+//! - `from_clap`: Load from arguments parsed by clap, requires feature `clap`.
+//! - [`from_kv`]: Load from entries in a key-value store like sled or redb.
+//! - [`from_map`]: Load from a map of dotted keys like `database.pool_size`.
+//! - `from_vault`: Load from a HashiCorp Vault secret, requires feature `vault`.
+//! - `from_redis`: Load from a JSON blob, hash or keys under a prefix in redis, requires feature `redis`.
+//! - `from_k8s_configmap`: Load from a Kubernetes ConfigMap, requires feature `k8s`.
 //! - [`from_self`]: Load the config value itself.
+//! - [`from_self_ref`]: Load from a reference to the config value.
+//! - [`from_fn`]: Load from a closure like a database lookup.
+//! - [`from_value`]: Load from a [`Value`][crate::Value] constructed programmatically.
+//! - [`from_json_value`]: Load from a [`serde_json::Value`].
+//! - [`cached`]: Wrap a collector to fall back to its last layer cached on disk.
+//! - [`from_uri`]: Load from source described by uri like `file:///etc/app.toml`.
 //!
 //! Collectors often been used by [`Builder`][`crate::Builder`]:
 //!
@@ -45,14 +77,125 @@
 //! }
 //! ```
 
+/// Load config from a file embedded into the binary at compile time with
+/// specific format, so the shipped defaults are always available.
+///
+/// The file is embedded via `include_str!`, so `path` is relative to the
+/// file calling this macro. The content is parsed when collecting.
+///
+/// # Examples
+///
+/// ```ignore
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_file};
+/// use serfig::parsers::Toml;
+/// use serfig::{from_embedded, Builder};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         // `default.toml` is shipped next to `main.rs`.
+///         .collect(from_embedded!(Toml, "default.toml"))
+///         .collect(from_file(Toml, "config.toml"))
+///         .collect(from_env());
+///
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! from_embedded {
+    ($parser:expr, $path:literal) => {
+        $crate::collectors::from_embedded($parser, $path, include_str!($path))
+    };
+}
+
 mod collector;
-pub use collector::{Collector, IntoCollector};
+pub use collector::{Collector, IntoCollector, Skipped, SourceDescriptor, SourceVersion, Trust};
+pub(crate) use collector::{Grouped, Overrides, Trusted};
+
+mod async_collector;
+pub use async_collector::AsyncCollector;
+pub(crate) use async_collector::{defer, DynAsyncCollector, Slot};
 
-mod env;
-pub use env::from_env;
+pub(crate) mod env;
+pub use env::{from_env, from_env_map, from_env_prefixed};
+
+#[cfg(any(feature = "http", feature = "k8s"))]
+mod tls;
 
 mod structural;
-pub use structural::{from_file, from_reader, from_str};
+pub(crate) use structural::expand_path;
+#[cfg(feature = "age")]
+pub use structural::from_age_file;
+#[cfg(feature = "ansible-vault")]
+pub use structural::from_ansible_vault_file;
+#[cfg(feature = "template")]
+pub use structural::from_file_templated;
+#[cfg(feature = "lua")]
+pub use structural::from_lua;
+#[cfg(feature = "nacos")]
+pub use structural::from_nacos;
+#[cfg(feature = "object_store")]
+pub use structural::from_object_store;
+#[cfg(feature = "opendal")]
+pub use structural::from_opendal;
+#[cfg(feature = "rhai")]
+pub use structural::from_rhai;
+#[cfg(feature = "http")]
+pub use structural::from_url;
+#[cfg(feature = "dirs")]
+pub use structural::from_user_config;
+#[cfg(unix)]
+pub use structural::PermissionPolicy;
+pub use structural::{
+    from_bytes, from_embedded, from_file, from_file_optional, from_reader, from_str,
+};
+
+mod glob;
+pub use self::glob::{from_glob, Glob};
+
+#[cfg(feature = "command")]
+mod command;
+#[cfg(feature = "command")]
+pub use command::{from_command, Command, ExitPolicy};
+
+mod cli;
+#[cfg(feature = "clap")]
+pub use cli::from_clap;
+pub use cli::{from_args, from_pairs};
+
+mod kv;
+pub use kv::{from_kv, from_map, Kv, KvRead};
+
+#[cfg(feature = "vault")]
+mod vault;
+#[cfg(feature = "vault")]
+pub use vault::{from_vault, Vault};
+
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "redis")]
+pub use self::redis::{from_redis, Redis};
+
+#[cfg(feature = "k8s")]
+mod k8s;
+#[cfg(feature = "k8s")]
+pub use k8s::{from_k8s_configmap, ConfigMap};
 
 mod value;
-pub use value::from_self;
+pub use value::{from_fn, from_json_value, from_self, from_self_ref, from_value, FromFn, RawValue};
+
+mod cached;
+pub use cached::{cached, Cached};
+
+mod registry;
+pub use registry::{from_file_auto, ParserRegistry};
+
+mod uri;
+pub use uri::{from_uri, Schemes};
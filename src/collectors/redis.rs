@@ -0,0 +1,287 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, bail, Result};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor, Trust};
+use crate::value::{to_value, REDACTED};
+use crate::{snake, Collector};
+
+/// Load config from redis at `url` like `redis://127.0.0.1:6379/0`.
+///
+/// `key` decides how settings are read:
+///
+/// - A string key holds a JSON blob of the whole config.
+/// - A hash key holds fields like `server.port`, values are parsed like
+///   env values.
+/// - A key ending with `*` like `app:*` is a prefix, every string key
+///   under it is a field with the prefix stripped, `:` or `.` in the rest
+///   like `server:port` map to nested fields.
+///
+/// Requires feature `redis`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_redis};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     rate_limit: u32,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_redis("redis://127.0.0.1:6379/0", "myapp:settings"));
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_redis<V>(url: &str, key: &str) -> Redis<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    Redis {
+        phantom: PhantomData,
+        url: url.to_string(),
+        key: key.to_string(),
+    }
+}
+
+/// Collector that loads config from redis.
+pub struct Redis<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    url: String,
+    key: String,
+}
+
+impl<V: DeserializeOwned + Serialize + Debug> Debug for Redis<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Redis")
+            .field("url", &strip_credentials(&self.url))
+            .field("key", &self.key)
+            .field("credentials", &REDACTED)
+            .finish()
+    }
+}
+
+/// Remove user and password from `url` so it can be shown in reports.
+fn strip_credentials(url: &str) -> String {
+    match (url.split_once("://"), url.rsplit_once('@')) {
+        (Some((scheme, _)), Some((_, host))) => format!("{scheme}://{host}"),
+        _ => url.to_string(),
+    }
+}
+
+impl<V> Redis<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    /// Read entries under the prefix of `key` as string pairs.
+    fn scan(&self, con: &mut redis::Connection, prefix: &str) -> Result<Vec<(String, String)>> {
+        let keys: Vec<String> = redis::cmd("SCAN")
+            .cursor_arg(0)
+            .arg("MATCH")
+            .arg(&self.key)
+            .clone()
+            .iter(con)?
+            .collect();
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let values: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query(con)?;
+        Ok(keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(k, v)| Some((k.strip_prefix(prefix)?.to_string(), v?)))
+            .collect())
+    }
+
+    fn read(&self) -> Result<V> {
+        let mut con = redis::Client::open(self.url.as_str())?.get_connection()?;
+
+        let pairs = match self.key.strip_suffix('*') {
+            Some(prefix) => self.scan(&mut con, prefix)?,
+            None => {
+                let ty: String = redis::cmd("TYPE").arg(&self.key).query(&mut con)?;
+                match ty.as_str() {
+                    "string" => {
+                        let s: String = redis::cmd("GET").arg(&self.key).query(&mut con)?;
+                        return Ok(serde_json::from_str(&s)?);
+                    }
+                    "hash" => {
+                        let m: BTreeMap<String, String> =
+                            redis::cmd("HGETALL").arg(&self.key).query(&mut con)?;
+                        m.into_iter().collect()
+                    }
+                    "none" => bail!("key is not found"),
+                    ty => bail!("key of type {ty} is not supported"),
+                }
+            }
+        };
+
+        let pairs: Vec<_> = pairs
+            .into_iter()
+            .map(|(k, v)| (snake::to_snake_case(&k).replace([':', '.'], "_"), v))
+            .collect();
+        Ok(snake::from_iter(pairs)?)
+    }
+}
+
+impl<V> Collector<V> for Redis<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let v = self
+            .read()
+            .map_err(|err| anyhow!("read {}: {err}", self.describe()))?;
+        debug!("value loaded from {}: {:?}", self.describe(), v);
+        to_value(&v)
+    }
+
+    fn trust(&self) -> Trust {
+        Trust::Remote
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("redis").with_location(&format!(
+            "{}#{}",
+            strip_credentials(&self.url),
+            self.key
+        ))
+    }
+}
+
+impl<V> IntoCollector<V> for Redis<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestServer {
+        port: u16,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        rate_limit: u32,
+        server: TestServer,
+    }
+
+    fn bulk(s: &str) -> String {
+        format!("${}\r\n{s}\r\n", s.len())
+    }
+
+    fn array(items: &[String]) -> String {
+        format!("*{}\r\n{}", items.len(), items.concat())
+    }
+
+    /// Serve a tiny subset of RESP2 over `stream` until it's closed.
+    fn serve(stream: TcpStream) {
+        let mut reader = BufReader::new(stream.try_clone().expect("must clone"));
+        let mut stream = stream;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).expect("must read") == 0 {
+                return;
+            }
+            let n: usize = line.trim()[1..].parse().expect("must be array");
+            let mut args = Vec::new();
+            for _ in 0..n {
+                line.clear();
+                reader.read_line(&mut line).expect("must read");
+                line.clear();
+                reader.read_line(&mut line).expect("must read");
+                args.push(line.trim_end().to_string());
+            }
+
+            let resp = match args[0].to_uppercase().as_str() {
+                "TYPE" => match args[1].as_str() {
+                    "app:json" => "+string\r\n".to_string(),
+                    "app:hash" => "+hash\r\n".to_string(),
+                    _ => "+none\r\n".to_string(),
+                },
+                "GET" => bulk(r#"{"rate_limit":10,"server":{"port":8080}}"#),
+                "HGETALL" => array(&[
+                    bulk("rate_limit"),
+                    bulk("20"),
+                    bulk("server.port"),
+                    bulk("80"),
+                ]),
+                "SCAN" => array(&[
+                    bulk("0"),
+                    array(&[bulk("cfg:rate_limit"), bulk("cfg:server:port")]),
+                ]),
+                "MGET" => array(&[bulk("30"), bulk("443")]),
+                _ => "+OK\r\n".to_string(),
+            };
+            stream.write_all(resp.as_bytes()).expect("must write");
+        }
+    }
+
+    #[test]
+    fn test_from_redis() {
+        let _ = env_logger::try_init();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream.expect("must accept");
+                thread::spawn(move || serve(stream));
+            }
+        });
+        let url = format!("redis://:hunter2@{addr}/0");
+
+        let mut c: Redis<TestConfig> = from_redis(&url, "app:json");
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.rate_limit, 10);
+        assert_eq!(t.server.port, 8080);
+        assert!(!format!("{c:?}").contains("hunter2"));
+        assert_eq!(
+            c.describe().to_string(),
+            format!("redis: redis://{addr}/0#app:json")
+        );
+
+        let mut c: Redis<TestConfig> = from_redis(&url, "app:hash");
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.rate_limit, 20);
+        assert_eq!(t.server.port, 80);
+
+        let mut c: Redis<TestConfig> = from_redis(&url, "cfg:*");
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.rate_limit, 30);
+        assert_eq!(t.server.port, 443);
+
+        let mut c: Redis<TestConfig> = from_redis(&url, "app:missing");
+        assert!(c.collect().is_err());
+    }
+}
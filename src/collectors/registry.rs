@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::collectors::from_file;
+use crate::parsers::{DotEnv, Jsonc, Toml};
+use crate::{Collector, Parser};
+
+type Factory<V> = Box<dyn Fn(&str) -> Box<dyn Collector<V>>>;
+
+/// Load config from file, format is decided by the extension.
+///
+/// This is a shortcut of `ParserRegistry::default().from_file(path)`,
+/// only builtin parsers are supported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_file_auto;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(from_file_auto("/etc/app.json")?);
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn from_file_auto<V>(path: &str) -> Result<Box<dyn Collector<V>>>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    ParserRegistry::default().from_file(path)
+}
+
+/// ParserRegistry maps file extensions to parsers.
+///
+/// `ParserRegistry::default()` contains all builtin parsers enabled by
+/// features, users can register parsers for their own formats or
+/// override builtin ones.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::ParserRegistry;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     // Our `.ini`-like files are valid toml.
+///     let registry = ParserRegistry::<TestConfig>::default().register("ini", || Toml);
+///     let c = registry.from_file("/etc/app.ini")?;
+///     Ok(())
+/// }
+/// ```
+pub struct ParserRegistry<V: DeserializeOwned + Serialize> {
+    factories: HashMap<String, Factory<V>>,
+}
+
+impl<V> Default for ParserRegistry<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn default() -> Self {
+        let registry = Self {
+            factories: HashMap::new(),
+        }
+        .register("toml", || Toml)
+        .register("env", || DotEnv)
+        .register("json", || Jsonc)
+        .register("jsonc", || Jsonc);
+        #[cfg(feature = "cbor")]
+        let registry = registry.register("cbor", || crate::parsers::Cbor);
+        #[cfg(feature = "dhall")]
+        let registry = registry.register("dhall", || crate::parsers::Dhall);
+        #[cfg(feature = "hocon")]
+        let registry = registry
+            .register("conf", || crate::parsers::Hocon)
+            .register("hocon", || crate::parsers::Hocon);
+        #[cfg(feature = "lua")]
+        let registry = registry.register("lua", || crate::parsers::Lua);
+        #[cfg(feature = "rhai")]
+        let registry = registry.register("rhai", || crate::parsers::Rhai);
+        #[cfg(feature = "starlark")]
+        let registry = registry
+            .register("star", || crate::parsers::Starlark)
+            .register("bzl", || crate::parsers::Starlark);
+        #[cfg(feature = "plist")]
+        let registry = registry.register("plist", || crate::parsers::Plist);
+        #[cfg(feature = "jsonnet")]
+        let registry = registry.register("jsonnet", crate::parsers::Jsonnet::default);
+        registry
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> Debug for ParserRegistry<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut extensions: Vec<_> = self.factories.keys().collect();
+        extensions.sort();
+        f.debug_struct("ParserRegistry")
+            .field("extensions", &extensions)
+            .finish()
+    }
+}
+
+impl<V> ParserRegistry<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    /// Register a parser for given extension without the leading dot.
+    ///
+    /// `f` will be called to create a new parser for every file.
+    pub fn register<P>(mut self, ext: &str, f: impl Fn() -> P + 'static) -> Self
+    where
+        P: Parser + 'static,
+    {
+        self.factories.insert(
+            ext.to_lowercase(),
+            Box::new(move |path| Box::new(from_file(f(), path))),
+        );
+        self
+    }
+
+    /// Build collector for given file path.
+    ///
+    /// Dot files like `.env` are looked up by their name.
+    pub fn from_file(&self, path: &str) -> Result<Box<dyn Collector<V>>> {
+        let p = Path::new(path);
+        let ext = p
+            .extension()
+            .or_else(|| p.file_name())
+            .and_then(|v| v.to_str())
+            .map(|v| v.trim_start_matches('.').to_lowercase())
+            .unwrap_or_default();
+        let f = self
+            .factories
+            .get(&ext)
+            .ok_or_else(|| anyhow!("can't decide file format of `{path}` from extension"))?;
+        Ok(f(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        a: String,
+    }
+
+    #[test]
+    fn test_parser_registry() {
+        let registry = ParserRegistry::<TestConfig>::default();
+        for path in ["/etc/app.toml", "/etc/app.JSON", "/srv/.env"] {
+            assert!(registry.from_file(path).is_ok(), "{path} must success");
+        }
+        assert!(registry.from_file("/etc/app.ini").is_err());
+
+        let path = std::env::temp_dir().join("serfig_test_parser_registry.ini");
+        std::fs::write(&path, "a = \"ini\"").expect("write must success");
+
+        let registry = registry.register("ini", || Toml);
+        let mut c = registry
+            .from_file(path.to_str().expect("path must be utf-8"))
+            .expect("must success");
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.a, "ini");
+    }
+}
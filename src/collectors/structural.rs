@@ -1,15 +1,20 @@
 use std::fmt::Debug;
 use std::fs::File;
 use std::marker::PhantomData;
-use std::{fs, io};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io, thread};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use log::{debug, warn};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_bridge::{IntoValue, Value};
+use serde_bridge::Value;
 
-use crate::collectors::collector::IntoCollector;
-use crate::{Collector, Parser};
+use crate::collectors::collector::{IntoCollector, SourceDescriptor, SourceVersion, Trust};
+use crate::path::KeyPath;
+use crate::value::{get, to_value};
+use crate::{de, Collector, Parser};
 
 /// load config from reader with specific format.
 ///
@@ -48,14 +53,21 @@ where
     P: Parser,
 {
     Structural {
-        phantom: PhantomData::default(),
+        phantom: PhantomData,
         reader: r,
         parser,
+        source: SourceDescriptor::new("reader"),
+        namespace: None,
+        optional: false,
+        include: None,
     }
 }
 
 /// load config from file path with specific format.
 ///
+/// `~` and env variables like `$XDG_CONFIG_HOME` or `${HOME}` in path are
+/// expanded when collecting, collect fails if a variable is not set.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -89,13 +101,21 @@ where
     P: Parser,
 {
     Structural {
-        phantom: PhantomData::default(),
+        phantom: PhantomData,
         reader: LazyFileReader::new(path),
         parser,
+        source: SourceDescriptor::new("file").with_location(path),
+        namespace: None,
+        optional: false,
+        include: None,
     }
 }
 
-/// load config from string with specific format.
+/// load config from file path with specific format, or an empty layer if
+/// the file doesn't exist.
+///
+/// Only a missing file is skipped, other errors like parse errors or
+/// permission denied are still returned.
 ///
 /// # Examples
 ///
@@ -103,108 +123,1632 @@ where
 /// use serde::Deserialize;
 /// use serde::Serialize;
 /// use serfig::Builder;
-/// use serfig::collectors::from_str;
+/// use serfig::collectors::{from_file, from_file_optional};
 /// use serfig::parsers::Toml;
 ///
 /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
 /// #[serde(default)]
 /// struct TestConfig {
 ///     a: String,
-///     b: String,
-///     c: i64,
 /// }
 ///
 /// fn main() -> anyhow::Result<()> {
 ///     let builder = Builder::default()
-///         .collect(from_str(Toml, r#"a = "Hello, World!""#));
+///         .collect(from_file_optional(Toml, "/path/to/not_exist.toml"));
 ///
 ///     let t: TestConfig = builder.build()?;
 ///
-///     println!("{:?}", t);
+///     assert_eq!(t, TestConfig::default());
 ///     Ok(())
 /// }
 /// ```
-pub fn from_str<V, P>(parser: P, s: &str) -> Structural<V, &[u8], P>
+pub fn from_file_optional<V, P>(parser: P, path: &str) -> Structural<V, LazyFileReader, P>
 where
     V: DeserializeOwned + Serialize + Debug,
     P: Parser,
 {
     Structural {
-        phantom: PhantomData::default(),
-        reader: s.as_bytes(),
-        parser,
+        optional: true,
+        include: None,
+        ..from_file(parser, path)
     }
 }
 
-/// Collector that load from a reader and than parsed by specified format.
-pub struct Structural<V: DeserializeOwned + Serialize + Debug, R: io::Read, P: Parser> {
-    phantom: PhantomData<V>,
-    reader: R,
-    parser: P,
+/// load config from file under the platform config directory with specific
+/// format, or an empty layer if the file doesn't exist.
+///
+/// `path` is relative to the config directory like `myapp/config.toml`,
+/// which resolves to:
+///
+/// - Linux: `$XDG_CONFIG_HOME/myapp/config.toml` or `$HOME/.config/myapp/config.toml`
+/// - macOS: `$HOME/Library/Application Support/myapp/config.toml`
+/// - Windows: `%APPDATA%\myapp\config.toml`
+///
+/// Requires feature `dirs`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::{from_env, from_user_config};
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_user_config(Toml, "myapp/config.toml"))
+///         .collect(from_env());
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "dirs")]
+pub fn from_user_config<V, P>(parser: P, path: &str) -> Structural<V, LazyFileReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    let path = match dirs::config_dir() {
+        Some(dir) => dir.join(path).to_string_lossy().to_string(),
+        None => {
+            warn!("config directory of current platform is unknown, skip {path}");
+            String::new()
+        }
+    };
+    from_file_optional(parser, &path)
 }
 
-impl<V, R, P> Collector<V> for Structural<V, R, P>
+/// load config from a sandboxed lua script.
+///
+/// The script must return a table, see [`Lua`][crate::parsers::Lua] for
+/// details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_lua;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+///     b: String,
+///     c: i64,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_lua("config.lua"));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "lua")]
+pub fn from_lua<V>(path: &str) -> Structural<V, LazyFileReader, crate::parsers::Lua>
 where
     V: DeserializeOwned + Serialize + Debug,
-    R: io::Read,
-    P: Parser,
 {
-    fn collect(&mut self) -> Result<Value> {
-        let mut bs = Vec::new();
-        self.reader.read_to_end(&mut bs)?;
+    from_file(crate::parsers::Lua, path)
+}
 
-        let v: V = self.parser.parse(&bs)?;
-        Ok(v.into_value()?)
-    }
+/// load config from a rhai script.
+///
+/// The script must return an object map, see [`Rhai`][crate::parsers::Rhai]
+/// for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_rhai;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+///     b: String,
+///     c: i64,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_rhai("config.rhai"));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "rhai")]
+pub fn from_rhai<V>(path: &str) -> Structural<V, LazyFileReader, crate::parsers::Rhai>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    from_file(crate::parsers::Rhai, path)
 }
 
-impl<V, R, P> IntoCollector<V> for Structural<V, R, P>
+/// load config from file rendered as a [MiniJinja](https://docs.rs/minijinja)
+/// template with `context` before parsing.
+///
+/// Requires feature `template`, see [`Templated`][crate::parsers::Templated]
+/// for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_file_templated;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     addrs: Vec<String>,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let context = serde_json::json!({ "hosts": ["a", "b"] });
+///     let builder = Builder::default()
+///         .collect(from_file_templated(Toml, "config.toml.j2", &context));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "template")]
+pub fn from_file_templated<V, P, C>(
+    parser: P,
+    path: &str,
+    context: &C,
+) -> Structural<V, LazyFileReader, crate::parsers::Templated<P>>
 where
-    V: DeserializeOwned + Serialize + Debug + 'static,
-    R: io::Read + 'static,
-    P: Parser + 'static,
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+    C: Serialize,
 {
-    fn into_collector(self) -> Box<dyn Collector<V>> {
-        Box::new(self)
-    }
+    from_file(crate::parsers::Templated::new(parser, context), path)
 }
 
-pub struct LazyFileReader {
-    path: String,
-    r: Option<File>,
+/// load config from [age](https://age-encryption.org) encrypted file
+/// decrypted with `identity` before parsing.
+///
+/// Requires feature `age`, see [`Age`][crate::parsers::Age] for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_age_file;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     db_password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let identity = std::env::var("MYAPP_AGE_KEY")?;
+///     let builder = Builder::default()
+///         .collect(from_age_file(Toml, "secrets.toml.age", &identity));
+///
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "age")]
+pub fn from_age_file<V, P>(
+    parser: P,
+    path: &str,
+    identity: &str,
+) -> Structural<V, LazyFileReader, crate::parsers::Age<P>>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    from_file(crate::parsers::Age::new(parser, identity), path)
 }
 
-impl LazyFileReader {
-    fn new(path: &str) -> LazyFileReader {
-        LazyFileReader {
-            path: path.to_string(),
-            r: None,
-        }
-    }
+/// load config from [Ansible Vault](https://docs.ansible.com/ansible/latest/vault_guide/index.html)
+/// encrypted file decrypted with the password in `password_file` before
+/// parsing, so vars files encrypted by `ansible-vault` can be reused.
+///
+/// Requires feature `ansible-vault`, see
+/// [`AnsibleVault`][crate::parsers::AnsibleVault] for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_ansible_vault_file;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     db_password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(from_ansible_vault_file(
+///         Toml,
+///         "secrets.toml",
+///         "/etc/myapp/vault-pass",
+///     ));
+///
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "ansible-vault")]
+pub fn from_ansible_vault_file<V, P>(
+    parser: P,
+    path: &str,
+    password_file: &str,
+) -> Structural<V, LazyFileReader, crate::parsers::AnsibleVault<P>>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    from_file(
+        crate::parsers::AnsibleVault::with_password_file(parser, password_file),
+        path,
+    )
 }
 
-impl io::Read for LazyFileReader {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match &mut self.r {
-            None => {
-                let f = fs::File::open(&self.path)?;
-                self.r = Some(f);
-                self.read(buf)
-            }
-            Some(f) => f.read(buf),
-        }
+/// load config from remote url over http(s) with specific format.
+///
+/// The content is fetched when collecting, non-2xx responses are treated
+/// as errors. Auth, headers, TLS, timeouts and retries can be set via
+/// methods like [`Structural::with_bearer_auth`] and
+/// [`Structural::with_retry`].
+///
+/// Requires feature `http`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::{from_file, from_url};
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+///     b: String,
+///     c: i64,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_url(Toml, "https://config.example.com/app.toml"))
+///         .collect(from_file(Toml, "config.toml"));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "http")]
+pub fn from_url<V, P>(parser: P, url: &str) -> Structural<V, LazyUrlReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    Structural {
+        phantom: PhantomData,
+        reader: LazyUrlReader::new(url),
+        parser,
+        source: SourceDescriptor::new("url").with_location(url),
+        namespace: None,
+        optional: false,
+        include: None,
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use log::debug;
-    use serde::{Deserialize, Serialize};
-    use serde_bridge::FromValue;
-
-    use super::*;
-    use crate::parsers::Toml;
-
+/// load config from `path` in storage services like S3, GCS, Azblob, HDFS
+/// or WebDAV via [OpenDAL](https://opendal.apache.org) with specific
+/// format.
+///
+/// Services are enabled by features of `opendal` in the application. The
+/// content is read when collecting, values are trusted as
+/// [`Trust::Remote`].
+///
+/// Requires feature `opendal`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use opendal::{Operator, Scheme};
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_opendal;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     // Requires feature `services-s3` of opendal.
+///     let op = Operator::via_iter(Scheme::S3, [("bucket".to_string(), "configs".to_string())])?
+///         .blocking();
+///     let builder = Builder::default()
+///         .collect(from_opendal(Toml, op, "myapp/config.toml"));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "opendal")]
+pub fn from_opendal<V, P>(
+    parser: P,
+    op: opendal::BlockingOperator,
+    path: &str,
+) -> Structural<V, LazyOpendalReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    let location = format!("{}://{path}", op.info().scheme());
+    Structural {
+        phantom: PhantomData,
+        reader: LazyOpendalReader::new(op, path),
+        parser,
+        source: SourceDescriptor::new("opendal").with_location(&location),
+        namespace: None,
+        optional: false,
+        include: None,
+    }
+}
+
+/// load config from `path` in an [`ObjectStore`][object_store::ObjectStore]
+/// of the [object_store](https://docs.rs/object_store) crate with specific
+/// format.
+///
+/// The object is fetched when collecting on a dedicated current thread
+/// runtime, so collecting must not happen inside an async runtime, use
+/// `spawn_blocking` there. Values are trusted as [`Trust::Remote`].
+///
+/// Requires feature `object_store`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use object_store::memory::InMemory;
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_object_store;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     // Like `AmazonS3Builder::from_env().build()?` in practice.
+///     let store = Arc::new(InMemory::new());
+///     let builder = Builder::default()
+///         .collect(from_object_store(Toml, store, "myapp/config.toml"));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "object_store")]
+pub fn from_object_store<V, P>(
+    parser: P,
+    store: std::sync::Arc<dyn object_store::ObjectStore>,
+    path: &str,
+) -> Structural<V, LazyObjectStoreReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    let location = format!("{store}/{path}");
+    Structural {
+        phantom: PhantomData,
+        reader: LazyObjectStoreReader::new(store, path),
+        parser,
+        source: SourceDescriptor::new("object_store").with_location(&location),
+        namespace: None,
+        optional: false,
+        include: None,
+    }
+}
+
+/// load config `data_id` in `group` from [Nacos](https://nacos.io) at
+/// `server` like `http://127.0.0.1:8848` with specific format.
+///
+/// The config is fetched via the open API when collecting, values are
+/// trusted as [`Trust::Remote`]. Use [`Structural::with_namespace`] and
+/// [`Structural::with_auth`] for namespaces and auth enabled servers.
+/// Servers behind `https://` are verified against the bundled Mozilla
+/// root certificates.
+///
+/// Requires feature `nacos`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_nacos;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(
+///         from_nacos(Toml, "http://127.0.0.1:8848", "myapp.toml", "DEFAULT_GROUP")
+///             .with_namespace("prod")
+///             .with_auth("nacos", "nacos"),
+///     );
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "nacos")]
+pub fn from_nacos<V, P>(
+    parser: P,
+    server: &str,
+    data_id: &str,
+    group: &str,
+) -> Structural<V, LazyNacosReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    let reader = LazyNacosReader::new(server, data_id, group);
+    Structural {
+        phantom: PhantomData,
+        source: SourceDescriptor::new("nacos").with_location(&reader.location()),
+        reader,
+        parser,
+        namespace: None,
+        optional: false,
+        include: None,
+    }
+}
+
+/// load config from string with specific format.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_str;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+///     b: String,
+///     c: i64,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_str(Toml, r#"a = "Hello, World!""#));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn from_str<V, P>(parser: P, s: &str) -> Structural<V, &[u8], P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    Structural {
+        phantom: PhantomData,
+        reader: s.as_bytes(),
+        parser,
+        source: SourceDescriptor::new("str"),
+        namespace: None,
+        optional: false,
+        include: None,
+    }
+}
+
+/// load config from a file embedded at compile time with specific format.
+///
+/// Use the [`from_embedded!`][crate::from_embedded] macro instead, which
+/// embeds the file via `include_str!` and passes its path here so the
+/// layer can be told apart from other strings in reports.
+pub fn from_embedded<V, P>(
+    parser: P,
+    path: &str,
+    s: &'static str,
+) -> Structural<V, &'static [u8], P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    Structural {
+        phantom: PhantomData,
+        reader: s.as_bytes(),
+        parser,
+        source: SourceDescriptor::new("embedded").with_location(path),
+        namespace: None,
+        optional: false,
+        include: None,
+    }
+}
+
+/// load config from in-memory bytes with specific format.
+///
+/// Useful for content that never touches disk like a decrypted blob or
+/// a network payload.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde::Serialize;
+/// use serfig::Builder;
+/// use serfig::collectors::from_bytes;
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+///     b: String,
+///     c: i64,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let bs = br#"a = "Hello, World!""#.to_vec();
+///     let builder = Builder::default()
+///         .collect(from_bytes(Toml, bs));
+///
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.a, "Hello, World!");
+///     Ok(())
+/// }
+/// ```
+pub fn from_bytes<V, P>(parser: P, bs: Vec<u8>) -> Structural<V, io::Cursor<Vec<u8>>, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    Structural {
+        phantom: PhantomData,
+        reader: io::Cursor::new(bs),
+        parser,
+        source: SourceDescriptor::new("bytes"),
+        namespace: None,
+        optional: false,
+        include: None,
+    }
+}
+
+/// Collector that load from a reader and than parsed by specified format.
+pub struct Structural<V: DeserializeOwned + Serialize + Debug, R: io::Read, P: Parser> {
+    phantom: PhantomData<V>,
+    reader: R,
+    parser: P,
+    source: SourceDescriptor,
+    namespace: Option<String>,
+    /// Treat missing input as an empty layer.
+    optional: bool,
+    /// Load files referenced by `include` directives, only set for file
+    /// sources.
+    include: Option<Includes>,
+}
+
+/// State of `include` directives.
+struct Includes {
+    /// Reader carrying the retry and permission settings of the including
+    /// file, reopened for every included file.
+    reader: LazyFileReader,
+    /// Versions of files included during the last collect.
+    versions: Vec<SourceVersion>,
+}
+
+impl<V, R, P> Structural<V, R, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    R: io::Read,
+    P: Parser,
+{
+    /// Only take keys under `prefix` like `myapp.`, and strip the prefix
+    /// before merging.
+    ///
+    /// This allows multiple apps to share one central document, each
+    /// taking its own namespace. A missing namespace is treated as empty.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// use serde::Serialize;
+    /// use serfig::Builder;
+    /// use serfig::collectors::from_file;
+    /// use serfig::parsers::Toml;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     port: u16,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // company.toml contains `[myapp]` and `[otherapp]` tables.
+    ///     let builder = Builder::default()
+    ///         .collect(from_file(Toml, "company.toml").strip_prefix_keys("myapp."));
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn strip_prefix_keys(mut self, prefix: &str) -> Self {
+        self.namespace = Some(prefix.trim_end_matches('.').to_string());
+        self
+    }
+
+    /// Read all input, returns `None` if it's optional and not found.
+    fn read(&mut self) -> Result<Option<Vec<u8>>> {
+        let mut bs = Vec::new();
+        match self.reader.read_to_end(&mut bs) {
+            Ok(_) => Ok(Some(bs)),
+            Err(err) if self.optional && err.kind() == io::ErrorKind::NotFound => {
+                debug!("optional {} not found, skip", self.source);
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Read and parse the input, stripping the namespace if set.
+    fn parse_raw(&mut self) -> Result<Value> {
+        let raw = if self.optional {
+            match self.read()? {
+                Some(bs) => self.parser.parse_value(&bs)?,
+                None => return Ok(Value::Map(Default::default())),
+            }
+        } else {
+            self.parser.parse_value_reader(&mut self.reader)?
+        };
+        let raw = match (&mut self.include, self.source.location()) {
+            (None, _) => raw,
+            (Some(include), Some(path)) if self.source.kind() == "file" => {
+                include.versions.clear();
+                let path = Path::new(&expand_path(path)?).canonicalize()?;
+                self.load_includes(raw, &mut vec![path])?
+            }
+            (Some(_), _) => bail!(
+                "include is only supported by files, but got {}",
+                self.source
+            ),
+        };
+        let namespace = match &self.namespace {
+            Some(namespace) => namespace,
+            None => return Ok(raw),
+        };
+        let path: KeyPath = namespace.parse()?;
+        Ok(get(&raw, &path)
+            .cloned()
+            .unwrap_or_else(|| Value::Map(Default::default())))
+    }
+
+    /// Load files referenced by the `include` directive of `v` and merge
+    /// `v` on top of them.
+    ///
+    /// `stack` holds the chain of files being loaded, the last one is the
+    /// file of `v`.
+    fn load_includes(&mut self, v: Value, stack: &mut Vec<PathBuf>) -> Result<Value> {
+        let mut m = match v {
+            Value::Map(m) => m,
+            v => return Ok(v),
+        };
+        let includes = match INCLUDE_KEYS
+            .iter()
+            .find_map(|k| m.shift_remove(&Value::Str(k.to_string())))
+        {
+            Some(Value::Str(s)) => vec![s],
+            Some(Value::Seq(vs)) => vs
+                .into_iter()
+                .map(|v| match v {
+                    Value::Str(s) => Ok(s),
+                    v => Err(anyhow!("include must be paths, but got {v:?}")),
+                })
+                .collect::<Result<_>>()?,
+            Some(v) => bail!("include must be paths, but got {v:?}"),
+            None => return Ok(Value::Map(m)),
+        };
+
+        let current = stack.last().cloned().unwrap_or_default();
+        let mut base = Value::Map(Default::default());
+        for include in includes {
+            let path = current
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(expand_path(&include)?);
+            let path = path.canonicalize().map_err(|err| {
+                anyhow!(
+                    "include {} from {}: {err}",
+                    path.display(),
+                    current.display()
+                )
+            })?;
+            if stack.contains(&path) {
+                let chain: Vec<_> = stack.iter().map(|p| p.display().to_string()).collect();
+                bail!(
+                    "include cycle detected: {} -> {}",
+                    chain.join(" -> "),
+                    path.display()
+                );
+            }
+
+            debug!("include {} from {}", path.display(), current.display());
+            let include = self
+                .include
+                .as_mut()
+                .ok_or_else(|| anyhow!("include is not enabled"))?;
+            let location = path.display().to_string();
+            // Record version before reading like the including file.
+            include.versions.push(SourceVersion::of_file(
+                SourceDescriptor::new("file").with_location(&location),
+                &path,
+            ));
+            let mut bs = Vec::new();
+            io::Read::read_to_end(&mut include.reader.reopen(&location), &mut bs)?;
+            let v = self
+                .parser
+                .parse_value(&bs)
+                .map_err(|err| anyhow!("parse included {}: {err}", path.display()))?;
+            stack.push(path);
+            let v = self.load_includes(v, stack)?;
+            stack.pop();
+            overlay(&mut base, v);
+        }
+        overlay(&mut base, Value::Map(m));
+        Ok(base)
+    }
+}
+
+/// Keys of the directive to include other files.
+const INCLUDE_KEYS: [&str; 2] = ["include", "@include"];
+
+/// Merge maps in `r` into `l` recursively, other values in `r` replace
+/// the ones in `l`.
+fn overlay(l: &mut Value, r: Value) {
+    match (l, r) {
+        (Value::Map(lm), Value::Map(rm)) => {
+            for (k, rv) in rm {
+                match lm.get_mut(&k) {
+                    Some(lv) => overlay(lv, rv),
+                    None => {
+                        lm.insert(k, rv);
+                    }
+                }
+            }
+        }
+        (l, r) => *l = r,
+    }
+}
+
+impl<V, R, P> Collector<V> for Structural<V, R, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    R: io::Read,
+    P: Parser,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let v: V = if self.namespace.is_none() && !self.optional && self.include.is_none() {
+            self.parser.parse_reader(&mut self.reader)?
+        } else {
+            de::from_value(self.parse_raw()?)?
+        };
+        to_value(&v)
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        Ok(Some(self.parse_raw()?))
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.source.clone()
+    }
+
+    fn version(&self) -> Option<SourceVersion> {
+        match (self.source.kind(), self.source.location()) {
+            ("file", Some(path)) => Some(SourceVersion::of_file(
+                self.source.clone(),
+                expand_path(path).unwrap_or_else(|_| path.to_string()),
+            )),
+            _ => None,
+        }
+    }
+
+    fn included(&mut self) -> Vec<SourceVersion> {
+        self.include
+            .as_mut()
+            .map(|include| std::mem::take(&mut include.versions))
+            .unwrap_or_default()
+    }
+
+    fn trust(&self) -> Trust {
+        match (self.source.kind(), self.source.location()) {
+            ("url" | "opendal" | "object_store" | "nacos", _) => Trust::Remote,
+            ("file", Some(path)) if path.starts_with("/etc/") => Trust::System,
+            _ => Trust::User,
+        }
+    }
+}
+
+impl<V, R, P> IntoCollector<V> for Structural<V, R, P>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+    R: io::Read + 'static,
+    P: Parser + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+impl<V, P> Structural<V, LazyFileReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    /// Retry transient io errors (like `EINTR`, `EAGAIN` or timeouts on
+    /// network filesystems) at most `times` times.
+    ///
+    /// The delay between retries starts from `backoff` and doubles after
+    /// every attempt.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use serde::Deserialize;
+    /// use serde::Serialize;
+    /// use serfig::Builder;
+    /// use serfig::collectors::from_file;
+    /// use serfig::parsers::Toml;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_file(Toml, "config.toml").with_retry(3, Duration::from_millis(10)));
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_retry(mut self, times: usize, backoff: Duration) -> Self {
+        self.reader.retry = times;
+        self.reader.backoff = backoff;
+        if let Some(include) = &mut self.include {
+            include.reader.retry = times;
+            include.reader.backoff = backoff;
+        }
+        self
+    }
+
+    /// Load other files referenced by the top level `include` (or
+    /// `@include`) directive like `include = ["base.toml", "secrets.toml"]`.
+    ///
+    /// Included files are parsed with the same parser, resolved relative
+    /// to the file including them, and may include other files. They are
+    /// merged in order, and the including file is merged last so it
+    /// overrides them. Include cycles are rejected with an error.
+    ///
+    /// Included files are read with the retry and permission settings of
+    /// this file, and changes to them are detected like this file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// use serde::Serialize;
+    /// use serfig::Builder;
+    /// use serfig::collectors::from_file;
+    /// use serfig::parsers::Toml;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     // config.toml contains `include = ["other.toml", "secrets.toml"]`.
+    ///     let builder = Builder::default().collect(from_file(Toml, "config.toml").with_includes());
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_includes(mut self) -> Self {
+        self.include = Some(Includes {
+            reader: self.reader.reopen(&self.reader.path),
+            versions: Vec::new(),
+        });
+        self
+    }
+}
+
+#[cfg(unix)]
+impl<V, P> Structural<V, LazyFileReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    /// Check the permissions of the config file before reading, like
+    /// OpenSSH does for its config and key files.
+    ///
+    /// A file is treated as insecure if it's writable by group or others,
+    /// or it's not owned by the current user or root.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// use serde::Serialize;
+    /// use serfig::Builder;
+    /// use serfig::collectors::{from_file, PermissionPolicy};
+    /// use serfig::parsers::Toml;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default()
+    ///         .collect(from_file(Toml, "secret.toml").with_permission_check(PermissionPolicy::Deny));
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_permission_check(mut self, policy: PermissionPolicy) -> Self {
+        self.reader.permission = policy;
+        if let Some(include) = &mut self.include {
+            include.reader.permission = policy;
+        }
+        self
+    }
+}
+
+/// PermissionPolicy decides what to do if the config file has insecure
+/// permissions.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PermissionPolicy {
+    /// Don't check permissions.
+    #[default]
+    Ignore,
+    /// Log a warning and continue.
+    Warn,
+    /// Return a [`io::ErrorKind::PermissionDenied`] error.
+    Deny,
+}
+
+/// Reader that will open the file until the first read happens.
+///
+/// All errors returned by this reader will carry the file path while
+/// keeping the original [`io::ErrorKind`], so callers can still tell
+/// [`io::ErrorKind::NotFound`] from [`io::ErrorKind::PermissionDenied`].
+pub struct LazyFileReader {
+    path: String,
+    r: Option<File>,
+    retry: usize,
+    backoff: Duration,
+    #[cfg(unix)]
+    permission: PermissionPolicy,
+}
+
+impl LazyFileReader {
+    fn new(path: &str) -> LazyFileReader {
+        LazyFileReader {
+            path: path.to_string(),
+            r: None,
+            retry: 0,
+            backoff: Duration::from_millis(10),
+            #[cfg(unix)]
+            permission: PermissionPolicy::Ignore,
+        }
+    }
+
+    /// Create a reader of `path` with the same settings.
+    fn reopen(&self, path: &str) -> LazyFileReader {
+        LazyFileReader {
+            path: path.to_string(),
+            r: None,
+            retry: self.retry,
+            backoff: self.backoff,
+            #[cfg(unix)]
+            permission: self.permission,
+        }
+    }
+
+    /// Check permissions of opened file according to the policy.
+    #[cfg(unix)]
+    fn check_permission(&self, f: &File) -> io::Result<()> {
+        use std::os::unix::fs::MetadataExt;
+
+        if self.permission == PermissionPolicy::Ignore {
+            return Ok(());
+        }
+
+        let meta = f.metadata()?;
+        // SAFETY: geteuid never fails and has no side effects.
+        let uid = unsafe { libc::geteuid() };
+        let problem = if meta.mode() & 0o022 != 0 {
+            format!(
+                "mode {:o} is writable by group or others",
+                meta.mode() & 0o777
+            )
+        } else if meta.uid() != uid && meta.uid() != 0 {
+            format!("owner {} is neither current user nor root", meta.uid())
+        } else {
+            return Ok(());
+        };
+
+        match self.permission {
+            PermissionPolicy::Deny => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("read file {}: insecure permissions: {problem}", self.path),
+            )),
+            _ => {
+                warn!("file {} has insecure permissions: {problem}", self.path);
+                Ok(())
+            }
+        }
+    }
+
+    /// Run `f` and retry it if transient errors happened.
+    fn with_retry<T>(&self, mut f: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match f() {
+                Err(err) if is_transient(&err) && attempt < self.retry => {
+                    attempt += 1;
+                    warn!(
+                        "read file {} failed: {}, retry {}/{} after {:?}",
+                        self.path, err, attempt, self.retry, backoff
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                Err(err) => {
+                    return Err(io::Error::new(
+                        err.kind(),
+                        format!("read file {}: {}", self.path, err),
+                    ))
+                }
+                Ok(v) => return Ok(v),
+            }
+        }
+    }
+}
+
+/// Expand leading `~` and env variables like `$HOME` or `${HOME}` in path.
+pub(crate) fn expand_path(path: &str) -> io::Result<String> {
+    let var = |name: &str| {
+        env::var(name).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("expand path {path}: env `{name}` is not set"),
+            )
+        })
+    };
+
+    let mut out = String::with_capacity(path.len());
+    let mut rest = path;
+    if let Some(home) = rest
+        .strip_prefix('~')
+        .filter(|r| r.is_empty() || r.starts_with('/'))
+    {
+        out.push_str(&var("HOME")?);
+        rest = home;
+    }
+    while let Some((head, tail)) = rest.split_once('$') {
+        out.push_str(head);
+        rest = tail;
+        let (name, next) = match rest.strip_prefix('{') {
+            Some(braced) => braced.split_once('}').unwrap_or((braced, "")),
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                rest.split_at(end)
+            }
+        };
+        if name.is_empty() {
+            out.push('$');
+            continue;
+        }
+        out.push_str(&var(name)?);
+        rest = next;
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+impl io::Read for LazyFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut f = match self.r.take() {
+            Some(f) => f,
+            None => {
+                let path = expand_path(&self.path)?;
+                let f = self.with_retry(|| fs::File::open(&path))?;
+                #[cfg(unix)]
+                self.check_permission(&f)?;
+                f
+            }
+        };
+        let result = self.with_retry(|| f.read(buf));
+        self.r = Some(f);
+        result
+    }
+}
+
+/// Reader that will fetch the url until the first read happens.
+#[cfg(feature = "http")]
+pub struct LazyUrlReader {
+    url: String,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    ca: Option<Vec<u8>>,
+    identity: Option<(Vec<u8>, Vec<u8>)>,
+    retry: usize,
+    backoff: Duration,
+    r: Option<Box<dyn io::Read + Send + Sync>>,
+}
+
+#[cfg(feature = "http")]
+impl LazyUrlReader {
+    fn new(url: &str) -> LazyUrlReader {
+        LazyUrlReader {
+            url: url.to_string(),
+            headers: Vec::new(),
+            timeout: None,
+            ca: None,
+            identity: None,
+            retry: 0,
+            backoff: Duration::from_millis(100),
+            r: None,
+        }
+    }
+
+    /// Fetch the url, retry on transport errors, `429` and `5xx`.
+    fn fetch(&self) -> io::Result<ureq::Response> {
+        let identity = self
+            .identity
+            .as_ref()
+            .map(|(cert, key)| (cert.as_slice(), key.as_slice()));
+        let agent = super::tls::agent(self.ca.as_deref(), identity, self.timeout)
+            .map_err(|err| io::Error::other(format!("fetch {}: {err}", self.url)))?;
+
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            let mut req = agent.get(&self.url);
+            for (k, v) in &self.headers {
+                req = req.set(k, v);
+            }
+            match req.call() {
+                Ok(resp) => return Ok(resp),
+                Err(err) if is_retryable(&err) && attempt < self.retry => {
+                    attempt += 1;
+                    warn!(
+                        "fetch {} failed: {err}, retry {attempt}/{} after {backoff:?}",
+                        self.url, self.retry
+                    );
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                Err(err) => return Err(io::Error::other(format!("fetch {}: {err}", self.url))),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+fn is_retryable(err: &ureq::Error) -> bool {
+    match err {
+        ureq::Error::Status(code, _) => *code == 429 || *code >= 500,
+        ureq::Error::Transport(_) => true,
+    }
+}
+
+#[cfg(feature = "http")]
+impl<V, P> Structural<V, LazyUrlReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    /// Send header `name: value` with the request.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.reader
+            .headers
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Authenticate with a bearer token.
+    pub fn with_bearer_auth(self, token: &str) -> Self {
+        self.with_header("Authorization", &format!("Bearer {token}"))
+    }
+
+    /// Authenticate with http basic auth.
+    pub fn with_basic_auth(self, username: &str, password: &str) -> Self {
+        use base64::Engine;
+
+        let credentials =
+            base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+        self.with_header("Authorization", &format!("Basic {credentials}"))
+    }
+
+    /// Fail the request if it doesn't finish within `timeout`, including
+    /// connecting and reading the body.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.reader.timeout = Some(timeout);
+        self
+    }
+
+    /// Trust the PEM encoded certificates in `ca` instead of the bundled
+    /// web roots, like a private CA of the config service.
+    pub fn with_ca_cert(mut self, ca: &[u8]) -> Self {
+        self.reader.ca = Some(ca.to_vec());
+        self
+    }
+
+    /// Authenticate with the PEM encoded client certificate and key for
+    /// mutual TLS.
+    pub fn with_client_cert(mut self, cert: &[u8], key: &[u8]) -> Self {
+        self.reader.identity = Some((cert.to_vec(), key.to_vec()));
+        self
+    }
+
+    /// Retry transport errors, `429` and `5xx` responses at most `times`
+    /// times.
+    ///
+    /// The delay between retries starts from `backoff` and doubles after
+    /// every attempt.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use serde::Deserialize;
+    /// use serde::Serialize;
+    /// use serfig::Builder;
+    /// use serfig::collectors::from_url;
+    /// use serfig::parsers::Toml;
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     a: String,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let builder = Builder::default().collect(
+    ///         from_url(Toml, "https://config.example.com/app.toml")
+    ///             .with_bearer_auth("token")
+    ///             .with_header("X-Env", "prod")
+    ///             .with_timeout(Duration::from_secs(5))
+    ///             .with_retry(3, Duration::from_millis(200)),
+    ///     );
+    ///
+    ///     let t: TestConfig = builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_retry(mut self, times: usize, backoff: Duration) -> Self {
+        self.reader.retry = times;
+        self.reader.backoff = backoff;
+        self
+    }
+}
+
+#[cfg(feature = "http")]
+impl io::Read for LazyUrlReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => {
+                let resp = self.fetch()?;
+                self.r.insert(resp.into_reader())
+            }
+        };
+        r.read(buf)
+            .map_err(|err| io::Error::new(err.kind(), format!("read url {}: {err}", self.url)))
+    }
+}
+
+/// Reader that will read from opendal until the first read happens.
+#[cfg(feature = "opendal")]
+pub struct LazyOpendalReader {
+    op: opendal::BlockingOperator,
+    path: String,
+    r: Option<io::Cursor<Vec<u8>>>,
+}
+
+#[cfg(feature = "opendal")]
+impl LazyOpendalReader {
+    fn new(op: opendal::BlockingOperator, path: &str) -> LazyOpendalReader {
+        LazyOpendalReader {
+            op,
+            path: path.to_string(),
+            r: None,
+        }
+    }
+}
+
+#[cfg(feature = "opendal")]
+impl io::Read for LazyOpendalReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => {
+                let bs = self.op.read(&self.path).map_err(|err| {
+                    let kind = match err.kind() {
+                        opendal::ErrorKind::NotFound => io::ErrorKind::NotFound,
+                        opendal::ErrorKind::PermissionDenied => io::ErrorKind::PermissionDenied,
+                        _ => io::ErrorKind::Other,
+                    };
+                    io::Error::new(kind, format!("read {}: {err}", self.path))
+                })?;
+                self.r.insert(io::Cursor::new(bs.to_vec()))
+            }
+        };
+        r.read(buf)
+    }
+}
+
+/// Reader that will fetch the object until the first read happens.
+#[cfg(feature = "object_store")]
+pub struct LazyObjectStoreReader {
+    store: std::sync::Arc<dyn object_store::ObjectStore>,
+    path: String,
+    r: Option<io::Cursor<Vec<u8>>>,
+}
+
+#[cfg(feature = "object_store")]
+impl LazyObjectStoreReader {
+    fn new(store: std::sync::Arc<dyn object_store::ObjectStore>, path: &str) -> Self {
+        LazyObjectStoreReader {
+            store,
+            path: path.to_string(),
+            r: None,
+        }
+    }
+
+    fn fetch(&self) -> io::Result<Vec<u8>> {
+        use object_store::ObjectStoreExt;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let path = object_store::path::Path::from(self.path.as_str());
+        rt.block_on(async {
+            let resp = self.store.get(&path).await?;
+            resp.bytes().await
+        })
+        .map(|bs| bs.to_vec())
+        .map_err(|err| {
+            let kind = match err {
+                object_store::Error::NotFound { .. } => io::ErrorKind::NotFound,
+                object_store::Error::PermissionDenied { .. } => io::ErrorKind::PermissionDenied,
+                _ => io::ErrorKind::Other,
+            };
+            io::Error::new(kind, format!("fetch {}: {err}", self.path))
+        })
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl io::Read for LazyObjectStoreReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => {
+                let bs = self.fetch()?;
+                self.r.insert(io::Cursor::new(bs))
+            }
+        };
+        r.read(buf)
+    }
+}
+
+/// Reader that will fetch the nacos config until the first read happens.
+#[cfg(feature = "nacos")]
+pub struct LazyNacosReader {
+    server: String,
+    data_id: String,
+    group: String,
+    namespace: Option<String>,
+    auth: Option<(String, String)>,
+    r: Option<io::Cursor<Vec<u8>>>,
+}
+
+#[cfg(feature = "nacos")]
+impl LazyNacosReader {
+    fn new(server: &str, data_id: &str, group: &str) -> Self {
+        LazyNacosReader {
+            server: server.trim_end_matches('/').to_string(),
+            data_id: data_id.to_string(),
+            group: group.to_string(),
+            namespace: None,
+            auth: None,
+            r: None,
+        }
+    }
+
+    fn location(&self) -> String {
+        match &self.namespace {
+            Some(ns) => format!("{}#{ns}/{}/{}", self.server, self.group, self.data_id),
+            None => format!("{}#{}/{}", self.server, self.group, self.data_id),
+        }
+    }
+
+    /// Login with username and password, returns the access token.
+    fn login(&self, username: &str, password: &str) -> Result<String> {
+        let body = ureq::post(&format!("{}/nacos/v1/auth/login", self.server))
+            .send_form(&[("username", username), ("password", password)])?
+            .into_string()?;
+        let resp: serde_json::Value = serde_json::from_str(&body)?;
+        resp.get("accessToken")
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .ok_or_else(|| anyhow::anyhow!("access token is missing"))
+    }
+
+    fn fetch(&self) -> io::Result<Vec<u8>> {
+        let mut req = ureq::get(&format!("{}/nacos/v1/cs/configs", self.server))
+            .query("dataId", &self.data_id)
+            .query("group", &self.group);
+        if let Some(ns) = &self.namespace {
+            req = req.query("tenant", ns);
+        }
+        if let Some((username, password)) = &self.auth {
+            let token = self
+                .login(username, password)
+                .map_err(|err| io::Error::other(format!("login nacos {}: {err}", self.server)))?;
+            req = req.query("accessToken", &token);
+        }
+
+        let resp = req.call().map_err(|err| {
+            let kind = match err {
+                ureq::Error::Status(404, _) => io::ErrorKind::NotFound,
+                ureq::Error::Status(401 | 403, _) => io::ErrorKind::PermissionDenied,
+                _ => io::ErrorKind::Other,
+            };
+            io::Error::new(kind, format!("fetch {}: {err}", self.location()))
+        })?;
+        let mut bs = Vec::new();
+        resp.into_reader().read_to_end(&mut bs)?;
+        Ok(bs)
+    }
+}
+
+#[cfg(feature = "nacos")]
+impl io::Read for LazyNacosReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => {
+                let bs = self.fetch()?;
+                self.r.insert(io::Cursor::new(bs))
+            }
+        };
+        r.read(buf)
+    }
+}
+
+#[cfg(feature = "nacos")]
+impl<V, P> Structural<V, LazyNacosReader, P>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    P: Parser,
+{
+    /// Read the config from nacos namespace `namespace`, the public
+    /// namespace is used by default.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.reader.namespace = Some(namespace.to_string());
+        self.source = SourceDescriptor::new("nacos").with_location(&self.reader.location());
+        self
+    }
+
+    /// Login with `username` and `password` before fetching, required by
+    /// servers with auth enabled.
+    pub fn with_auth(mut self, username: &str, password: &str) -> Self {
+        self.reader.auth = Some((username.to_string(), password.to_string()));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::debug;
+    use serde::{Deserialize, Serialize};
+    use serde_bridge::FromValue;
+
+    use super::*;
+    use crate::parsers::Toml;
+
     #[derive(Debug, Serialize, Deserialize, PartialEq)]
     struct TestStruct {
         #[serde(rename = "serfig_test_str")]
@@ -230,4 +1774,507 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_from_bytes() {
+        let _ = env_logger::try_init();
+
+        let mut c: Structural<TestStruct, io::Cursor<Vec<u8>>, Toml> =
+            from_bytes(Toml, br#"serfig_test_str = "test_bytes""#.to_vec());
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "test_bytes");
+        assert_eq!(Collector::<TestStruct>::describe(&c).kind(), "bytes");
+    }
+
+    #[test]
+    fn test_strip_prefix_keys() {
+        let _ = env_logger::try_init();
+
+        let doc = r#"
+[myapp]
+serfig_test_str = "myapp"
+
+[otherapp]
+serfig_test_str = "otherapp"
+"#;
+        let mut c: Structural<TestStruct, &[u8], Toml> =
+            from_str(Toml, doc).strip_prefix_keys("myapp.");
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "myapp");
+
+        let mut c: Structural<TestStruct, &[u8], Toml> =
+            from_str(Toml, doc).strip_prefix_keys("unknown.");
+        // Missing namespace is empty, so required fields are missing.
+        assert!(c.collect().is_err());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_from_url() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let _ = env_logger::try_init();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            for (status, body) in [
+                ("200 OK", r#"serfig_test_str = "remote""#),
+                ("404 Not Found", ""),
+            ] {
+                let (mut stream, _) = listener.accept().expect("must accept");
+                let _ = stream.read(&mut [0; 1024]);
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .expect("must write");
+            }
+        });
+
+        let url = format!("http://{addr}/app.toml");
+        let mut c: Structural<TestStruct, LazyUrlReader, Toml> = from_url(Toml, &url);
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "remote");
+
+        let mut c: Structural<TestStruct, LazyUrlReader, Toml> = from_url(Toml, &url);
+        let err = c.collect().expect_err("must fail");
+        assert!(err.to_string().contains("404"), "{err}");
+        server.join().expect("server must exit");
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_from_url_options() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in [
+                ("503 Service Unavailable", ""),
+                ("200 OK", r#"serfig_test_str = "retried""#),
+            ] {
+                let (mut stream, _) = listener.accept().expect("must accept");
+                let mut buf = [0; 4096];
+                let n = stream.read(&mut buf).expect("must read");
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .expect("must write");
+            }
+            requests
+        });
+
+        let mut c: Structural<TestStruct, LazyUrlReader, Toml> =
+            from_url(Toml, &format!("http://{addr}/app.toml"))
+                .with_basic_auth("user", "pass")
+                .with_header("X-Env", "prod")
+                .with_timeout(Duration::from_secs(5))
+                .with_retry(1, Duration::from_millis(1));
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "retried");
+
+        let requests = server.join().expect("server must exit");
+        assert_eq!(requests.len(), 2);
+        // base64 of `user:pass`.
+        assert!(requests[1].contains("authorization: basic dxnlcjpwyxnz"));
+        assert!(requests[1].contains("x-env: prod"));
+    }
+
+    #[cfg(feature = "opendal")]
+    #[test]
+    fn test_from_opendal() {
+        let op = opendal::Operator::new(opendal::services::Memory::default())
+            .expect("must build")
+            .finish()
+            .blocking();
+        op.write("app/config.toml", r#"serfig_test_str = "opendal""#)
+            .expect("must write");
+
+        let mut c: Structural<TestStruct, LazyOpendalReader, Toml> =
+            from_opendal(Toml, op.clone(), "app/config.toml");
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "opendal");
+        assert_eq!(
+            Collector::<TestStruct>::describe(&c).to_string(),
+            "opendal: memory://app/config.toml"
+        );
+        assert_eq!(Collector::<TestStruct>::trust(&c), Trust::Remote);
+
+        let mut c: Structural<TestStruct, LazyOpendalReader, Toml> =
+            from_opendal(Toml, op, "app/not_exist.toml");
+        let err = c.collect().expect_err("must fail");
+        let err = err.downcast::<io::Error>().expect("must be io error");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "object_store")]
+    #[test]
+    fn test_from_object_store() {
+        use std::sync::Arc;
+
+        use object_store::memory::InMemory;
+        use object_store::ObjectStoreExt;
+
+        let store = Arc::new(InMemory::new());
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("must build runtime")
+            .block_on(store.put(
+                &"app/config.toml".into(),
+                r#"serfig_test_str = "object_store""#.into(),
+            ))
+            .expect("must put");
+
+        let mut c: Structural<TestStruct, LazyObjectStoreReader, Toml> =
+            from_object_store(Toml, store.clone(), "app/config.toml");
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "object_store");
+        assert_eq!(Collector::<TestStruct>::trust(&c), Trust::Remote);
+
+        let mut c: Structural<TestStruct, LazyObjectStoreReader, Toml> =
+            from_object_store(Toml, store, "app/not_exist.toml");
+        let err = c.collect().expect_err("must fail");
+        let err = err.downcast::<io::Error>().expect("must be io error");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "nacos")]
+    #[test]
+    fn test_from_nacos() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let _ = env_logger::try_init();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in [
+                ("200 OK", r#"{"accessToken":"test-token","tokenTtl":18000}"#),
+                ("200 OK", r#"serfig_test_str = "nacos""#),
+                ("404 Not Found", "config data not exist"),
+            ] {
+                let (mut stream, _) = listener.accept().expect("must accept");
+                // Requests may arrive in several segments, read until the
+                // headers and the form body are complete.
+                let mut req = Vec::new();
+                let mut buf = [0; 4096];
+                while !String::from_utf8_lossy(&req).contains("\r\n\r\n")
+                    || (req.starts_with(b"POST") && req.ends_with(b"\r\n\r\n"))
+                {
+                    let n = stream.read(&mut buf).expect("must read");
+                    req.extend_from_slice(&buf[..n]);
+                }
+                requests.push(String::from_utf8_lossy(&req).to_string());
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .expect("must write");
+            }
+            requests
+        });
+
+        let server_addr = format!("http://{addr}");
+        let mut c: Structural<TestStruct, LazyNacosReader, Toml> =
+            from_nacos(Toml, &server_addr, "app.toml", "DEFAULT_GROUP")
+                .with_namespace("prod")
+                .with_auth("nacos", "secret");
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "nacos");
+        assert_eq!(Collector::<TestStruct>::trust(&c), Trust::Remote);
+        assert_eq!(
+            c.describe().to_string(),
+            format!("nacos: {server_addr}#prod/DEFAULT_GROUP/app.toml")
+        );
+
+        let mut c: Structural<TestStruct, LazyNacosReader, Toml> =
+            from_nacos(Toml, &server_addr, "missing.toml", "DEFAULT_GROUP");
+        let err = c.collect().expect_err("must fail");
+        let err = err.downcast::<io::Error>().expect("must be io error");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let requests = server.join().expect("server must exit");
+        assert!(requests[0].starts_with("POST /nacos/v1/auth/login"));
+        assert!(requests[0].contains("username=nacos&password=secret"));
+        assert!(requests[1].starts_with(
+            "GET /nacos/v1/cs/configs?dataId=app.toml&group=DEFAULT_GROUP&tenant=prod&accessToken=test-token"
+        ));
+    }
+
+    #[cfg(feature = "nacos")]
+    #[test]
+    fn test_from_nacos_https() {
+        use std::io::Read;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("must accept");
+            let mut buf = [0; 1];
+            stream.read_exact(&mut buf).expect("must read");
+            buf[0]
+        });
+
+        let mut c: Structural<TestStruct, LazyNacosReader, Toml> = from_nacos(
+            Toml,
+            &format!("https://{addr}"),
+            "app.toml",
+            "DEFAULT_GROUP",
+        );
+        let err = c.collect().expect_err("must fail");
+        assert!(!format!("{err:#}").contains("no TLS backend"), "{err:#}");
+
+        // The first byte of a TLS handshake record.
+        assert_eq!(server.join().expect("server must exit"), 0x16);
+    }
+
+    #[test]
+    fn test_from_embedded() {
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+        #[serde(default)]
+        struct Package {
+            name: String,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+        #[serde(default)]
+        struct Manifest {
+            package: Package,
+        }
+
+        let mut c: Structural<Manifest, &[u8], Toml> =
+            crate::from_embedded!(Toml, "../../Cargo.toml");
+        let t = Manifest::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.package.name, "serfig");
+        assert_eq!(c.describe().to_string(), "embedded: ../../Cargo.toml");
+    }
+
+    #[test]
+    fn test_from_file_not_found() {
+        let _ = env_logger::try_init();
+
+        let mut c: Structural<TestStruct, LazyFileReader, Toml> =
+            from_file(Toml, "/path/to/not_exist.toml").with_retry(3, Duration::from_millis(1));
+
+        let err = c.collect().expect_err("must fail");
+        debug!("error: {:?}", err);
+
+        let err = err.downcast::<io::Error>().expect("must be io error");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("/path/to/not_exist.toml"));
+    }
+
+    #[test]
+    fn test_from_file_optional() {
+        let _ = env_logger::try_init();
+
+        let mut c: Structural<TestStruct, LazyFileReader, Toml> =
+            from_file_optional(Toml, "/path/to/not_exist.toml");
+        // Missing file is empty, so required fields are missing.
+        let err = c.collect().expect_err("must fail");
+        assert!(err.to_string().contains("serfig_test_str"), "{err}");
+        assert_eq!(
+            c.collect_raw().expect("must success"),
+            Some(Value::Map(Default::default()))
+        );
+
+        let path = std::env::temp_dir().join("serfig-optional.toml");
+        fs::write(&path, "serfig_test_str = ").expect("write file");
+        let mut c: Structural<TestStruct, LazyFileReader, Toml> =
+            from_file_optional(Toml, path.to_str().unwrap());
+        assert!(c.collect().is_err());
+
+        fs::write(&path, r#"serfig_test_str = "test_str""#).expect("write file");
+        let mut c: Structural<TestStruct, LazyFileReader, Toml> =
+            from_file_optional(Toml, path.to_str().unwrap());
+        let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.test_str, "test_str");
+    }
+
+    #[test]
+    fn test_from_file_includes() {
+        let _ = env_logger::try_init();
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+        #[serde(default)]
+        struct TestServer {
+            host: String,
+            port: u16,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+        #[serde(default)]
+        struct TestConfig {
+            name: String,
+            password: String,
+            server: TestServer,
+        }
+
+        let dir = std::env::temp_dir().join(format!("serfig-includes-{}", std::process::id()));
+        fs::create_dir_all(dir.join("conf.d")).expect("create dir");
+        fs::write(
+            dir.join("conf.d/base.toml"),
+            "name = \"base\"\n[server]\nhost = \"0.0.0.0\"\nport = 80",
+        )
+        .expect("write file");
+        fs::write(
+            dir.join("conf.d/secrets.toml"),
+            "include = \"base.toml\"\npassword = \"hunter2\"",
+        )
+        .expect("write file");
+        fs::write(
+            dir.join("config.toml"),
+            "include = [\"conf.d/base.toml\", \"conf.d/secrets.toml\"]\n[server]\nport = 8080",
+        )
+        .expect("write file");
+
+        let mut c: Structural<TestConfig, LazyFileReader, Toml> =
+            from_file(Toml, dir.join("config.toml").to_str().unwrap()).with_includes();
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.name, "base");
+        assert_eq!(t.password, "hunter2");
+        assert_eq!(t.server.host, "0.0.0.0");
+        assert_eq!(t.server.port, 8080);
+        let included: Vec<_> = Collector::<TestConfig>::included(&mut c)
+            .into_iter()
+            .map(|v| v.path)
+            .collect();
+        let base = dir
+            .join("conf.d/base.toml")
+            .canonicalize()
+            .expect("canonicalize");
+        let secrets = dir
+            .join("conf.d/secrets.toml")
+            .canonicalize()
+            .expect("canonicalize");
+        assert_eq!(included, vec![base.clone(), secrets.clone(), base]);
+
+        // Included files are checked like the including file.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(&secrets, fs::Permissions::from_mode(0o666))
+                .expect("set permissions");
+            let mut c: Structural<TestConfig, LazyFileReader, Toml> =
+                from_file(Toml, dir.join("config.toml").to_str().unwrap())
+                    .with_includes()
+                    .with_permission_check(PermissionPolicy::Deny);
+            let err = c.collect().expect_err("must fail");
+            assert!(err.to_string().contains("insecure permissions"), "{err}");
+            fs::set_permissions(&secrets, fs::Permissions::from_mode(0o600))
+                .expect("set permissions");
+        }
+
+        // Without includes, the directive is an unknown key.
+        let mut c: Structural<TestConfig, LazyFileReader, Toml> =
+            from_file(Toml, dir.join("config.toml").to_str().unwrap());
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("from value");
+        assert_eq!(t.name, "");
+
+        fs::write(
+            dir.join("conf.d/base.toml"),
+            "\"@include\" = \"../config.toml\"",
+        )
+        .expect("write file");
+        let mut c: Structural<TestConfig, LazyFileReader, Toml> =
+            from_file(Toml, dir.join("config.toml").to_str().unwrap()).with_includes();
+        let err = c.collect().expect_err("must fail");
+        assert!(err.to_string().contains("include cycle detected"), "{err}");
+
+        fs::remove_dir_all(&dir).expect("remove dir");
+    }
+
+    #[cfg(all(feature = "dirs", target_os = "linux"))]
+    #[test]
+    fn test_from_user_config() {
+        let dir = std::env::temp_dir().join("serfig-user-config");
+        fs::create_dir_all(dir.join("myapp")).expect("create dir");
+        fs::write(dir.join("myapp/config.toml"), r#"serfig_test_str = "user""#)
+            .expect("write file");
+
+        temp_env::with_var("XDG_CONFIG_HOME", Some(&dir), || {
+            let mut c: Structural<TestStruct, LazyFileReader, Toml> =
+                from_user_config(Toml, "myapp/config.toml");
+            let t = TestStruct::from_value(c.collect().expect("must success")).expect("from value");
+            assert_eq!(t.test_str, "user");
+
+            let mut c: Structural<TestStruct, LazyFileReader, Toml> =
+                from_user_config(Toml, "other/config.toml");
+            assert_eq!(
+                c.collect_raw().expect("must success"),
+                Some(Value::Map(Default::default()))
+            );
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_from_file_permission() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _ = env_logger::try_init();
+
+        let path = std::env::temp_dir().join("serfig-permission.toml");
+        fs::write(&path, r#"serfig_test_str = "test_str""#).expect("write file");
+        let path_str = path.to_str().unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o666)).expect("chmod");
+        let mut c: Structural<TestStruct, _, _> =
+            from_file(Toml, path_str).with_permission_check(PermissionPolicy::Deny);
+        let err = c.collect().expect_err("must fail");
+        let err = err.downcast::<io::Error>().expect("must be io error");
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "read file {path_str}: insecure permissions: mode 666 is writable by group or others"
+            )
+        );
+
+        let mut c: Structural<TestStruct, _, _> =
+            from_file(Toml, path_str).with_permission_check(PermissionPolicy::Warn);
+        assert!(c.collect().is_ok());
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).expect("chmod");
+        let mut c: Structural<TestStruct, _, _> =
+            from_file(Toml, path_str).with_permission_check(PermissionPolicy::Deny);
+        assert!(c.collect().is_ok());
+    }
+
+    #[test]
+    fn test_expand_path() {
+        temp_env::with_vars(
+            [
+                ("HOME", Some("/home/serfig")),
+                ("SERFIG_TEST_DIR", Some("/etc")),
+                ("SERFIG_TEST_NOT_SET", None),
+            ],
+            || {
+                assert_eq!(
+                    expand_path("~/.config/$SERFIG_TEST_DIR/${SERFIG_TEST_DIR}x/$/a.toml")
+                        .expect("must success"),
+                    "/home/serfig/.config//etc//etcx/$/a.toml"
+                );
+                assert_eq!(expand_path("a~/b").expect("must success"), "a~/b");
+
+                let err = expand_path("$SERFIG_TEST_NOT_SET/a.toml").expect_err("must fail");
+                assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+                assert!(err.to_string().contains("`SERFIG_TEST_NOT_SET`"));
+            },
+        );
+    }
 }
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+/// Build an http agent trusting `ca` (PEM) instead of the bundled roots,
+/// and authenticating with `identity` (PEM cert and key) if set.
+pub(crate) fn agent(
+    ca: Option<&[u8]>,
+    identity: Option<(&[u8], &[u8])>,
+    timeout: Option<Duration>,
+) -> Result<ureq::Agent> {
+    let mut builder = ureq::AgentBuilder::new();
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    if ca.is_none() && identity.is_none() {
+        return Ok(builder.build());
+    }
+
+    let mut roots = rustls::RootCertStore::empty();
+    match ca {
+        Some(ca) => {
+            for cert in CertificateDer::pem_slice_iter(ca) {
+                roots.add(cert?)?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+    let tls = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_root_certificates(roots);
+    let tls = match identity {
+        Some((cert, key)) => tls.with_client_auth_cert(
+            CertificateDer::pem_slice_iter(cert).collect::<Result<_, _>>()?,
+            PrivateKeyDer::from_pem_slice(key)?,
+        )?,
+        None => tls.with_no_client_auth(),
+    };
+    Ok(builder.tls_config(Arc::new(tls)).build())
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::collectors::{from_env, from_env_prefixed, ParserRegistry};
+use crate::Collector;
+
+type Factory<V> = Box<dyn Fn(&str) -> Result<Box<dyn Collector<V>>>>;
+
+/// Load config from the source described by uri.
+///
+/// This is a shortcut of `Schemes::default().resolve(uri)`, only builtin
+/// schemes are supported:
+///
+/// - `file:///path/to/config.toml`: load from file, format is decided
+///   by the extension.
+/// - `env://`: load from current environment.
+/// - `env://MYAPP`: load from env variables starting with `MYAPP_`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_uri;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_uri("file:///etc/app.toml")?)
+///         .collect(from_uri("env://")?);
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn from_uri<V>(uri: &str) -> Result<Box<dyn Collector<V>>>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    Schemes::default().resolve(uri)
+}
+
+/// Schemes maps uri schemes to collector factories.
+///
+/// `Schemes::default()` contains builtin `file` and `env` schemes, users
+/// can register their own schemes like `https` or override builtin ones.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use std::io::Cursor;
+///
+/// use serfig::collectors::{from_reader, Schemes};
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let schemes = Schemes::<TestConfig>::default().register("https", |uri| {
+///         # let content = uri.to_string();
+///         // Fetch content from uri.
+///         Ok(Box::new(from_reader(Toml, Cursor::new(content))))
+///     });
+///     let c = schemes.resolve("https://example.com/app.toml")?;
+///     Ok(())
+/// }
+/// ```
+pub struct Schemes<V: DeserializeOwned + Serialize> {
+    factories: HashMap<String, Factory<V>>,
+}
+
+impl<V> Default for Schemes<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn default() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+        .register("file", from_file_uri)
+        .register("env", from_env_uri)
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> Debug for Schemes<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut schemes: Vec<_> = self.factories.keys().collect();
+        schemes.sort();
+        f.debug_struct("Schemes")
+            .field("schemes", &schemes)
+            .finish()
+    }
+}
+
+impl<V> Schemes<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    /// Resolve `file` scheme with given parser registry instead of the
+    /// builtin one.
+    pub fn with_parsers(self, registry: ParserRegistry<V>) -> Self {
+        self.register("file", move |uri| {
+            let path = &uri["file://".len()..];
+            if path.is_empty() {
+                bail!("invalid uri `{uri}`: file path is empty");
+            }
+            registry
+                .from_file(path)
+                .map_err(|err| anyhow!("invalid uri `{uri}`: {err}"))
+        })
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> Schemes<V> {
+    /// Register a factory for given scheme.
+    ///
+    /// The factory will be called with the full uri.
+    pub fn register(
+        mut self,
+        scheme: &str,
+        f: impl Fn(&str) -> Result<Box<dyn Collector<V>>> + 'static,
+    ) -> Self {
+        self.factories.insert(scheme.to_lowercase(), Box::new(f));
+        self
+    }
+
+    /// Build collector for given uri.
+    pub fn resolve(&self, uri: &str) -> Result<Box<dyn Collector<V>>> {
+        let (scheme, _) = uri
+            .split_once("://")
+            .ok_or_else(|| anyhow!("invalid uri `{uri}`: missing scheme"))?;
+        let f = self
+            .factories
+            .get(&scheme.to_lowercase())
+            .ok_or_else(|| anyhow!("invalid uri `{uri}`: scheme `{scheme}` is not supported"))?;
+        f(uri)
+    }
+}
+
+fn from_file_uri<V>(uri: &str) -> Result<Box<dyn Collector<V>>>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    let path = &uri["file://".len()..];
+    if path.is_empty() {
+        bail!("invalid uri `{uri}`: file path is empty");
+    }
+
+    ParserRegistry::default()
+        .from_file(path)
+        .map_err(|err| anyhow!("invalid uri `{uri}`: {err}"))
+}
+
+fn from_env_uri<V>(uri: &str) -> Result<Box<dyn Collector<V>>>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    let prefix = &uri["env://".len()..];
+    if prefix.is_empty() {
+        return Ok(Box::new(from_env()));
+    }
+    Ok(Box::new(from_env_prefixed(prefix)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::collectors::from_reader;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        a: String,
+    }
+
+    #[test]
+    fn test_from_uri() {
+        let c = from_uri::<TestConfig>("file:///etc/app.toml").expect("must success");
+        assert_eq!(c.describe().to_string(), "file: /etc/app.toml");
+        let c = from_uri::<TestConfig>("ENV://").expect("must success");
+        assert_eq!(c.describe().to_string(), "env");
+        let c = from_uri::<TestConfig>("env://MYAPP").expect("must success");
+        assert_eq!(c.describe().to_string(), "env: MYAPP_*");
+
+        for uri in [
+            "/etc/app.toml",
+            "file://",
+            "file:///etc/app.yaml",
+            "https://example.com/app.toml",
+        ] {
+            assert!(from_uri::<TestConfig>(uri).is_err(), "{uri} must fail");
+        }
+
+        let schemes = Schemes::<TestConfig>::default().register("mem", |uri| {
+            let content = format!("a = {:?}", &uri["mem://".len()..]);
+            Ok(Box::new(from_reader(Toml, Cursor::new(content))))
+        });
+        let mut c = schemes.resolve("mem://hello").expect("must success");
+        assert!(c.collect().is_ok());
+    }
+}
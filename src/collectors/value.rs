@@ -1,12 +1,14 @@
 use std::fmt::Debug;
+use std::marker::PhantomData;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use serde_bridge::{IntoValue, Value};
+use serde_bridge::Value;
 
-use crate::collectors::collector::IntoCollector;
-use crate::Collector;
+use crate::collectors::collector::{IntoCollector, SourceDescriptor};
+use crate::value::{from_json, to_value};
+use crate::{de, Collector};
 
 /// load config from `Self`.
 ///
@@ -52,7 +54,14 @@ where
     V: DeserializeOwned + Serialize + Debug,
 {
     fn collect(&mut self) -> Result<Value> {
-        Ok(self.0.take().expect("contains valid value").into_value()?)
+        match self.0.take() {
+            Some(v) => to_value(&v),
+            None => Err(anyhow!("value has already been collected")),
+        }
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("self")
     }
 }
 
@@ -65,6 +74,328 @@ where
     }
 }
 
+/// load config from a reference to `V`.
+///
+/// The value is serialized immediately, so large defaults owned
+/// elsewhere don't need to be cloned.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_self_ref};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let defaults = TestConfig::default();
+///     let builder = Builder::default()
+///         .collect(from_self_ref(&defaults))
+///         .collect(from_env());
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t, defaults);
+///     Ok(())
+/// }
+/// ```
+pub fn from_self_ref<V>(v: &V) -> FromSelfRef<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    FromSelfRef {
+        phantom: PhantomData,
+        value: Some(to_value(v)),
+    }
+}
+
+/// Collectors that can load configs from a reference to self.
+pub struct FromSelfRef<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    value: Option<Result<Value>>,
+}
+
+impl<V> Collector<V> for FromSelfRef<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        self.value
+            .take()
+            .unwrap_or_else(|| Err(anyhow!("value has already been collected")))
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("self")
+    }
+}
+
+impl<V> IntoCollector<V> for FromSelfRef<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+/// Load config from a closure like a database lookup or computed
+/// defaults.
+///
+/// The closure is called on every collect.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_fn};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_fn(|| {
+///             Ok(TestConfig {
+///                 workers: std::thread::available_parallelism()?.get(),
+///             })
+///         }))
+///         .collect(from_env());
+///     let t: TestConfig = builder.build()?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn from_fn<V, F>(f: F) -> FromFn<V, F>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    F: FnMut() -> Result<V>,
+{
+    FromFn {
+        phantom: PhantomData,
+        f,
+    }
+}
+
+/// Collectors that can load configs from a closure.
+pub struct FromFn<V, F>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    F: FnMut() -> Result<V>,
+{
+    phantom: PhantomData<V>,
+    f: F,
+}
+
+impl<V, F> Collector<V> for FromFn<V, F>
+where
+    V: DeserializeOwned + Serialize + Debug,
+    F: FnMut() -> Result<V>,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let v = (self.f)()?;
+        to_value(&v)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("fn")
+    }
+}
+
+impl<V, F> IntoCollector<V> for FromFn<V, F>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+    F: FnMut() -> Result<V> + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+/// Closures returning `V` can be passed to
+/// [`Builder::collect()`][crate::Builder::collect()] directly, so existing
+/// loaders can be adopted without wrapping them in [`from_fn`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_env;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn legacy_load() -> anyhow::Result<TestConfig> {
+///     Ok(TestConfig { workers: 4 })
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(legacy_load).collect(from_env());
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.workers, 4);
+///     Ok(())
+/// }
+/// ```
+impl<V, F> IntoCollector<V> for F
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+    F: FnMut() -> Result<V> + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(from_fn(self))
+    }
+}
+
+/// Load config from a [`Value`] constructed programmatically, like
+/// values produced by plugins.
+///
+/// The value can be partial like a map with only some keys, it's
+/// deserialized into `V` when collecting so unknown or mistyped keys are
+/// reported like other sources.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_value};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let v = serde_bridge::into_value(BTreeMap::from([("workers", 8)]))?;
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_value(v));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.workers, 8);
+///     Ok(())
+/// }
+/// ```
+pub fn from_value<V>(v: Value) -> RawValue<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    RawValue {
+        phantom: PhantomData,
+        value: Ok(v),
+        source: SourceDescriptor::new("value"),
+    }
+}
+
+/// Load config from a [`serde_json::Value`], like responses of APIs that
+/// already return JSON values.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_json_value};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     server: Server,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let v = serde_json::json!({ "server": { "port": 8080 } });
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_json_value(v));
+///     let t: TestConfig = builder.build()?;
+///
+///     assert_eq!(t.server.port, 8080);
+///     Ok(())
+/// }
+/// ```
+pub fn from_json_value<V>(v: serde_json::Value) -> RawValue<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    RawValue {
+        phantom: PhantomData,
+        value: Ok(from_json(v)),
+        source: SourceDescriptor::new("json"),
+    }
+}
+
+/// Collectors that can load configs from a [`Value`].
+///
+/// Created by [`from_value`] or [`from_json_value`].
+pub struct RawValue<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    /// Value or the error happened while converting it.
+    value: std::result::Result<Value, String>,
+    source: SourceDescriptor,
+}
+
+impl<V> RawValue<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn value(&self) -> Result<Value> {
+        self.value
+            .clone()
+            .map_err(|err| anyhow!("convert {}: {err}", self.source))
+    }
+}
+
+impl<V> Collector<V> for RawValue<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let v: V = de::from_value(self.value()?)?;
+        to_value(&v)
+    }
+
+    fn collect_raw(&mut self) -> Result<Option<Value>> {
+        self.value().map(Some)
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        self.source.clone()
+    }
+}
+
+impl<V> IntoCollector<V> for RawValue<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use log::debug;
@@ -98,6 +429,76 @@ mod tests {
             TestStruct {
                 test_str: "Hello, World!".to_string()
             }
-        )
+        );
+        // Value can only be collected once.
+        assert!(c.collect().is_err());
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let mut calls = 0;
+        let mut c = from_fn(|| {
+            calls += 1;
+            Ok(TestStruct {
+                test_str: format!("call {calls}"),
+            })
+        });
+
+        let t = TestStruct::from_value(c.collect().expect("collect")).expect("from value");
+        assert_eq!(t.test_str, "call 1");
+        let t = TestStruct::from_value(c.collect().expect("collect")).expect("from value");
+        assert_eq!(t.test_str, "call 2");
+    }
+
+    #[test]
+    fn test_into_collector_for_fn() {
+        let mut c = (|| {
+            Ok(TestStruct {
+                test_str: "Hello, World!".to_string(),
+            })
+        })
+        .into_collector();
+
+        let t = TestStruct::from_value(c.collect().expect("collect")).expect("from value");
+        assert_eq!(t.test_str, "Hello, World!");
+        assert_eq!(c.describe().kind(), "fn");
+    }
+
+    #[test]
+    fn test_from_self_ref() {
+        let raw = TestStruct {
+            test_str: "Hello, World!".to_string(),
+        };
+
+        let mut c = from_self_ref(&raw);
+        let t = TestStruct::from_value(c.collect().expect("collect")).expect("from value");
+        assert_eq!(t, raw);
+        assert!(c.collect().is_err());
+    }
+
+    #[test]
+    fn test_from_value() {
+        let v = serde_bridge::into_value(std::collections::BTreeMap::from([(
+            "serfig_test_str",
+            "Hello, World!",
+        )]))
+        .expect("into value");
+
+        let mut c: RawValue<TestStruct> = from_value(v.clone());
+        let t = TestStruct::from_value(c.collect().expect("collect")).expect("from value");
+        assert_eq!(t.test_str, "Hello, World!");
+        assert_eq!(c.collect_raw().expect("collect raw"), Some(v));
+
+        let mut c: RawValue<TestStruct> = from_value(Value::U64(1));
+        assert!(c.collect().is_err());
+    }
+
+    #[test]
+    fn test_from_json_value() {
+        let mut c: RawValue<TestStruct> =
+            from_json_value(serde_json::json!({ "serfig_test_str": "Hello, World!" }));
+        let t = TestStruct::from_value(c.collect().expect("collect")).expect("from value");
+        assert_eq!(t.test_str, "Hello, World!");
+        assert_eq!(c.describe().kind(), "json");
     }
 }
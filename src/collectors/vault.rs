@@ -0,0 +1,281 @@
+use std::env;
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::collectors::collector::{IntoCollector, SourceDescriptor, Trust};
+use crate::value::{to_value, REDACTED};
+use crate::Collector;
+
+/// Load config from a [HashiCorp Vault](https://www.vaultproject.io) KV
+/// version 2 secret at `path` under `mount` like `secret`.
+///
+/// Fields of the secret map to fields of the config. The token is taken
+/// from env `VAULT_TOKEN` unless [`Vault::with_token`] or
+/// [`Vault::with_approle`] is used.
+///
+/// Both `http://` and `https://` addresses are supported, servers are
+/// verified against the bundled Mozilla root certificates.
+///
+/// Requires feature `vault`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_vault};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     db_password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_env())
+///         .collect(from_vault("https://vault:8200", "secret", "myapp").with_approle("role", "secret"));
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub fn from_vault<V>(addr: &str, mount: &str, path: &str) -> Vault<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    Vault {
+        phantom: PhantomData,
+        addr: addr.trim_end_matches('/').to_string(),
+        mount: mount.trim_matches('/').to_string(),
+        path: path.trim_matches('/').to_string(),
+        auth: VaultAuth::Env,
+    }
+}
+
+/// How to authenticate against vault.
+enum VaultAuth {
+    /// Read token from env `VAULT_TOKEN`.
+    Env,
+    Token(String),
+    AppRole {
+        role_id: String,
+        secret_id: String,
+    },
+}
+
+/// Collector that loads config from a vault secret.
+pub struct Vault<V: DeserializeOwned + Serialize + Debug> {
+    phantom: PhantomData<V>,
+    addr: String,
+    mount: String,
+    path: String,
+    auth: VaultAuth,
+}
+
+impl<V: DeserializeOwned + Serialize + Debug> Debug for Vault<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let auth = match &self.auth {
+            VaultAuth::Env => "env",
+            VaultAuth::Token(_) => "token",
+            VaultAuth::AppRole { .. } => "approle",
+        };
+        f.debug_struct("Vault")
+            .field("addr", &self.addr)
+            .field("mount", &self.mount)
+            .field("path", &self.path)
+            .field("auth", &auth)
+            .field("credentials", &REDACTED)
+            .finish()
+    }
+}
+
+impl<V> Vault<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    /// Authenticate with given token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.auth = VaultAuth::Token(token.to_string());
+        self
+    }
+
+    /// Authenticate with the [AppRole](https://developer.hashicorp.com/vault/docs/auth/approle)
+    /// auth method mounted at `approle`.
+    pub fn with_approle(mut self, role_id: &str, secret_id: &str) -> Self {
+        self.auth = VaultAuth::AppRole {
+            role_id: role_id.to_string(),
+            secret_id: secret_id.to_string(),
+        };
+        self
+    }
+
+    fn token(&self) -> Result<String> {
+        match &self.auth {
+            VaultAuth::Env => {
+                env::var("VAULT_TOKEN").map_err(|_| anyhow!("env `VAULT_TOKEN` is not set"))
+            }
+            VaultAuth::Token(token) => Ok(token.clone()),
+            VaultAuth::AppRole { role_id, secret_id } => {
+                let body = serde_json::json!({ "role_id": role_id, "secret_id": secret_id });
+                let resp: serde_json::Value = send(
+                    ureq::post(&format!("{}/v1/auth/approle/login", self.addr)),
+                    Some(&body),
+                )
+                .map_err(|err| anyhow!("login with approle: {err}"))?;
+                resp.pointer("/auth/client_token")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .ok_or_else(|| anyhow!("login with approle: client token is missing"))
+            }
+        }
+    }
+}
+
+/// Send request and decode the json response.
+fn send(req: ureq::Request, body: Option<&serde_json::Value>) -> Result<serde_json::Value> {
+    let resp = match body {
+        Some(body) => req
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string()),
+        None => req.call(),
+    }?;
+    Ok(serde_json::from_str(&resp.into_string()?)?)
+}
+
+impl<V> Collector<V> for Vault<V>
+where
+    V: DeserializeOwned + Serialize + Debug,
+{
+    fn collect(&mut self) -> Result<Value> {
+        let token = self.token()?;
+        let url = format!("{}/v1/{}/data/{}", self.addr, self.mount, self.path);
+        let mut resp = send(ureq::get(&url).set("X-Vault-Token", &token), None)
+            .map_err(|err| anyhow!("read vault secret {}/{}: {err}", self.mount, self.path))?;
+
+        let data = resp
+            .pointer_mut("/data/data")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| {
+                anyhow!(
+                    "read vault secret {}/{}: data is missing",
+                    self.mount,
+                    self.path
+                )
+            })?;
+        let v: V = serde_json::from_value(data).map_err(|err| {
+            anyhow!(
+                "deserialize vault secret {}/{}: {err}",
+                self.mount,
+                self.path
+            )
+        })?;
+        debug!(
+            "value loaded from vault secret {}/{}",
+            self.mount, self.path
+        );
+        to_value(&v)
+    }
+
+    fn trust(&self) -> Trust {
+        Trust::Remote
+    }
+
+    fn describe(&self) -> SourceDescriptor {
+        SourceDescriptor::new("vault").with_location(&format!(
+            "{}/v1/{}/data/{}",
+            self.addr, self.mount, self.path
+        ))
+    }
+}
+
+impl<V> IntoCollector<V> for Vault<V>
+where
+    V: DeserializeOwned + Serialize + Debug + 'static,
+{
+    fn into_collector(self) -> Box<dyn Collector<V>> {
+        Box::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use serde::Deserialize;
+    use serde_bridge::FromValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        db_password: String,
+    }
+
+    #[test]
+    fn test_from_vault() {
+        let _ = env_logger::try_init();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for body in [
+                r#"{"auth":{"client_token":"s.test"}}"#,
+                r#"{"data":{"data":{"db_password":"hunter2"}}}"#,
+            ] {
+                let (mut stream, _) = listener.accept().expect("must accept");
+                let mut buf = [0; 4096];
+                let n = stream.read(&mut buf).expect("must read");
+                requests.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .expect("must write");
+            }
+            requests
+        });
+
+        let mut c: Vault<TestConfig> = from_vault(&format!("http://{addr}/"), "secret", "myapp")
+            .with_approle("test-role-id", "test-secret-id");
+        assert!(!format!("{c:?}").contains("test-secret-id"));
+
+        let t = TestConfig::from_value(c.collect().expect("must success")).expect("must success");
+        assert_eq!(t.db_password, "hunter2");
+
+        let requests = server.join().expect("server must exit");
+        assert!(requests[0].starts_with("post /v1/auth/approle/login"));
+        assert!(requests[1].starts_with("get /v1/secret/data/myapp"));
+        assert!(requests[1].contains("x-vault-token: s.test"));
+    }
+
+    #[test]
+    fn test_from_vault_https() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("must bind");
+        let addr = listener.local_addr().expect("must have addr");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("must accept");
+            let mut buf = [0; 1];
+            stream.read_exact(&mut buf).expect("must read");
+            buf[0]
+        });
+
+        let mut c: Vault<TestConfig> =
+            from_vault(&format!("https://{addr}"), "secret", "myapp").with_token("s.test");
+        let err = c.collect().expect_err("must fail");
+        assert!(!err.to_string().contains("no TLS backend"), "{err}");
+
+        // The first byte of a TLS handshake record.
+        assert_eq!(server.join().expect("server must exit"), 0x16);
+    }
+}
@@ -0,0 +1,272 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::path::KeyPath;
+use crate::value::{get, render};
+
+/// Rule declared on a key path by [`Builder::range`][crate::Builder::range]
+/// or [`Builder::one_of`][crate::Builder::one_of].
+///
+/// Rules are checked after build, and can be listed by
+/// [`Builder::annotations`][crate::Builder::annotations] to generate
+/// schemas or docs from the same declaration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    /// Number must be within inclusive bounds, `None` means unbounded.
+    Range {
+        /// Inclusive lower bound.
+        min: Option<i64>,
+        /// Inclusive upper bound.
+        max: Option<i64>,
+    },
+    /// String must be one of the values.
+    OneOf(Vec<String>),
+}
+
+impl Rule {
+    /// Render rule as JSON Schema keywords like `{"minimum": 1}`.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        let mut m = serde_json::Map::new();
+        match self {
+            Rule::Range { min, max } => {
+                if let Some(min) = min {
+                    m.insert("minimum".to_string(), (*min).into());
+                }
+                if let Some(max) = max {
+                    m.insert("maximum".to_string(), (*max).into());
+                }
+            }
+            Rule::OneOf(values) => {
+                m.insert("enum".to_string(), values.clone().into());
+            }
+        }
+        serde_json::Value::Object(m)
+    }
+
+    /// Check value and return the violation message if any.
+    fn check(&self, path: &KeyPath, v: &Value) -> Option<String> {
+        match self {
+            Rule::Range { min, max } => {
+                let ok = match (as_i128(v), v) {
+                    (Some(n), _) => {
+                        min.is_none_or(|min| n >= i128::from(min))
+                            && max.is_none_or(|max| n <= i128::from(max))
+                    }
+                    (None, Value::F32(_) | Value::F64(_)) => {
+                        let n = match v {
+                            Value::F32(n) => f64::from(*n),
+                            Value::F64(n) => *n,
+                            _ => f64::NAN,
+                        };
+                        min.is_none_or(|min| n >= min as f64)
+                            && max.is_none_or(|max| n <= max as f64)
+                    }
+                    _ => return Some(format!("`{path}` must be a number in {self}")),
+                };
+                (!ok).then(|| format!("`{path}` must be in {self}, got {}", render(v)))
+            }
+            Rule::OneOf(values) => {
+                let s = match v {
+                    Value::Str(s) => s.as_str(),
+                    _ => return Some(format!("`{path}` must be {self}")),
+                };
+                if values.iter().any(|v| v == s) {
+                    return None;
+                }
+                let mut msg = format!("`{path}` must be {self}, got `{s}`");
+                let closest = values
+                    .iter()
+                    .map(|v| (distance(v, s), v))
+                    .filter(|(d, v)| *d <= v.len().max(2) / 2)
+                    .min();
+                if let Some((_, v)) = closest {
+                    msg.push_str(&format!(", did you mean `{v}`?"));
+                }
+                Some(msg)
+            }
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Range { min, max } => {
+                if let Some(min) = min {
+                    write!(f, "{min}")?;
+                }
+                write!(f, "..")?;
+                if let Some(max) = max {
+                    write!(f, "={max}")?;
+                }
+                Ok(())
+            }
+            Rule::OneOf(values) => {
+                let values: Vec<_> = values.iter().map(|v| format!("`{v}`")).collect();
+                write!(f, "one of {}", values.join(", "))
+            }
+        }
+    }
+}
+
+/// Annotation is a [`Rule`] declared on a key path.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Annotation {
+    /// Dot separated path like `server.port`.
+    pub path: String,
+    /// Rule of the path.
+    pub rule: Rule,
+}
+
+fn as_i128(v: &Value) -> Option<i128> {
+    Some(match v {
+        Value::I8(v) => i128::from(*v),
+        Value::I16(v) => i128::from(*v),
+        Value::I32(v) => i128::from(*v),
+        Value::I64(v) => i128::from(*v),
+        Value::I128(v) => *v,
+        Value::U8(v) => i128::from(*v),
+        Value::U16(v) => i128::from(*v),
+        Value::U32(v) => i128::from(*v),
+        Value::U64(v) => i128::from(*v),
+        Value::U128(v) => i128::try_from(*v).unwrap_or(i128::MAX),
+        _ => return None,
+    })
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev.get(j).copied().unwrap_or(0) + usize::from(ca != *cb);
+            let del = prev.get(j + 1).copied().unwrap_or(0) + 1;
+            let ins = cur.get(j).copied().unwrap_or(0) + 1;
+            cur.push(sub.min(del).min(ins));
+        }
+        prev = cur;
+    }
+    prev.last().copied().unwrap_or(0)
+}
+
+/// Constraint that will be checked against the built value.
+pub(crate) enum Constraint<V> {
+    /// Closure based constraint with its error message.
+    Fn(Box<dyn Fn(&V) -> bool>, String),
+    /// If `0` has been set, `1` must be set too.
+    Requires(KeyPath, KeyPath),
+    /// `0` and `1` can't be set at the same time.
+    Conflicts(KeyPath, KeyPath),
+    /// Value at `0` must follow the rule, missing or null values are
+    /// skipped.
+    Rule(KeyPath, Rule),
+}
+
+impl<V> Constraint<V> {
+    /// Check constraint and return the violation message if any.
+    ///
+    /// A path is treated as set if its value differs from the default.
+    pub(crate) fn check(&self, v: &V, value: &Value, default: &Value) -> Option<String> {
+        let is_set = |path: &KeyPath| get(value, path) != get(default, path);
+
+        match self {
+            Constraint::Fn(f, msg) => (!f(v)).then(|| msg.clone()),
+            Constraint::Requires(path, dep) => (is_set(path) && !is_set(dep))
+                .then(|| format!("`{path}` requires `{dep}` to be set")),
+            Constraint::Conflicts(l, r) => {
+                (is_set(l) && is_set(r)).then(|| format!("`{l}` conflicts with `{r}`"))
+            }
+            Constraint::Rule(path, rule) => match get(value, path) {
+                None | Some(Value::None | Value::Unit) => None,
+                Some(Value::Some(v)) => rule.check(path, v),
+                Some(v) => rule.check(path, v),
+            },
+        }
+    }
+}
+
+/// Error returned if any constraint has been violated, kept as a type so
+/// that [`Builder::check`][crate::Builder::check] can list them one by one.
+#[derive(Debug)]
+pub(crate) struct Violations(pub(crate) Vec<String>);
+
+impl Display for Violations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "config constraints violated:\n  - {}",
+            self.0.join("\n  - ")
+        )
+    }
+}
+
+impl std::error::Error for Violations {}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use Value::{Str, Struct};
+
+    use super::*;
+
+    fn key_path(s: &str) -> KeyPath {
+        s.parse().expect("must be valid path")
+    }
+
+    #[test]
+    fn test_check() {
+        let default = Struct(
+            "test",
+            indexmap! {
+                "cert" => Str("".to_string()),
+                "key" => Str("".to_string()),
+            },
+        );
+        let value = Struct(
+            "test",
+            indexmap! {
+                "cert" => Str("cert".to_string()),
+                "key" => Str("".to_string()),
+            },
+        );
+
+        let c: Constraint<()> = Constraint::Requires(key_path("cert"), key_path("key"));
+        assert_eq!(
+            c.check(&(), &value, &default),
+            Some("`cert` requires `key` to be set".to_string())
+        );
+
+        let c: Constraint<()> = Constraint::Conflicts(key_path("cert"), key_path("key"));
+        assert_eq!(c.check(&(), &value, &default), None);
+
+        let c: Constraint<()> = Constraint::Rule(
+            key_path("cert"),
+            Rule::OneOf(vec!["cart".to_string(), "key".to_string()]),
+        );
+        assert_eq!(
+            c.check(&(), &value, &default),
+            Some(
+                "`cert` must be one of `cart`, `key`, got `cert`, did you mean `cart`?".to_string()
+            )
+        );
+
+        let rule = Rule::Range {
+            min: Some(1),
+            max: Some(65535),
+        };
+        assert_eq!(
+            rule.check(&key_path("port"), &Value::U16(0)),
+            Some("`port` must be in 1..=65535, got 0".to_string())
+        );
+        assert_eq!(rule.check(&key_path("port"), &Value::U16(80)), None);
+        assert_eq!(
+            rule.to_json_schema().to_string(),
+            r#"{"maximum":65535,"minimum":1}"#
+        );
+    }
+}
@@ -0,0 +1,930 @@
+//! Deserializer for [`serde_bridge::Value`].
+//!
+//! Unlike `serde_bridge::from_value`, this deserializer accepts values that
+//! produced by different formats, for example:
+//!
+//! - Deserialize struct from map with string keys.
+//! - Deserialize enum from string or externally tagged map.
+//! - Deserialize integers from all numeric types as long as they fit.
+//!
+//! Errors returned by this deserializer will carry the path of the value.
+//! Builder and [`check_file`][crate::check_file] also use it to record keys
+//! ignored by the target type and keys only accepted via aliases, which
+//! `serde_bridge::from_value` can't report.
+//!
+//! Other crates can deserialize their own types from a config subtree via
+//! [`ValueExt::into_deserializer`], without serfig depending on them.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serfig::de::ValueExt;
+//! use serfig::Value;
+//!
+//! // Config type owned by a third-party crate.
+//! #[derive(Debug, Deserialize)]
+//! struct TracingConfig {
+//!     level: String,
+//! }
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! # let tracing = serde_bridge::into_value(std::collections::BTreeMap::from([("level", "info")]))?;
+//! // `tracing` is the `tracing` section of the merged config.
+//! let cfg = TracingConfig::deserialize(tracing.into_deserializer())?;
+//! assert_eq!(cfg.level, "info");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+
+use indexmap::IndexMap;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde_bridge::Value;
+
+/// Errors returned by [`Deserializer`].
+#[derive(Debug)]
+pub struct Error {
+    path: String,
+    msg: String,
+    /// Field reported by [`de::Error::duplicate_field`].
+    duplicate: Option<&'static str>,
+}
+
+impl Error {
+    fn with_path(mut self, path: &str) -> Self {
+        if self.path.is_empty() {
+            self.path = path.to_string();
+        }
+        self
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            path: String::new(),
+            msg: msg.to_string(),
+            duplicate: None,
+        }
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        Self {
+            duplicate: Some(field),
+            ..de::Error::custom(format_args!("duplicate field `{field}`"))
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.msg)
+        } else {
+            write!(f, "{} at `{}`", self.msg, self.path)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Deserialize `T` from value.
+pub fn from_value<T: DeserializeOwned>(v: Value) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(v))
+}
+
+/// Keys recorded by [`from_value_tracked`].
+#[derive(Debug, Default)]
+pub(crate) struct Track {
+    /// Paths of keys ignored by the target type.
+    pub ignored: Vec<String>,
+    /// Paths and keys that matched the field list of a struct, including
+    /// aliases.
+    pub fields: Vec<(String, String)>,
+    /// Path of the key to feed twice, see [`aliases`].
+    duplicate: Option<String>,
+}
+
+/// Deserialize `T` from value and record keys into `track`.
+pub(crate) fn from_value_tracked<T: DeserializeOwned>(
+    v: Value,
+    track: &mut Track,
+) -> Result<T, Error> {
+    let mut de = Deserializer::new(v);
+    de.track = Some(track);
+    T::deserialize(de)
+}
+
+/// Return paths of struct fields in `v` that `T` only accepts via
+/// `#[serde(alias)]`.
+///
+/// `typed` is `v` deserialized into `T` and serialized back, and `fields`
+/// comes from [`Track::fields`]. Keys that show up in `typed` at the same
+/// path are main names. The rest are aliases or fields skipped by
+/// `Serialize`, which are told apart by feeding the key to `T` twice:
+/// derived impls report the duplicate by the main name.
+pub(crate) fn aliases<T: DeserializeOwned>(
+    v: &Value,
+    typed: &Value,
+    fields: &[(String, String)],
+) -> Vec<String> {
+    let mut missing = HashSet::new();
+    missing_keys(v, typed, "", &mut missing);
+
+    fields
+        .iter()
+        .filter(|(path, _)| missing.contains(path.as_str()))
+        .filter(|(path, key)| {
+            let mut track = Track {
+                duplicate: Some(path.clone()),
+                ..Track::default()
+            };
+            match from_value_tracked::<T>(v.clone(), &mut track) {
+                Err(Error {
+                    duplicate: Some(name),
+                    ..
+                }) => name != key,
+                _ => false,
+            }
+        })
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+/// Collect paths of keys in `raw` that don't show up in `typed`.
+fn missing_keys(raw: &Value, typed: &Value, prefix: &str, out: &mut HashSet<String>) {
+    let path = |k: &str| {
+        if prefix.is_empty() {
+            k.to_string()
+        } else {
+            format!("{prefix}.{k}")
+        }
+    };
+    match (raw, typed) {
+        (_, Value::Some(tv) | Value::NewtypeStruct(_, tv)) => missing_keys(raw, tv, prefix, out),
+        (Value::Map(m), Value::Struct(_, fields)) => {
+            for (k, v) in m {
+                let Value::Str(k) = k else { continue };
+                match fields.get(k.as_str()) {
+                    Some(tv) => missing_keys(v, tv, &path(k), out),
+                    None => {
+                        out.insert(path(k));
+                    }
+                }
+            }
+        }
+        (Value::Map(m), Value::Map(tm)) => {
+            for (k, v) in m {
+                let Value::Str(k) = k else { continue };
+                if let Some(tv) = tm.get(&Value::Str(k.clone())) {
+                    missing_keys(v, tv, &path(k), out);
+                }
+            }
+        }
+        (Value::Seq(vs), Value::Seq(tvs)) => {
+            for (idx, (v, tv)) in vs.iter().zip(tvs).enumerate() {
+                missing_keys(v, tv, &format!("{prefix}[{idx}]"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Deserializer for [`serde_bridge::Value`].
+pub struct Deserializer<'a> {
+    value: Value,
+    path: String,
+    track: Option<&'a mut Track>,
+}
+
+impl<'a> Deserializer<'a> {
+    /// Create a deserializer over `value`.
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            path: String::new(),
+            track: None,
+        }
+    }
+
+    fn child<'b>(&'b mut self, key: impl Display, value: Value) -> Deserializer<'b> {
+        let path = if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        Deserializer {
+            value,
+            path,
+            track: self.track.as_deref_mut(),
+        }
+    }
+
+    fn with_value(self, value: Value) -> Self {
+        Self { value, ..self }
+    }
+
+    /// Take the value out and leave a unit in place.
+    fn take(mut self) -> (Value, Self) {
+        let v = std::mem::replace(&mut self.value, Value::Unit);
+        (v, self)
+    }
+}
+
+fn invalid_type(v: &Value, expect: &str) -> Error {
+    de::Error::custom(format!("invalid type: {}, expect {expect}", describe(v)))
+}
+
+/// Attach path to the error if it doesn't have one.
+fn at<T>(path: &str, r: Result<T, Error>) -> Result<T, Error> {
+    r.map_err(|err| err.with_path(path))
+}
+
+/// Describe the type of value in error messages.
+fn describe(v: &Value) -> &'static str {
+    match v {
+        Value::Bool(_) => "bool",
+        Value::I8(_)
+        | Value::I16(_)
+        | Value::I32(_)
+        | Value::I64(_)
+        | Value::I128(_)
+        | Value::U8(_)
+        | Value::U16(_)
+        | Value::U32(_)
+        | Value::U64(_)
+        | Value::U128(_) => "integer",
+        Value::F32(_) | Value::F64(_) => "float",
+        Value::Char(_) => "char",
+        Value::Str(_) => "string",
+        Value::Bytes(_) => "bytes",
+        Value::None | Value::Some(_) => "option",
+        Value::Unit | Value::UnitStruct(_) => "unit",
+        Value::NewtypeStruct(_, _) => "newtype struct",
+        Value::UnitVariant { .. }
+        | Value::NewtypeVariant { .. }
+        | Value::TupleVariant { .. }
+        | Value::StructVariant { .. } => "enum",
+        Value::Seq(_) | Value::Tuple(_) | Value::TupleStruct(_, _) => "sequence",
+        Value::Map(_) | Value::Struct(_, _) => "map",
+    }
+}
+
+/// Convert integer value into `i128` or `u128`.
+enum Integer {
+    Signed(i128),
+    Unsigned(u128),
+}
+
+fn integer(v: &Value) -> Option<Integer> {
+    Some(match v {
+        Value::I8(v) => Integer::Signed(*v as i128),
+        Value::I16(v) => Integer::Signed(*v as i128),
+        Value::I32(v) => Integer::Signed(*v as i128),
+        Value::I64(v) => Integer::Signed(*v as i128),
+        Value::I128(v) => Integer::Signed(*v),
+        Value::U8(v) => Integer::Unsigned(*v as u128),
+        Value::U16(v) => Integer::Unsigned(*v as u128),
+        Value::U32(v) => Integer::Unsigned(*v as u128),
+        Value::U64(v) => Integer::Unsigned(*v as u128),
+        Value::U128(v) => Integer::Unsigned(*v),
+        _ => return None,
+    })
+}
+
+macro_rules! deserialize_integer {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+            let v = match integer(&self.value) {
+                Some(Integer::Signed(v)) => <$ty>::try_from(v).ok(),
+                Some(Integer::Unsigned(v)) => <$ty>::try_from(v).ok(),
+                None => return at(&self.path, Err(invalid_type(&self.value, stringify!($ty)))),
+            };
+            let r = match v {
+                Some(v) => vis.$visit(v),
+                None => Err(de::Error::custom(format!(
+                    "integer out of range for {}",
+                    stringify!($ty)
+                ))),
+            };
+            at(&self.path, r)
+        }
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        let (value, de) = self.take();
+        let r = match value {
+            Value::Bool(v) => vis.visit_bool(v),
+            Value::I8(v) => vis.visit_i8(v),
+            Value::I16(v) => vis.visit_i16(v),
+            Value::I32(v) => vis.visit_i32(v),
+            Value::I64(v) => vis.visit_i64(v),
+            Value::I128(v) => vis.visit_i128(v),
+            Value::U8(v) => vis.visit_u8(v),
+            Value::U16(v) => vis.visit_u16(v),
+            Value::U32(v) => vis.visit_u32(v),
+            Value::U64(v) => vis.visit_u64(v),
+            Value::U128(v) => vis.visit_u128(v),
+            Value::F32(v) => vis.visit_f32(v),
+            Value::F64(v) => vis.visit_f64(v),
+            Value::Char(v) => vis.visit_char(v),
+            Value::Str(v) => vis.visit_string(v),
+            Value::Bytes(v) => vis.visit_byte_buf(v),
+            Value::None => vis.visit_none(),
+            Value::Some(v) => vis.visit_some(de.with_value(*v)),
+            Value::Unit | Value::UnitStruct(_) => vis.visit_unit(),
+            Value::UnitVariant { variant, .. } => vis.visit_str(variant),
+            Value::NewtypeStruct(_, v) => vis.visit_newtype_struct(de.with_value(*v)),
+            Value::Seq(v) | Value::Tuple(v) | Value::TupleStruct(_, v) => {
+                vis.visit_seq(SeqAccessor::new(de, v))
+            }
+            Value::Map(m) => vis.visit_map(MapAccessor::new(de, m)),
+            Value::Struct(_, m) => vis.visit_map(MapAccessor::new(de, into_map(m))),
+            Value::NewtypeVariant { variant, value, .. } => vis.visit_map(MapAccessor::new(
+                de,
+                IndexMap::from([(Value::Str(variant.to_string()), *value)]),
+            )),
+            Value::TupleVariant {
+                variant, fields, ..
+            } => vis.visit_map(MapAccessor::new(
+                de,
+                IndexMap::from([(Value::Str(variant.to_string()), Value::Seq(fields))]),
+            )),
+            Value::StructVariant {
+                variant, fields, ..
+            } => vis.visit_map(MapAccessor::new(
+                de,
+                IndexMap::from([(
+                    Value::Str(variant.to_string()),
+                    Value::Map(into_map(fields)),
+                )]),
+            )),
+        };
+        at(&path, r)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let r = match self.value {
+            Value::Bool(v) => vis.visit_bool(v),
+            ref v => Err(invalid_type(v, "bool")),
+        };
+        at(&self.path, r)
+    }
+
+    deserialize_integer!(deserialize_i8, visit_i8, i8);
+    deserialize_integer!(deserialize_i16, visit_i16, i16);
+    deserialize_integer!(deserialize_i32, visit_i32, i32);
+    deserialize_integer!(deserialize_i64, visit_i64, i64);
+    deserialize_integer!(deserialize_i128, visit_i128, i128);
+    deserialize_integer!(deserialize_u8, visit_u8, u8);
+    deserialize_integer!(deserialize_u16, visit_u16, u16);
+    deserialize_integer!(deserialize_u32, visit_u32, u32);
+    deserialize_integer!(deserialize_u64, visit_u64, u64);
+    deserialize_integer!(deserialize_u128, visit_u128, u128);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(vis)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let r = match (&self.value, integer(&self.value)) {
+            (Value::F32(v), _) => vis.visit_f64(*v as f64),
+            (Value::F64(v), _) => vis.visit_f64(*v),
+            (_, Some(Integer::Signed(v))) => vis.visit_f64(v as f64),
+            (_, Some(Integer::Unsigned(v))) => vis.visit_f64(v as f64),
+            (v, None) => Err(invalid_type(v, "float")),
+        };
+        at(&self.path, r)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let r = match self.value {
+            Value::Char(v) => vis.visit_char(v),
+            Value::Str(v) => vis.visit_string(v),
+            ref v => Err(invalid_type(v, "char")),
+        };
+        at(&self.path, r)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        self.deserialize_string(vis)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let r = match self.value {
+            Value::Str(v) => vis.visit_string(v),
+            Value::Char(v) => vis.visit_char(v),
+            ref v => Err(invalid_type(v, "string")),
+        };
+        at(&self.path, r)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        self.deserialize_byte_buf(vis)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        if let Value::Seq(_) = self.value {
+            return self.deserialize_seq(vis);
+        }
+        let r = match self.value {
+            Value::Bytes(v) => vis.visit_byte_buf(v),
+            Value::Str(v) => vis.visit_string(v),
+            ref v => Err(invalid_type(v, "bytes")),
+        };
+        at(&self.path, r)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        let r = match self.value {
+            Value::None | Value::Unit => vis.visit_none(),
+            Value::Some(_) => {
+                let (value, de) = self.take();
+                match value {
+                    Value::Some(v) => vis.visit_some(de.with_value(*v)),
+                    v => vis.visit_some(de.with_value(v)),
+                }
+            }
+            _ => vis.visit_some(self),
+        };
+        at(&path, r)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let r = match self.value {
+            Value::Unit | Value::UnitStruct(_) | Value::None => vis.visit_unit(),
+            Value::Map(ref m) if m.is_empty() => vis.visit_unit(),
+            ref v => Err(invalid_type(v, "unit")),
+        };
+        at(&self.path, r)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_unit(vis)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        let r = match self.take() {
+            (Value::NewtypeStruct(_, v), de) => vis.visit_newtype_struct(de.with_value(*v)),
+            (v, de) => vis.visit_newtype_struct(de.with_value(v)),
+        };
+        at(&path, r)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        let r = match self.take() {
+            (Value::Seq(v) | Value::Tuple(v) | Value::TupleStruct(_, v), de) => {
+                vis.visit_seq(SeqAccessor::new(de, v))
+            }
+            (Value::Bytes(v), de) => {
+                vis.visit_seq(SeqAccessor::new(de, v.into_iter().map(Value::U8).collect()))
+            }
+            (v, _) => Err(invalid_type(&v, "sequence")),
+        };
+        at(&path, r)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, vis: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(vis)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(vis)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        let r = match self.take() {
+            (Value::Map(m), de) => vis.visit_map(MapAccessor::new(de, m)),
+            (Value::Struct(_, m), de) => vis.visit_map(MapAccessor::new(de, into_map(m))),
+            (v, _) => Err(invalid_type(&v, "map")),
+        };
+        at(&path, r)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        mut self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        if let (Some(track), Value::Map(m)) = (self.track.as_deref_mut(), &self.value) {
+            for k in m.keys() {
+                let Value::Str(k) = k else { continue };
+                if fields.contains(&k.as_str()) {
+                    let path = if self.path.is_empty() {
+                        k.clone()
+                    } else {
+                        format!("{}.{k}", self.path)
+                    };
+                    track.fields.push((path, k.clone()));
+                }
+            }
+        }
+        self.deserialize_map(vis)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        let path = self.path.clone();
+        let (value, de) = self.take();
+        let (variant, content) = match value {
+            Value::Str(v) => (v, None),
+            Value::UnitVariant { variant, .. } => (variant.to_string(), None),
+            Value::NewtypeVariant { variant, value, .. } => (variant.to_string(), Some(*value)),
+            Value::TupleVariant {
+                variant, fields, ..
+            } => (variant.to_string(), Some(Value::Seq(fields))),
+            Value::StructVariant {
+                variant, fields, ..
+            } => (variant.to_string(), Some(Value::Map(into_map(fields)))),
+            Value::Map(m) if m.len() == 1 => match m.into_iter().next() {
+                Some((Value::Str(k), v)) => (k, Some(v)),
+                _ => {
+                    return at(
+                        &path,
+                        Err(de::Error::custom("invalid type: map key, expect string")),
+                    )
+                }
+            },
+            v => return at(&path, Err(invalid_type(&v, "enum"))),
+        };
+
+        at(
+            &path,
+            vis.visit_enum(EnumAccessor {
+                de,
+                variant,
+                content,
+            }),
+        )
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Str(_) | Value::Char(_) => self.deserialize_string(vis),
+            _ => self.deserialize_any(vis),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        if let Some(track) = self.track {
+            track.ignored.push(self.path);
+        }
+        vis.visit_unit()
+    }
+}
+
+fn into_map(m: IndexMap<&'static str, Value>) -> IndexMap<Value, Value> {
+    m.into_iter()
+        .map(|(k, v)| (Value::Str(k.to_string()), v))
+        .collect()
+}
+
+struct SeqAccessor<'a> {
+    de: Deserializer<'a>,
+    elements: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+}
+
+impl<'a> SeqAccessor<'a> {
+    fn new(de: Deserializer<'a>, elements: Vec<Value>) -> Self {
+        Self {
+            de,
+            elements: elements.into_iter().enumerate(),
+        }
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for SeqAccessor<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.elements.next() {
+            None => Ok(None),
+            Some((idx, v)) => {
+                let path = format!("{}[{idx}]", self.de.path);
+                let mut child = self.de.child("", v);
+                child.path = path;
+                seed.deserialize(child).map(Some)
+            }
+        }
+    }
+}
+
+struct MapAccessor<'a> {
+    de: Deserializer<'a>,
+    entries: indexmap::map::IntoIter<Value, Value>,
+    value: Option<(String, Value)>,
+    /// Entry to yield again for [`Track::duplicate`].
+    repeat: Option<(Value, Value)>,
+}
+
+impl<'a> MapAccessor<'a> {
+    fn new(de: Deserializer<'a>, entries: IndexMap<Value, Value>) -> Self {
+        Self {
+            de,
+            entries: entries.into_iter(),
+            value: None,
+            repeat: None,
+        }
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for MapAccessor<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.repeat.take().or_else(|| self.entries.next()) {
+            None => Ok(None),
+            Some((k, v)) => {
+                let key = match &k {
+                    Value::Str(k) => k.clone(),
+                    k => format!("{k:?}"),
+                };
+                let mut child = self.de.child(&key, k);
+                if let Some(track) = child.track.as_deref_mut() {
+                    if track.duplicate.as_deref() == Some(child.path.as_str()) {
+                        track.duplicate = None;
+                        self.repeat = Some((child.value.clone(), v.clone()));
+                    }
+                }
+                let result = seed.deserialize(child);
+                self.value = Some((key, v));
+                result.map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value is requested before key"))?;
+        seed.deserialize(self.de.child(key, value))
+    }
+}
+
+struct EnumAccessor<'a> {
+    de: Deserializer<'a>,
+    variant: String,
+    content: Option<Value>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for EnumAccessor<'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let de: de::value::StrDeserializer<Error> = self.variant.as_str().into_deserializer();
+        let v = seed.deserialize(de)?;
+        Ok((v, self))
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for EnumAccessor<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.content {
+            None | Some(Value::Unit) => Ok(()),
+            Some(v) => Err(de::Error::custom(format!(
+                "invalid type: {}, expect unit variant",
+                describe(&v)
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(mut self, seed: T) -> Result<T::Value, Error> {
+        let content = self.content.take().unwrap_or(Value::Unit);
+        let variant = self.variant.clone();
+        seed.deserialize(self.de.child(variant, content))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(mut self, _: usize, vis: V) -> Result<V::Value, Error> {
+        let content = self.content.take().unwrap_or(Value::Unit);
+        let variant = self.variant.clone();
+        de::Deserializer::deserialize_seq(self.de.child(variant, content), vis)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        mut self,
+        _: &'static [&'static str],
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        let content = self.content.take().unwrap_or(Value::Unit);
+        let variant = self.variant.clone();
+        de::Deserializer::deserialize_map(self.de.child(variant, content), vis)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Deserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Extension methods for [`Value`].
+pub trait ValueExt {
+    /// Convert value into a [`serde::Deserializer`] with the same rules
+    /// used by builder, like deserializing structs from maps.
+    fn into_deserializer(self) -> Deserializer<'static>;
+}
+
+impl ValueExt for Value {
+    fn into_deserializer(self) -> Deserializer<'static> {
+        Deserializer::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use serde::{Deserialize, Serialize};
+    use serde_bridge::IntoValue;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum TestLevel {
+        Debug,
+        Info,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum TestOutput {
+        File(String),
+        Stdout,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestLog {
+        level: TestLevel,
+        output: TestOutput,
+        file: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        port: u16,
+        ratio: f64,
+        log: TestLog,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_from_raw_value() {
+        let raw = Value::Map(indexmap! {
+            Value::Str("name".to_string()) => Value::Str("test".to_string()),
+            Value::Str("port".to_string()) => Value::I64(8080),
+            Value::Str("ratio".to_string()) => Value::I64(1),
+            Value::Str("unknown".to_string()) => Value::I64(1),
+            Value::Str("log".to_string()) => Value::Map(indexmap! {
+                Value::Str("level".to_string()) => Value::Str("info".to_string()),
+                Value::Str("output".to_string()) => Value::Map(indexmap! {
+                    Value::Str("File".to_string()) => Value::Str("/tmp/log".to_string()),
+                }),
+                Value::Str("file".to_string()) => Value::Str("/tmp".to_string()),
+            }),
+            Value::Str("tags".to_string()) => Value::Seq(vec![Value::Str("a".to_string())]),
+        });
+
+        let mut track = Track::default();
+        let t: TestStruct = from_value_tracked(raw, &mut track).expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "test".to_string(),
+                port: 8080,
+                ratio: 1.0,
+                log: TestLog {
+                    level: TestLevel::Info,
+                    output: TestOutput::File("/tmp/log".to_string()),
+                    file: Some("/tmp".to_string()),
+                },
+                tags: vec!["a".to_string()],
+            }
+        );
+        assert_eq!(track.ignored, vec!["unknown"]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let t = TestStruct {
+            name: "test".to_string(),
+            port: 8080,
+            ratio: 0.5,
+            log: TestLog {
+                level: TestLevel::Debug,
+                output: TestOutput::Stdout,
+                file: None,
+            },
+            tags: vec![],
+        };
+
+        let v = t.into_value().expect("into value");
+        let actual: TestStruct =
+            TestStruct::deserialize(Deserializer::new(v)).expect("must success");
+        assert_eq!(
+            actual,
+            TestStruct {
+                name: "test".to_string(),
+                port: 8080,
+                ratio: 0.5,
+                log: TestLog {
+                    level: TestLevel::Debug,
+                    output: TestOutput::Stdout,
+                    file: None,
+                },
+                tags: vec![],
+            }
+        )
+    }
+
+    #[test]
+    fn test_error_path() {
+        let raw = Value::Map(indexmap! {
+            Value::Str("name".to_string()) => Value::Str("test".to_string()),
+            Value::Str("port".to_string()) => Value::I64(65536),
+        });
+
+        let err = TestStruct::deserialize(Deserializer::new(raw)).expect_err("must fail");
+        assert_eq!(err.to_string(), "integer out of range for u16 at `port`");
+    }
+
+    #[test]
+    fn test_aliases() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct TestServer {
+            #[serde(alias = "address")]
+            addr: String,
+            #[serde(skip_serializing)]
+            token: String,
+        }
+
+        #[derive(Debug, Serialize, Deserialize)]
+        struct TestConfig {
+            servers: Vec<TestServer>,
+        }
+
+        let raw = Value::Map(indexmap! {
+            Value::Str("servers".to_string()) => Value::Seq(vec![
+                Value::Map(indexmap! {
+                    Value::Str("addr".to_string()) => Value::Str("a".to_string()),
+                    Value::Str("token".to_string()) => Value::Str("t".to_string()),
+                }),
+                Value::Map(indexmap! {
+                    Value::Str("address".to_string()) => Value::Str("b".to_string()),
+                    Value::Str("token".to_string()) => Value::Str("t".to_string()),
+                }),
+            ]),
+        });
+
+        let mut track = Track::default();
+        let t: TestConfig = from_value_tracked(raw.clone(), &mut track).expect("must success");
+        assert_eq!(t.servers[1].token, "t");
+        let typed = t.into_value().expect("into value");
+        assert_eq!(
+            aliases::<TestConfig>(&raw, &typed, &track.fields),
+            vec!["servers[1].address"]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Decide whether a value is a default that must not override others.
+//!
+//! [`Builder`][crate::Builder] only merges values that have been set by
+//! a layer. A value is treated as unset if:
+//!
+//! - An override registered by [`MergeConfig::override_type`] for its
+//!   type says so.
+//! - Otherwise, the predicate set by [`MergeConfig::default_predicate`]
+//!   says so.
+//! - Otherwise, it equals the value in `V::default()` at the same path.
+//!
+//! Values without a default at their path, like entries of a map that
+//! only exist in some layers, are always treated as set, so a later layer
+//! can turn `features.x` off again. Use [`looks_default`] in a predicate
+//! to treat zero values of such entries as unset instead.
+//!
+//! ```
+//! use serfig::defaultness::looks_default;
+//! use serfig::{MergeConfig, Value};
+//!
+//! // `Level` is an enum whose default variant is `Info`.
+//! let cfg = MergeConfig::default().override_type("Level", |v| {
+//!     matches!(v, Value::UnitVariant { variant: "Info", .. })
+//! });
+//!
+//! let info = Value::UnitVariant {
+//!     name: "Level",
+//!     variant_index: 1,
+//!     variant: "Info",
+//! };
+//! assert!(cfg.is_default(&info, None));
+//! assert!(!cfg.is_default(&Value::U64(0), None));
+//! assert!(cfg.is_default(&Value::U64(0), Some(&Value::U64(0))));
+//! assert!(looks_default(&Value::U64(0)));
+//! ```
+//!
+//! [`MergeConfig::override_type`]: crate::MergeConfig::override_type
+//! [`MergeConfig::default_predicate`]: crate::MergeConfig::default_predicate
+
+use serde_bridge::Value;
+
+/// Predicate returns `Some` to decide whether a value is default, or
+/// `None` to fall back to the built-in rules.
+pub type Predicate = fn(&Value) -> Option<bool>;
+
+/// TypeOverride decides whether values of a type are default.
+pub type TypeOverride = fn(&Value) -> bool;
+
+/// Check whether `v` is default by the built-in rules, `d` is the value
+/// in `V::default()` at the same path if there is one.
+pub fn is_default(v: &Value, d: Option<&Value>) -> bool {
+    d == Some(v)
+}
+
+/// Check whether `v` looks like a value produced by `Default`.
+///
+/// Enum variants never look like default since the default variant can't
+/// be known from the value.
+pub fn looks_default(v: &Value) -> bool {
+    match v {
+        Value::Bool(v) => !v,
+        Value::I8(v) => *v == 0,
+        Value::I16(v) => *v == 0,
+        Value::I32(v) => *v == 0,
+        Value::I64(v) => *v == 0,
+        Value::I128(v) => *v == 0,
+        Value::U8(v) => *v == 0,
+        Value::U16(v) => *v == 0,
+        Value::U32(v) => *v == 0,
+        Value::U64(v) => *v == 0,
+        Value::U128(v) => *v == 0,
+        Value::F32(v) => *v == 0.0,
+        Value::F64(v) => *v == 0.0,
+        Value::Char(v) => *v == '\0',
+        Value::Str(v) => v.is_empty(),
+        Value::Bytes(v) => v.is_empty(),
+        Value::None | Value::Unit | Value::UnitStruct(_) => true,
+        Value::Seq(v) => v.is_empty(),
+        Value::Map(v) => v.is_empty(),
+        Value::NewtypeStruct(_, v) => looks_default(v),
+        Value::Tuple(vs) | Value::TupleStruct(_, vs) => vs.iter().all(looks_default),
+        Value::Struct(_, fields) => fields.values().all(looks_default),
+        Value::Some(_)
+        | Value::UnitVariant { .. }
+        | Value::NewtypeVariant { .. }
+        | Value::TupleVariant { .. }
+        | Value::StructVariant { .. } => false,
+    }
+}
+
+/// Name of the struct or enum of `v` seen by serde.
+pub(crate) fn type_name(v: &Value) -> Option<&'static str> {
+    match v {
+        Value::UnitStruct(name)
+        | Value::NewtypeStruct(name, _)
+        | Value::TupleStruct(name, _)
+        | Value::Struct(name, _)
+        | Value::UnitVariant { name, .. }
+        | Value::NewtypeVariant { name, .. }
+        | Value::TupleVariant { name, .. }
+        | Value::StructVariant { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::MergeConfig;
+
+    #[test]
+    fn test_looks_default() {
+        assert!(looks_default(&Value::Str(String::new())));
+        assert!(looks_default(&Value::Struct(
+            "Server",
+            IndexMap::from([("host", Value::Str(String::new())), ("port", Value::U16(0))])
+        )));
+        assert!(!looks_default(&Value::Struct(
+            "Server",
+            IndexMap::from([("port", Value::U16(80))])
+        )));
+        assert!(!looks_default(&Value::Some(Box::new(Value::U16(0)))));
+    }
+
+    #[test]
+    fn test_is_default() {
+        assert!(is_default(&Value::U16(80), Some(&Value::U16(80))));
+        assert!(!is_default(&Value::U16(0), Some(&Value::U16(80))));
+        // Values without default are always set.
+        assert!(!is_default(&Value::Bool(false), None));
+    }
+
+    #[test]
+    fn test_override_type() {
+        let cfg = MergeConfig::default().override_type("Mode", |v| {
+            matches!(
+                v,
+                Value::UnitVariant {
+                    variant: "Auto",
+                    ..
+                }
+            )
+        });
+
+        let auto = Value::UnitVariant {
+            name: "Mode",
+            variant_index: 0,
+            variant: "Auto",
+        };
+        let manual = Value::UnitVariant {
+            name: "Mode",
+            variant_index: 1,
+            variant: "Manual",
+        };
+        assert!(cfg.is_default(&auto, None));
+        assert!(!cfg.is_default(&manual, None));
+        // Overrides win over equality to default.
+        assert!(!cfg.is_default(&manual, Some(&manual)));
+        // Other configs are not affected.
+        assert!(!MergeConfig::default().is_default(&auto, None));
+    }
+
+    #[test]
+    fn test_default_predicate() {
+        let cfg = MergeConfig::default().default_predicate(|v| match v {
+            Value::Str(s) if s == "unset" => Some(true),
+            _ => None,
+        });
+
+        assert!(cfg.is_default(
+            &Value::Str("unset".to_string()),
+            Some(&Value::Str("other".to_string()))
+        ));
+        assert!(!cfg.is_default(&Value::Str("set".to_string()), None));
+        assert!(!MergeConfig::default().is_default(&Value::Str("unset".to_string()), None));
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::value::{flatten, is_sensitive, to_value, REDACTED};
+use crate::Parser;
+
+/// Difference between two config snapshots returned by [`diff_files`].
+///
+/// Changes are sorted by path so the output is stable across runs.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigDiff {
+    /// All changed leaf values.
+    pub changes: Vec<Change>,
+}
+
+/// A single changed value in [`ConfigDiff`].
+///
+/// `old` is `None` if the value is added and `new` is `None` if the value
+/// is removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change {
+    /// Dot separated path of the value, like `tls.cert`.
+    pub path: String,
+    /// Value in the old snapshot.
+    pub old: Option<String>,
+    /// Value in the new snapshot.
+    pub new: Option<String>,
+}
+
+impl ConfigDiff {
+    /// Returns `true` if two snapshots are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Render diff as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl Display for ConfigDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for c in &self.changes {
+            match (&c.old, &c.new) {
+                (None, Some(new)) => writeln!(f, "+ {} = {}", c.path, new)?,
+                (Some(old), None) => writeln!(f, "- {} = {}", c.path, old)?,
+                (Some(old), Some(new)) => writeln!(f, "~ {} = {} -> {}", c.path, old, new)?,
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare two exported config snapshots of type `V`.
+///
+/// Both files are deserialized into `V` first, so fields that only differ
+/// in form (like default values that omitted in one file) will not be
+/// reported. Values of sensitive keys like `password` or `token` are
+/// redacted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::parsers::Toml;
+///
+/// #[derive(Debug, Serialize, Deserialize, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let diff = serfig::diff_files::<TestConfig, _>(Toml, "v1.toml", "v2.toml")?;
+///     println!("{diff}");
+///     println!("{}", diff.to_json()?);
+///     Ok(())
+/// }
+/// ```
+pub fn diff_files<V, P>(
+    mut parser: P,
+    old: impl AsRef<Path>,
+    new: impl AsRef<Path>,
+) -> Result<ConfigDiff>
+where
+    V: DeserializeOwned + Serialize,
+    P: Parser,
+{
+    let mut load = |path: &Path| -> Result<Value> {
+        let v: V = parser.parse(&fs::read(path)?)?;
+        to_value(&v)
+    };
+    let (old, new) = (load(old.as_ref())?, load(new.as_ref())?);
+
+    Ok(diff_values(&old, &new))
+}
+
+/// Compare two values with redaction applied.
+pub(crate) fn diff_values(old: &Value, new: &Value) -> ConfigDiff {
+    let mut old = flatten(old);
+    let mut new = flatten(new);
+
+    let mut paths: Vec<String> = old.keys().chain(new.keys()).cloned().collect();
+    paths.sort();
+    paths.dedup();
+
+    let changes = paths
+        .into_iter()
+        .filter_map(|path| {
+            let (o, n) = (old.remove(&path), new.remove(&path));
+            if o == n {
+                return None;
+            }
+            let (o, n) = if is_sensitive(&path) {
+                (
+                    o.map(|_| REDACTED.to_string()),
+                    n.map(|_| REDACTED.to_string()),
+                )
+            } else {
+                (o, n)
+            };
+            Some(Change {
+                path,
+                old: o,
+                new: n,
+            })
+        })
+        .collect();
+
+    ConfigDiff { changes }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        addr: String,
+        port: u16,
+        workers: Option<u16>,
+        tls: TestConfigTls,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    #[serde(default)]
+    struct TestConfigTls {
+        cert: String,
+        password: String,
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let dir = std::env::temp_dir();
+        let old = dir.join("serfig-diff-old.toml");
+        let new = dir.join("serfig-diff-new.toml");
+        fs::write(
+            &old,
+            r#"
+addr = "127.0.0.1"
+port = 8080
+tls = { cert = "/tmp/a", password = "foo" }
+"#,
+        )
+        .expect("write old");
+        fs::write(
+            &new,
+            r#"
+addr = "127.0.0.1"
+workers = 4
+tls = { cert = "/tmp/b", password = "bar" }
+"#,
+        )
+        .expect("write new");
+
+        let diff = diff_files::<TestConfig, _>(Toml, &old, &new).expect("diff files");
+
+        assert_eq!(
+            diff.to_string(),
+            r#"~ port = 8080 -> 0
+~ tls.cert = "/tmp/a" -> "/tmp/b"
+~ tls.password = <redacted> -> <redacted>
+~ workers = null -> 4
+"#
+        );
+        assert!(diff
+            .to_json()
+            .expect("to json")
+            .contains(r#""path": "port""#));
+    }
+}
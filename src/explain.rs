@@ -0,0 +1,48 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::collectors::SourceDescriptor;
+
+/// Detail decides which fields will be included in [`Explanation`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Detail {
+    /// Only include fields that differ from their default values.
+    #[default]
+    ChangedOnly,
+    /// Include all fields.
+    Full,
+}
+
+/// Explanation of a build returned by [`Builder::explain`][crate::Builder::explain].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Explanation {
+    /// Fields sorted by path.
+    pub entries: Vec<ExplainEntry>,
+    /// Count of fields hidden by [`Detail::ChangedOnly`].
+    pub hidden: usize,
+}
+
+/// A single field in [`Explanation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainEntry {
+    /// Dot separated path of the field, like `tls.cert`.
+    pub path: String,
+    /// Rendered value of the field, sensitive values are redacted.
+    pub value: String,
+    /// The last source that set this field, `None` means default.
+    pub source: Option<SourceDescriptor>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for e in &self.entries {
+            match &e.source {
+                Some(source) => writeln!(f, "{} = {} ({})", e.path, e.value, source)?,
+                None => writeln!(f, "{} = {} (default)", e.path, e.value)?,
+            }
+        }
+        if self.hidden > 0 {
+            writeln!(f, "({} fields equal to default are hidden)", self.hidden)?;
+        }
+        Ok(())
+    }
+}
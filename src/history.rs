@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use log::warn;
+use serde_bridge::Value;
+
+/// Delta turns one value into another.
+///
+/// Only changed struct fields and map entries are stored, so deltas
+/// between similar configs stay small.
+#[derive(Debug, Clone, PartialEq)]
+enum Delta {
+    Replace(Value),
+    Fields(Vec<(&'static str, Delta)>),
+    Entries(Vec<(Value, Delta)>),
+}
+
+impl Delta {
+    /// Build the delta that turns `from` into `to`.
+    fn between(from: &Value, to: &Value) -> Option<Delta> {
+        if from == to {
+            return None;
+        }
+
+        Some(match (from, to) {
+            (Value::Struct(fname, fm), Value::Struct(tname, tm))
+                if fname == tname && fm.keys().eq(tm.keys()) =>
+            {
+                Delta::Fields(
+                    fm.iter()
+                        .zip(tm.values())
+                        .filter_map(|((k, f), t)| Delta::between(f, t).map(|d| (*k, d)))
+                        .collect(),
+                )
+            }
+            (Value::Map(fm), Value::Map(tm)) if fm.keys().eq(tm.keys()) => Delta::Entries(
+                fm.iter()
+                    .zip(tm.values())
+                    .filter_map(|((k, f), t)| Delta::between(f, t).map(|d| (k.clone(), d)))
+                    .collect(),
+            ),
+            _ => Delta::Replace(to.clone()),
+        })
+    }
+
+    fn apply(&self, v: &mut Value) {
+        match (self, v) {
+            (Delta::Fields(ds), Value::Struct(_, m)) => {
+                for (k, d) in ds {
+                    if let Some(v) = m.get_mut(k) {
+                        d.apply(v)
+                    }
+                }
+            }
+            (Delta::Entries(ds), Value::Map(m)) => {
+                for (k, d) in ds {
+                    if let Some(v) = m.get_mut(k) {
+                        d.apply(v)
+                    }
+                }
+            }
+            (Delta::Replace(to), v) => *v = to.clone(),
+            // Deltas are built against the same value, mismatches can't
+            // happen but are ignored to be safe.
+            (d, v) => warn!("delta {d:?} doesn't match value {v:?}, ignored"),
+        }
+    }
+}
+
+/// History keeps the latest value in full and older values as deltas
+/// against their successors.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    capacity: usize,
+    latest: Option<Value>,
+    /// `deltas[0]` turns `latest` into the previous value, `deltas[1]`
+    /// turns that into the one before, and so on.
+    deltas: VecDeque<Delta>,
+}
+
+impl History {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    /// Number of retained values before the latest one.
+    pub(crate) fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    pub(crate) fn push(&mut self, v: Value) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some(latest) = self.latest.take() {
+            self.deltas
+                .push_front(Delta::between(&v, &latest).unwrap_or(Delta::Fields(Vec::new())));
+            self.deltas.truncate(self.capacity);
+        }
+        self.latest = Some(v);
+    }
+
+    /// Reconstruct the value `n` versions before the latest one.
+    pub(crate) fn get(&self, n: usize) -> Option<Value> {
+        if n > self.deltas.len() {
+            return None;
+        }
+        let mut v = self.latest.clone()?;
+        for d in self.deltas.iter().take(n) {
+            d.apply(&mut v)
+        }
+        Some(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use serde_bridge::Value::{Map, Str, Struct, U64};
+
+    use super::*;
+
+    fn version(workers: u64, name: &str) -> Value {
+        Struct(
+            "Config",
+            indexmap! {
+                "workers" => U64(workers),
+                "server" => Struct("Server", indexmap! {
+                    "name" => Str(name.to_string()),
+                    "tags" => Map(indexmap! {
+                        Str("zone".to_string()) => Str("a".to_string()),
+                    }),
+                }),
+            },
+        )
+    }
+
+    #[test]
+    fn test_history() {
+        let mut h = History::new(2);
+        for (workers, name) in [(1, "a"), (2, "a"), (2, "b"), (3, "b")] {
+            h.push(version(workers, name));
+        }
+
+        assert_eq!(h.len(), 2);
+        assert_eq!(
+            h.deltas[0],
+            Delta::Fields(vec![("workers", Delta::Replace(U64(2)))])
+        );
+        assert_eq!(h.get(0), Some(version(3, "b")));
+        assert_eq!(h.get(1), Some(version(2, "b")));
+        assert_eq!(h.get(2), Some(version(2, "a")));
+        assert_eq!(h.get(3), None);
+    }
+}
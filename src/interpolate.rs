@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::{self, Debug, Formatter};
+
+use anyhow::{anyhow, bail, Result};
+use serde_bridge::Value;
+
+type Function = Box<dyn Fn(&str) -> Result<String>>;
+
+/// Interpolator expands `${function:args}` expressions in string values.
+///
+/// `Interpolator::default()` contains the following functions:
+///
+/// - `${env:HOME}`: value of env `HOME`, fails if not set.
+/// - `${env:HOME:-/root}`: value of env `HOME`, or `/root` if not set or empty.
+/// - `${upper:abc}`, `${lower:ABC}`: convert case of args.
+/// - `${concat:a,b}`: join comma separated args.
+///
+/// Use `$${` to write a literal `${`.
+///
+/// # Examples
+///
+/// ```
+/// use serfig::Interpolator;
+///
+/// fn main() -> anyhow::Result<()> {
+///     let i = Interpolator::default().register("reverse", |args| Ok(args.chars().rev().collect()));
+///
+///     assert_eq!(i.expand("${env:SERFIG_NOT_SET:-/root}/data")?, "/root/data");
+///     assert_eq!(i.expand("${reverse:abc}")?, "cba");
+///     assert_eq!(i.expand("$${upper:abc}")?, "${upper:abc}");
+///     Ok(())
+/// }
+/// ```
+pub struct Interpolator {
+    functions: HashMap<String, Function>,
+}
+
+impl Default for Interpolator {
+    fn default() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+        .register("env", env_function)
+        .register("upper", |args| Ok(args.to_uppercase()))
+        .register("lower", |args| Ok(args.to_lowercase()))
+        .register("concat", |args| Ok(args.split(',').collect()))
+    }
+}
+
+impl Debug for Interpolator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut functions: Vec<_> = self.functions.keys().collect();
+        functions.sort();
+        f.debug_struct("Interpolator")
+            .field("functions", &functions)
+            .finish()
+    }
+}
+
+impl Interpolator {
+    /// Register a function that can be called like `${name:args}`.
+    ///
+    /// `f` will be called with the raw args after the first `:`.
+    pub fn register(mut self, name: &str, f: impl Fn(&str) -> Result<String> + 'static) -> Self {
+        self.functions.insert(name.to_string(), Box::new(f));
+        self
+    }
+
+    /// Expand all expressions in given string.
+    ///
+    /// Expressions are not nested: the first `}` closes an expression.
+    pub fn expand(&self, s: &str) -> Result<String> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+        while let Some(idx) = rest.find("${") {
+            if rest[..idx].ends_with('$') {
+                out.push_str(&rest[..idx - 1]);
+                out.push_str("${");
+                rest = &rest[idx + 2..];
+                continue;
+            }
+            out.push_str(&rest[..idx]);
+
+            let expr = &rest[idx + 2..];
+            let end = expr
+                .find('}')
+                .ok_or_else(|| anyhow!("interpolate `{s}`: unclosed expression"))?;
+            let (name, args) = expr[..end].split_once(':').unwrap_or((&expr[..end], ""));
+            let f = self
+                .functions
+                .get(name)
+                .ok_or_else(|| anyhow!("interpolate `{s}`: unknown function `{name}`"))?;
+            out.push_str(&f(args).map_err(|err| anyhow!("interpolate `{s}`: {err}"))?);
+            rest = &expr[end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Expand all string values inside given value in place.
+    pub(crate) fn expand_value(&self, v: &mut Value) -> Result<()> {
+        match v {
+            Value::Str(s) if s.contains("${") => *s = self.expand(s)?,
+            Value::Some(v) | Value::NewtypeStruct(_, v) => self.expand_value(v)?,
+            Value::NewtypeVariant { value, .. } => self.expand_value(value)?,
+            Value::Seq(vs)
+            | Value::Tuple(vs)
+            | Value::TupleStruct(_, vs)
+            | Value::TupleVariant { fields: vs, .. } => {
+                for v in vs {
+                    self.expand_value(v)?
+                }
+            }
+            Value::Struct(_, fields) | Value::StructVariant { fields, .. } => {
+                for v in fields.values_mut() {
+                    self.expand_value(v)?
+                }
+            }
+            Value::Map(m) => {
+                for v in m.values_mut() {
+                    self.expand_value(v)?
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Lookup env like shell's `${NAME:-default}`.
+fn env_function(args: &str) -> Result<String> {
+    let (name, default) = match args.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (args, None),
+    };
+    match (env::var(name), default) {
+        (Ok(v), Some(_)) if !v.is_empty() => Ok(v),
+        (Ok(v), None) => Ok(v),
+        (_, Some(default)) => Ok(default.to_string()),
+        (Err(_), None) => bail!("env `{name}` is not set"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        temp_env::with_vars(
+            vec![
+                ("SERFIG_TEST_INTERPOLATE", Some("serfig")),
+                ("SERFIG_TEST_INTERPOLATE_EMPTY", Some("")),
+            ],
+            || {
+                let i = Interpolator::default();
+                let cases = vec![
+                    ("plain", "plain"),
+                    ("${env:SERFIG_TEST_INTERPOLATE}/data", "serfig/data"),
+                    ("${env:SERFIG_TEST_INTERPOLATE:-x}", "serfig"),
+                    ("${env:SERFIG_TEST_INTERPOLATE_EMPTY:-x}", "x"),
+                    ("${env:SERFIG_TEST_INTERPOLATE_NOT_SET:-/root}", "/root"),
+                    ("${upper:abc}-${lower:DEF}", "ABC-def"),
+                    ("${concat:a,b,c}", "abc"),
+                    ("$${upper:abc}", "${upper:abc}"),
+                ];
+                for (input, expected) in cases {
+                    assert_eq!(i.expand(input).expect("must success"), expected, "{input}");
+                }
+
+                for input in [
+                    "${env:SERFIG_TEST_INTERPOLATE_NOT_SET}",
+                    "${unknown:abc}",
+                    "${upper:abc",
+                ] {
+                    assert!(i.expand(input).is_err(), "{input} must fail");
+                }
+            },
+        )
+    }
+}
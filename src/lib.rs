@@ -29,8 +29,41 @@
 //! }
 //! ```
 
+// Config is untrusted input, collecting, merging and deserializing must
+// never abort the host process.
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::unreachable,
+        clippy::indexing_slicing
+    )
+)]
+
 mod builder;
-pub use builder::Builder;
+pub use builder::{Builder, Group};
+
+mod load;
+pub use load::load;
+
+pub use serde_bridge::Value;
+
+mod value;
+pub use value::{MergeConfig, MergeStrategy, SeqPolicy};
+
+mod path;
+pub use path::{KeyPath, Segment};
+
+mod interpolate;
+pub use interpolate::Interpolator;
+
+mod template;
+pub use template::Template;
+
+mod service;
+pub use service::{ConfigHandle, ConfigHealth, ConfigService, OverlayGuard};
 
 pub mod collectors;
 pub use collectors::Collector;
@@ -38,4 +71,36 @@ pub use collectors::Collector;
 pub mod parsers;
 pub use parsers::Parser;
 
-mod value;
+pub mod testing;
+
+pub mod de;
+
+pub mod defaultness;
+
+mod check;
+pub use check::{check_file, BuildCheck, CheckReport, CheckStatus};
+
+mod diff;
+pub use diff::{diff_files, Change, ConfigDiff};
+
+mod explain;
+pub use explain::{Detail, ExplainEntry, Explanation};
+
+mod plan;
+pub use plan::{BuildPlan, PlannedSource, TrustPolicy};
+
+mod report;
+pub use report::{BuildReport, LayerStats, ReportedKey};
+
+mod snapshot;
+pub use snapshot::Snapshot;
+
+mod usage;
+pub use usage::Tracked;
+
+mod constraint;
+pub use constraint::{Annotation, Rule};
+
+mod history;
+mod probe;
+mod snake;
@@ -0,0 +1,119 @@
+use std::env;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::collectors::{from_env_prefixed, from_file};
+use crate::parsers::Toml;
+use crate::Builder;
+
+/// Load config for app `name` with a sensible default stack.
+///
+/// Layers are applied in the following order, later layers override
+/// earlier ones:
+///
+/// - `V::default()`
+/// - `/etc/{name}/config.toml`
+/// - `$XDG_CONFIG_HOME/{name}/config.toml` or `$HOME/.config/{name}/config.toml`
+/// - `./{name}.toml`
+/// - Env variables starting with `{NAME}_`, like `MYAPP_PORT` for `myapp`.
+///   `-` in `name` is replaced by `_`.
+///
+/// Missing files are skipped. Use [`Builder`] directly for anything more
+/// involved, this function is built upon it only.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     port: u16,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let t: TestConfig = serfig::load("myapp")?;
+///
+///     println!("{:?}", t);
+///     Ok(())
+/// }
+/// ```
+pub fn load<V>(name: &str) -> Result<V>
+where
+    V: DeserializeOwned + Serialize + Debug + Default + 'static,
+{
+    let mut builder = Builder::default();
+    for path in config_paths(name) {
+        if path.is_file() {
+            builder = builder.collect(from_file(Toml, &path.to_string_lossy()));
+        }
+    }
+
+    let prefix = name.to_uppercase().replace('-', "_");
+    builder.collect(from_env_prefixed(&prefix)).build()
+}
+
+/// Standard config file paths of app `name` from system wide to local.
+fn config_paths(name: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/etc").join(name).join("config.toml")];
+
+    let user_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|v| PathBuf::from(v).join(".config")));
+    if let Some(dir) = user_dir {
+        paths.push(dir.join(name).join("config.toml"));
+    }
+
+    paths.push(PathBuf::from(format!("{name}.toml")));
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        addr: String,
+        port: u16,
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = env::temp_dir().join("serfig-load");
+        fs::create_dir_all(dir.join("serfig-load-test")).expect("create dir");
+        fs::write(
+            dir.join("serfig-load-test/config.toml"),
+            "addr = \"127.0.0.1\"\nport = 80",
+        )
+        .expect("write config");
+
+        temp_env::with_vars(
+            [
+                ("XDG_CONFIG_HOME", Some(dir.to_str().expect("utf-8 path"))),
+                ("SERFIG_LOAD_TEST_PORT", Some("8080")),
+            ],
+            || {
+                let t: TestConfig = load("serfig-load-test").expect("load");
+                assert_eq!(
+                    t,
+                    TestConfig {
+                        addr: "127.0.0.1".to_string(),
+                        port: 8080,
+                    }
+                );
+            },
+        );
+    }
+}
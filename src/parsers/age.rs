@@ -0,0 +1,133 @@
+use std::fmt::{self, Debug, Formatter};
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+
+use crate::value::REDACTED;
+use crate::Parser;
+
+/// Decrypt [age](https://age-encryption.org) encrypted input before
+/// passing it to the inner parser.
+///
+/// Both binary and armored (`-----BEGIN AGE ENCRYPTED FILE-----`) files
+/// are accepted. The identity is an x25519 secret key like
+/// `AGE-SECRET-KEY-1...`, which is usually read from a key file or env.
+///
+/// Requires feature `age`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_file;
+/// use serfig::parsers::{Age, Toml};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     db_password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let identity = std::fs::read_to_string("/etc/myapp/key.txt")?;
+///     let builder = Builder::default()
+///         .collect(from_file(Age::new(Toml, &identity), "secrets.toml.age"));
+///
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub struct Age<P: Parser> {
+    inner: P,
+    identity: String,
+}
+
+impl<P: Parser> Age<P> {
+    /// Wrap given parser, input will be decrypted with `identity`.
+    ///
+    /// Comment lines starting with `#` like the ones written by
+    /// `age-keygen` are ignored.
+    pub fn new(inner: P, identity: &str) -> Self {
+        Self {
+            inner,
+            identity: identity.to_string(),
+        }
+    }
+
+    fn decrypt(&self, bs: &[u8]) -> Result<Vec<u8>> {
+        let key = self
+            .identity
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#'))
+            .ok_or_else(|| anyhow!("decrypt age: identity is empty"))?;
+        let identity: age::x25519::Identity = key
+            .parse()
+            .map_err(|err| anyhow!("decrypt age: invalid identity: {err}"))?;
+        age::decrypt(&identity, bs).map_err(|err| anyhow!("decrypt age: {err}"))
+    }
+}
+
+impl<P: Parser> Debug for Age<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Age")
+            .field("identity", &REDACTED)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: Parser> Parser for Age<P> {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let bs = self.decrypt(bs)?;
+        self.inner.parse(&bs)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        let bs = self.decrypt(bs)?;
+        self.inner.parse_value(&bs)
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        self.inner.export(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestConfig {
+        db_password: String,
+    }
+
+    #[test]
+    fn test_age() {
+        let key = age::x25519::Identity::generate();
+        let plaintext = br#"db_password = "hunter2""#;
+        let encrypted = age::encrypt(&key.to_public(), plaintext).expect("must encrypt");
+        let armored = age::encrypt_and_armor(&key.to_public(), plaintext)
+            .expect("must encrypt")
+            .into_bytes();
+
+        use age::secrecy::ExposeSecret;
+        let identity = format!("# created: now\n{}\n", key.to_string().expose_secret());
+        let mut p = Age::new(Toml, &identity);
+        assert!(!format!("{p:?}").contains("AGE-SECRET-KEY"));
+
+        for bs in [encrypted, armored] {
+            let t: TestConfig = p.parse(&bs).expect("must decrypt");
+            assert_eq!(t.db_password, "hunter2");
+        }
+
+        let other = age::x25519::Identity::generate();
+        let mut p = Age::new(Toml, other.to_string().expose_secret());
+        let encrypted = age::encrypt(&key.to_public(), plaintext).expect("must encrypt");
+        assert!(p.parse::<TestConfig>(&encrypted).is_err());
+    }
+}
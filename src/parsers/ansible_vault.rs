@@ -0,0 +1,217 @@
+use std::fmt::{self, Debug, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use anyhow::{anyhow, bail, Result};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+use sha2::Sha256;
+
+use crate::value::REDACTED;
+use crate::Parser;
+
+type Aes256Ctr = ctr::Ctr128BE<aes::Aes256>;
+
+/// Iterations of PBKDF2 used by ansible-vault to derive keys.
+const ITERATIONS: u32 = 10000;
+
+/// Decrypt [Ansible Vault](https://docs.ansible.com/ansible/latest/vault_guide/index.html)
+/// encrypted input before passing it to the inner parser.
+///
+/// Files created by `ansible-vault encrypt` with format `1.1` or `1.2`
+/// (vault ids) and cipher `AES256` are supported. Input without the
+/// `$ANSIBLE_VAULT` header is rejected.
+///
+/// Requires feature `ansible-vault`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_file;
+/// use serfig::parsers::{AnsibleVault, Toml};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     db_password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(from_file(
+///         AnsibleVault::with_password_file(Toml, "/etc/myapp/vault-pass"),
+///         "secrets.toml",
+///     ));
+///
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub struct AnsibleVault<P: Parser> {
+    inner: P,
+    password: Password,
+}
+
+enum Password {
+    Plain(String),
+    File(PathBuf),
+}
+
+impl<P: Parser> AnsibleVault<P> {
+    /// Wrap given parser, input will be decrypted with `password`.
+    pub fn new(inner: P, password: &str) -> Self {
+        Self {
+            inner,
+            password: Password::Plain(password.to_string()),
+        }
+    }
+
+    /// Wrap given parser, input will be decrypted with the password read
+    /// from `path` like `--vault-password-file` of ansible.
+    ///
+    /// The file is read while parsing, surrounding whitespace is trimmed.
+    pub fn with_password_file(inner: P, path: impl AsRef<Path>) -> Self {
+        Self {
+            inner,
+            password: Password::File(path.as_ref().to_path_buf()),
+        }
+    }
+
+    fn password(&self) -> Result<String> {
+        match &self.password {
+            Password::Plain(s) => Ok(s.clone()),
+            Password::File(path) => {
+                let s = fs::read_to_string(path)
+                    .map_err(|err| anyhow!("read password file {}: {err}", path.display()))?;
+                Ok(s.trim().to_string())
+            }
+        }
+    }
+
+    fn decrypt(&self, bs: &[u8]) -> Result<Vec<u8>> {
+        let s = std::str::from_utf8(bs)?;
+        let mut lines = s.lines();
+        let header = lines.next().unwrap_or_default();
+        let mut fields = header.trim().split(';');
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some("$ANSIBLE_VAULT"), Some("1.1" | "1.2"), Some("AES256")) => {}
+            (Some("$ANSIBLE_VAULT"), Some(version), Some(cipher)) => {
+                bail!("ansible vault {version} with cipher {cipher} is not supported")
+            }
+            _ => bail!("input is not ansible vault encrypted"),
+        }
+
+        let body: String = lines.map(str::trim).collect();
+        let body = String::from_utf8(hex::decode(body)?)?;
+        let (salt, mac, ciphertext) = match body.split('\n').collect::<Vec<_>>()[..] {
+            [salt, mac, ciphertext] => (
+                hex::decode(salt)?,
+                hex::decode(mac)?,
+                hex::decode(ciphertext)?,
+            ),
+            _ => bail!("ansible vault payload is malformed"),
+        };
+
+        let mut keys = [0u8; 80];
+        pbkdf2::pbkdf2_hmac::<Sha256>(self.password()?.as_bytes(), &salt, ITERATIONS, &mut keys);
+        let (cipher_key, rest) = keys.split_at(32);
+        let (mac_key, iv) = rest.split_at(32);
+
+        let mut hmac = Hmac::<Sha256>::new_from_slice(mac_key)?;
+        hmac.update(&ciphertext);
+        hmac.verify_slice(&mac)
+            .map_err(|_| anyhow!("ansible vault hmac mismatch, password is wrong"))?;
+
+        let mut plaintext = ciphertext;
+        Aes256Ctr::new_from_slices(cipher_key, iv)
+            .map_err(|err| anyhow!("init cipher: {err}"))?
+            .apply_keystream(&mut plaintext);
+
+        // Remove PKCS#7 padding.
+        let pad = plaintext.last().copied().unwrap_or_default() as usize;
+        if pad == 0 || pad > 16 || pad > plaintext.len() {
+            bail!("ansible vault padding is malformed");
+        }
+        plaintext.truncate(plaintext.len() - pad);
+        Ok(plaintext)
+    }
+}
+
+impl<P: Parser> Debug for AnsibleVault<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("AnsibleVault");
+        match &self.password {
+            Password::Plain(_) => d.field("password", &REDACTED),
+            Password::File(path) => d.field("password_file", path),
+        };
+        d.finish_non_exhaustive()
+    }
+}
+
+impl<P: Parser> Parser for AnsibleVault<P> {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let bs = self
+            .decrypt(bs)
+            .map_err(|err| anyhow!("decrypt ansible vault: {err}"))?;
+        self.inner.parse(&bs)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        let bs = self
+            .decrypt(bs)
+            .map_err(|err| anyhow!("decrypt ansible vault: {err}"))?;
+        self.inner.parse_value(&bs)
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        self.inner.export(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestConfig {
+        db_password: String,
+    }
+
+    /// `db_password = "s3cret"` encrypted with password `hunter2`.
+    const VAULT: &str = "$ANSIBLE_VAULT;1.1;AES256
+30303031303230333034303530363037303830393061306230633064306530663130313131323133
+3134313531363137313831393161316231633164316531660a363537346430323538363966306538
+62626130336162313933653662663366666137646539643536663838376239393764396439303333
+3636666438346566340a306338363438666465303866316132623639313939316566363563643932
+36613132333939306132353630633432646666353230623564353730396531643762
+";
+
+    #[test]
+    fn test_ansible_vault() {
+        let mut p = AnsibleVault::new(Toml, "hunter2");
+        let t: TestConfig = p.parse(VAULT.as_bytes()).expect("must decrypt");
+        assert_eq!(t.db_password, "s3cret");
+        assert!(!format!("{p:?}").contains("hunter2"));
+
+        let path = std::env::temp_dir().join("serfig-ansible-vault-pass");
+        fs::write(&path, "hunter2\n").expect("must write");
+        let mut p = AnsibleVault::with_password_file(Toml, &path);
+        let t: TestConfig = p.parse(VAULT.as_bytes()).expect("must decrypt");
+        assert_eq!(t.db_password, "s3cret");
+
+        let mut p = AnsibleVault::new(Toml, "wrong");
+        let err = p
+            .parse::<TestConfig>(VAULT.as_bytes())
+            .expect_err("must fail");
+        assert!(err.to_string().contains("hmac mismatch"), "{err}");
+
+        let mut p = AnsibleVault::new(Toml, "hunter2");
+        assert!(p.parse::<TestConfig>(br#"db_password = "s3cret""#).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use std::io;
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+
+use crate::Parser;
+
+/// CBOR format support
+///
+/// The input is decoded as a single [CBOR](https://cbor.io) data item,
+/// which is useful for compact binary configs pushed to embedded devices.
+#[derive(Debug)]
+pub struct Cbor;
+
+impl Parser for Cbor {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        ciborium::de::from_reader(bs).map_err(|err| anyhow!("decode cbor: {err}"))
+    }
+
+    fn parse_reader<T: DeserializeOwned>(&mut self, r: &mut dyn io::Read) -> Result<T> {
+        ciborium::de::from_reader(r).map_err(|err| match err {
+            // Keep io errors like not found as they are.
+            ciborium::de::Error::Io(err) => err.into(),
+            err => anyhow!("decode cbor: {err}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = TestStruct {
+            name: "serfig".to_string(),
+            ports: vec![8001, 8002],
+        };
+        let mut bs = Vec::new();
+        ciborium::ser::into_writer(&expected, &mut bs).expect("encode cbor");
+
+        let t: TestStruct = Cbor.parse(&bs).expect("must success");
+        assert_eq!(t, expected);
+
+        assert!(Cbor.parse::<TestStruct>(&bs[..bs.len() - 1]).is_err());
+
+        let t: TestStruct = Cbor
+            .parse_reader(&mut io::Cursor::new(&bs))
+            .expect("must success");
+        assert_eq!(t, expected);
+    }
+}
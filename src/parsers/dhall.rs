@@ -0,0 +1,53 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+
+use crate::Parser;
+
+/// Dhall format support
+///
+/// The input is evaluated as a [Dhall](https://dhall-lang.org) expression
+/// and the normalized result is deserialized. Relative imports are
+/// resolved against the current working directory.
+#[derive(Debug)]
+pub struct Dhall;
+
+impl Parser for Dhall {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+        serde_dhall::from_str(s)
+            .parse()
+            .map_err(|err| anyhow!("evaluate dhall: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+let base = 8000
+in  { name = "serfig", ports = [ base + 1, base + 2 ] }
+"#;
+
+        let t: TestStruct = Dhall.parse(content.as_bytes()).expect("must success");
+
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                ports: vec![8001, 8002],
+            }
+        )
+    }
+}
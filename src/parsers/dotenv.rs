@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+
+use crate::{snake, Parser};
+
+/// DotEnv format support
+///
+/// Every line of the input is a `KEY=VALUE` pair which could be:
+///
+/// - unquoted: `KEY=value # comment`
+/// - single quoted without escapes: `KEY='value'`
+/// - double quoted with escapes like `\n`: `KEY="value"`
+///
+/// Lines start with `#` are comments, and the optional `export ` prefix
+/// will be ignored. Keys are mapped into fields in the same way as
+/// [`from_env`][crate::collectors::from_env].
+#[derive(Debug)]
+pub struct DotEnv;
+
+impl Parser for DotEnv {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+
+        let mut pairs = Vec::new();
+        for (idx, line) in s.lines().enumerate() {
+            if let Some(pair) =
+                parse_line(line).map_err(|err| anyhow!("parse line {}: {err}", idx + 1))?
+            {
+                pairs.push(pair);
+            }
+        }
+
+        Ok(snake::from_iter(pairs)?)
+    }
+}
+
+/// Parse a line into key value pair, returns `None` for empty lines and
+/// comments.
+fn parse_line(line: &str) -> Result<Option<(String, String)>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+
+    let (key, value) = line
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expect KEY=VALUE but got `{line}`"))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(anyhow!("key is empty"));
+    }
+    let value = value.trim();
+
+    let value = if let Some(v) = value.strip_prefix('\'') {
+        let (v, _) = v
+            .split_once('\'')
+            .ok_or_else(|| anyhow!("unclosed single quote for `{key}`"))?;
+        v.to_string()
+    } else if let Some(v) = value.strip_prefix('"') {
+        parse_double_quoted(v).ok_or_else(|| anyhow!("unclosed double quote for `{key}`"))?
+    } else {
+        match value.split_once(" #") {
+            Some((v, _)) => v.trim_end().to_string(),
+            None => value.to_string(),
+        }
+    };
+
+    Ok(Some((key.to_string(), value)))
+}
+
+/// Parse the content after the opening double quote.
+fn parse_double_quoted(s: &str) -> Option<String> {
+    let mut value = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                c => value.push(c),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestDatabase {
+        url: String,
+        pool: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        message: String,
+        raw: String,
+        debug: bool,
+        database: TestDatabase,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+# This is a comment
+NAME=serfig # inline comment
+export MESSAGE="Hello,\n\"World\""
+RAW='no\nescape'
+DEBUG=true
+DATABASE_URL=postgres://localhost/db
+DATABASE_POOL=8
+"#;
+
+        let t: TestStruct = DotEnv.parse(content.as_bytes()).expect("must success");
+
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                message: "Hello,\n\"World\"".to_string(),
+                raw: "no\\nescape".to_string(),
+                debug: true,
+                database: TestDatabase {
+                    url: "postgres://localhost/db".to_string(),
+                    pool: 8,
+                },
+            }
+        )
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let err = DotEnv
+            .parse::<TestStruct>(b"NAME=serfig\nINVALID")
+            .expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            "parse line 2: expect KEY=VALUE but got `INVALID`"
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestIsolated {
+        name: String,
+        home: Option<String>,
+    }
+
+    #[test]
+    fn test_parse_ignore_process_env() {
+        // Only pairs in the input are used, the process environment is
+        // never read.
+        temp_env::with_vars([("NAME", Some("env")), ("HOME", Some("/root"))], || {
+            let t: TestIsolated = DotEnv.parse(b"NAME=dotenv").expect("must success");
+            assert_eq!(
+                t,
+                TestIsolated {
+                    name: "dotenv".to_string(),
+                    home: None,
+                }
+            )
+        });
+    }
+}
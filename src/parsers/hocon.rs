@@ -0,0 +1,70 @@
+use anyhow::{anyhow, Result};
+use hocon::HoconLoader;
+use serde::de::DeserializeOwned;
+
+use crate::Parser;
+
+/// Hocon format support
+///
+/// HOCON's own `include` and `${substitution}` semantics are resolved
+/// before the document is deserialized. Relative includes are resolved
+/// against the current working directory.
+#[derive(Debug)]
+pub struct Hocon;
+
+impl Parser for Hocon {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+        HoconLoader::new()
+            .load_str(s)
+            .and_then(|loader| loader.resolve())
+            .map_err(|err| anyhow!("parse hocon: {err:?}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestServer {
+        host: String,
+        port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        server: TestServer,
+        url: String,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+name = "serfig"
+server {
+  host = localhost
+  port = 8080
+}
+url = "http://"${server.host}":"${server.port}
+"#;
+
+        let t: TestStruct = Hocon.parse(content.as_bytes()).expect("must success");
+
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                server: TestServer {
+                    host: "localhost".to_string(),
+                    port: 8080,
+                },
+                url: "http://localhost:8080".to_string(),
+            }
+        )
+    }
+}
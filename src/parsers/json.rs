@@ -0,0 +1,101 @@
+use std::io;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+
+use crate::value::from_json;
+use crate::Parser;
+
+/// JSON format support
+///
+/// Unlike [`Jsonc`][crate::parsers::Jsonc], input is decoded directly from
+/// the reader without buffering it first, which keeps memory low for large
+/// payloads. Comments and trailing commas are not allowed.
+#[derive(Debug)]
+pub struct Json;
+
+/// Convert `err` into anyhow error, io errors are kept as they are so
+/// callers can still check their kinds.
+fn map_err(err: serde_json::Error) -> anyhow::Error {
+    if err.is_io() {
+        io::Error::from(err).into()
+    } else {
+        err.into()
+    }
+}
+
+impl Parser for Json {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        Ok(serde_json::from_slice(bs)?)
+    }
+
+    fn parse_reader<T: DeserializeOwned>(&mut self, r: &mut dyn io::Read) -> Result<T> {
+        serde_json::from_reader(io::BufReader::new(r)).map_err(map_err)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        Ok(from_json(self.parse(bs)?))
+    }
+
+    fn parse_value_reader(&mut self, r: &mut dyn io::Read) -> Result<Value> {
+        Ok(from_json(self.parse_reader(r)?))
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(v)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        ports: Vec<u64>,
+    }
+
+    /// Reader that fails after returning `bs`.
+    struct Broken<'a> {
+        bs: &'a [u8],
+    }
+
+    impl io::Read for Broken<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.bs.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+            }
+            io::Read::read(&mut self.bs, buf)
+        }
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let input = br#"{"name": "serfig", "ports": [8001, 8002]}"#;
+
+        let t: TestStruct = Json
+            .parse_reader(&mut io::Cursor::new(input))
+            .expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                ports: vec![8001, 8002],
+            }
+        );
+
+        let err = Json
+            .parse_reader::<TestStruct>(&mut Broken { bs: &input[..10] })
+            .expect_err("must fail");
+        let err = err.downcast::<io::Error>().expect("must be io error");
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        assert!(Json
+            .parse_reader::<TestStruct>(&mut io::Cursor::new(b"{\"name\": 1}"))
+            .is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+
+use crate::value::from_json;
+use crate::Parser;
+
+/// JSON with comments support
+///
+/// Line comments (`//`), block comments (`/* */`) and trailing commas are
+/// allowed like VS Code's `.jsonc` files. They are replaced by spaces
+/// before deserialization so that error positions are kept.
+#[derive(Debug)]
+pub struct Jsonc;
+
+impl Parser for Jsonc {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+        let s = strip_trailing_commas(&strip_comments(s)?);
+        Ok(serde_json::from_str(&s)?)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        Ok(from_json(self.parse(bs)?))
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(v)?)
+    }
+}
+
+/// Replace comments with spaces, newlines are kept.
+fn strip_comments(s: &str) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    let mut in_str = false;
+
+    while let Some(c) = chars.next() {
+        if in_str {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_str = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_str = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                out.push_str("  ");
+                chars.next();
+                while let Some(&c) = chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    out.push(' ');
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                out.push_str("  ");
+                chars.next();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.peek() == Some(&'/') {
+                        out.push_str("  ");
+                        chars.next();
+                        closed = true;
+                        break;
+                    }
+                    out.push(if c == '\n' { '\n' } else { ' ' });
+                }
+                if !closed {
+                    bail!("block comment is not closed");
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Replace commas followed by `}` or `]` with spaces.
+fn strip_trailing_commas(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    let mut in_str = false;
+    let mut escaped = false;
+
+    for (idx, &c) in chars.iter().enumerate() {
+        if in_str {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_str = false,
+                _ => {}
+            }
+            out.push(c);
+            continue;
+        }
+
+        match c {
+            '"' => in_str = true,
+            ',' => {
+                let next = chars.iter().skip(idx + 1).find(|c| !c.is_whitespace());
+                if matches!(next, Some('}' | ']')) {
+                    out.push(' ');
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        url: String,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"{
+    // name of the service
+    "name": "serfig /* not a comment */",
+    "url": "http://example.com", /* trailing comment */
+    "ports": [
+        8001,
+        8002, // trailing comma
+    ],
+}"#;
+
+        let t: TestStruct = Jsonc.parse(content.as_bytes()).expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig /* not a comment */".to_string(),
+                url: "http://example.com".to_string(),
+                ports: vec![8001, 8002],
+            }
+        );
+
+        let err = Jsonc
+            .parse::<TestStruct>(b"{ /* unclosed")
+            .expect_err("must fail");
+        assert_eq!(err.to_string(), "block comment is not closed");
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use jrsonnet_evaluator::{EvaluationState, FileImportResolver};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+
+use crate::value::from_json;
+use crate::Parser;
+
+/// Jsonnet format support
+///
+/// The input is evaluated as a [Jsonnet](https://jsonnet.org) program and
+/// the manifested JSON is deserialized. Imports are resolved relative to the
+/// current working directory and then the configured import paths.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serfig::parsers::Jsonnet;
+///
+/// let parser = Jsonnet::default()
+///     .import_path("/etc/app/lib")
+///     .ext_var("env", "prod");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Jsonnet {
+    import_paths: Vec<PathBuf>,
+    ext_vars: Vec<(String, String)>,
+}
+
+impl Jsonnet {
+    /// Add a library path to search for imported files, a.k.a `--jpath`.
+    pub fn import_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.import_paths.push(path.into());
+        self
+    }
+
+    /// Add an external string variable which can be read via `std.extVar`.
+    pub fn ext_var(mut self, name: &str, value: &str) -> Self {
+        self.ext_vars.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl Jsonnet {
+    fn evaluate(&self, bs: &[u8]) -> Result<serde_json::Value> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+
+        let state = EvaluationState::default();
+        state.with_stdlib();
+        state.set_import_resolver(Box::new(FileImportResolver {
+            library_paths: self.import_paths.clone(),
+        }));
+        for (k, v) in &self.ext_vars {
+            state.add_ext_str(k.as_str().into(), v.as_str().into());
+        }
+
+        let json = state
+            .run_in_state(|| {
+                let val = state.evaluate_snippet_raw(PathBuf::from("<input>").into(), s.into())?;
+                serde_json::Value::try_from(&val)
+            })
+            .map_err(|err| anyhow!("evaluate jsonnet: {}", state.stringify_err(&err)))?;
+
+        Ok(json)
+    }
+}
+
+impl Parser for Jsonnet {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        Ok(serde_json::from_value(self.evaluate(bs)?)?)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        Ok(from_json(self.evaluate(bs)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        env: String,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let lib = std::env::temp_dir().join("serfig-jsonnet-lib");
+        fs::create_dir_all(&lib).expect("create dir");
+        fs::write(lib.join("base.libsonnet"), "{ port:: 8000 }").expect("write lib");
+
+        let content = r#"
+local base = import 'base.libsonnet';
+{
+  name: 'serfig',
+  env: std.extVar('env'),
+  ports: [base.port + i for i in std.range(1, 2)],
+}
+"#;
+
+        let t: TestStruct = Jsonnet::default()
+            .import_path(&lib)
+            .ext_var("env", "prod")
+            .parse(content.as_bytes())
+            .expect("must success");
+
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                env: "prod".to_string(),
+                ports: vec![8001, 8002],
+            }
+        )
+    }
+}
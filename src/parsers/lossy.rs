@@ -0,0 +1,113 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+
+use crate::Parser;
+
+/// Decode input lossily before passing it to the inner text parser.
+///
+/// Text parsers like [`Toml`][crate::parsers::Toml] fail on invalid UTF-8
+/// by default. Wrapping them with `Lossy` replaces invalid sequences with
+/// `U+FFFD` and logs a warning with their byte offsets instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serfig::collectors::from_file;
+/// use serfig::parsers::{Lossy, Toml};
+/// # use serde::{Deserialize, Serialize};
+/// # #[derive(Debug, Serialize, Deserialize, Default)]
+/// # struct TestConfig {}
+///
+/// let c = from_file::<TestConfig, _>(Lossy::new(Toml), "config.toml");
+/// ```
+#[derive(Debug)]
+pub struct Lossy<P: Parser> {
+    inner: P,
+}
+
+impl<P: Parser> Lossy<P> {
+    /// Wrap given parser.
+    pub fn new(inner: P) -> Self {
+        Self { inner }
+    }
+}
+
+/// Decode `bs` lossily, returns `bs` itself if it's valid UTF-8.
+fn decode(bs: &[u8]) -> Cow<'_, [u8]> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    for chunk in bs.utf8_chunks() {
+        offset += chunk.valid().len();
+        if !chunk.invalid().is_empty() {
+            offsets.push(offset);
+            offset += chunk.invalid().len();
+        }
+    }
+    if offsets.is_empty() {
+        return Cow::Borrowed(bs);
+    }
+
+    warn!("input value is not valid utf-8, invalid bytes at offsets {offsets:?} are replaced");
+    Cow::Owned(String::from_utf8_lossy(bs).into_owned().into_bytes())
+}
+
+impl<P: Parser> Parser for Lossy<P> {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        self.inner.parse(&decode(bs))
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        self.inner.parse_value(&decode(bs))
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        self.inner.export(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::{Json, Toml};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        port: u16,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = b"name = \"ser\xfffig\"\nport = 8080\n";
+
+        assert!(Toml.parse::<TestStruct>(content).is_err());
+
+        let t: TestStruct = Lossy::new(Toml).parse(content).expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "ser\u{FFFD}fig".to_string(),
+                port: 8080,
+            }
+        )
+    }
+
+    #[test]
+    fn test_parse_value() {
+        // Forwarded to the inner parser, which converts JSON numbers
+        // explicitly.
+        let v = Lossy::new(Json)
+            .parse_value(b"{\"name\": \"ser\xfffig\", \"port\": 8080}")
+            .expect("must success");
+        assert_eq!(
+            crate::value::get(&v, &"port".parse().expect("must be valid path")),
+            Some(&Value::U64(8080))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use mlua::{LuaOptions, LuaSerdeExt, StdLib};
+use serde::de::DeserializeOwned;
+
+use crate::Parser;
+
+/// Lua script support
+///
+/// The input is executed as a [Lua](https://www.lua.org) chunk and the
+/// returned table is deserialized. Scripts are sandboxed: only `table`,
+/// `string`, `math` and `utf8` libraries are available, so scripts can't
+/// touch files, processes or load other modules.
+///
+/// ```lua
+/// local base = 8000
+/// return { name = "serfig", ports = { base + 1, base + 2 } }
+/// ```
+#[derive(Debug)]
+pub struct Lua;
+
+impl Parser for Lua {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let lua = mlua::Lua::new_with(
+            StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8,
+            LuaOptions::default(),
+        )
+        .map_err(|err| anyhow!("create lua state: {err}"))?;
+
+        let globals = lua.globals();
+        for name in ["dofile", "loadfile", "load", "collectgarbage"] {
+            globals
+                .raw_remove(name)
+                .map_err(|err| anyhow!("sandbox lua state: {err}"))?;
+        }
+
+        let value: mlua::Value = lua
+            .load(bs)
+            .set_name("=config")
+            .eval()
+            .map_err(|err| anyhow!("evaluate lua: {err}"))?;
+        lua.from_value(value)
+            .map_err(|err| anyhow!("deserialize lua value: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+local ports = {}
+for i = 1, 2 do
+  table.insert(ports, 8000 + i)
+end
+return { name = string.lower("SERFIG"), ports = ports }
+"#;
+
+        let t: TestStruct = Lua.parse(content.as_bytes()).expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                ports: vec![8001, 8002],
+            }
+        );
+
+        for content in [
+            r#"return { name = io.read(), ports = {} }"#,
+            r#"return { name = os.getenv("HOME"), ports = {} }"#,
+            r#"return dofile("/etc/passwd")"#,
+        ] {
+            assert!(Lua.parse::<TestStruct>(content.as_bytes()).is_err());
+        }
+    }
+}
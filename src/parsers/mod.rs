@@ -1,7 +1,101 @@
 //! Parsers will provide abstractions for parsing structural data like toml and json.
+//!
+//! We are supports the following parsers:
+//!
+//! - [`Toml`]: Parse [toml](https://toml.io) documents.
+//! - [`DotEnv`]: Parse `.env` files with `KEY=VALUE` pairs.
+//! - [`Json`]: Parse JSON documents, streaming from readers.
+//! - [`Jsonc`]: Parse JSON with comments and trailing commas.
+//! - `Dhall`: Evaluate [Dhall](https://dhall-lang.org) expressions, requires feature `dhall`.
+//! - `Hocon`: Parse [HOCON](https://github.com/lightbend/config/blob/main/HOCON.md) documents, requires feature `hocon`.
+//! - `Cbor`: Decode [CBOR](https://cbor.io) data items, requires feature `cbor`.
+//! - `Jsonnet`: Evaluate [Jsonnet](https://jsonnet.org) programs, requires feature `jsonnet`.
+//! - `Lua`: Execute sandboxed [Lua](https://www.lua.org) scripts, requires feature `lua`.
+//! - `Plist`: Parse XML and binary property lists, requires feature `plist`.
+//! - `Rhai`: Evaluate [Rhai](https://rhai.rs) scripts, requires feature `rhai`.
+//! - `Starlark`: Evaluate [Starlark](https://github.com/bazelbuild/starlark) modules, requires feature `starlark`.
+//! - `Age`: Decrypt [age](https://age-encryption.org) encrypted input before parsing, requires feature `age`.
+//! - `AnsibleVault`: Decrypt [Ansible Vault](https://docs.ansible.com/ansible/latest/vault_guide/index.html) encrypted input before parsing, requires feature `ansible-vault`.
+//! - `Verified`: Verify a sha256 checksum or ed25519 signature of input before parsing, requires feature `verify`.
+//! - `Templated`: Render [MiniJinja](https://docs.rs/minijinja) templates before parsing, requires feature `template`.
+//!
+//! Text parsers reject invalid UTF-8 input, wrap them with [`Lossy`] to
+//! decode lossily instead.
 
 mod parser;
 pub use parser::Parser;
 
 mod toml;
 pub use self::toml::Toml;
+
+mod dotenv;
+pub use dotenv::DotEnv;
+
+mod json;
+pub use json::Json;
+
+mod jsonc;
+pub use jsonc::Jsonc;
+
+mod lossy;
+pub use lossy::Lossy;
+
+#[cfg(feature = "age")]
+mod age;
+#[cfg(feature = "age")]
+pub use self::age::Age;
+
+#[cfg(feature = "ansible-vault")]
+mod ansible_vault;
+#[cfg(feature = "ansible-vault")]
+pub use self::ansible_vault::AnsibleVault;
+
+#[cfg(feature = "hocon")]
+mod hocon;
+#[cfg(feature = "hocon")]
+pub use self::hocon::Hocon;
+
+#[cfg(feature = "dhall")]
+mod dhall;
+#[cfg(feature = "dhall")]
+pub use self::dhall::Dhall;
+
+#[cfg(feature = "jsonnet")]
+mod jsonnet;
+#[cfg(feature = "jsonnet")]
+pub use self::jsonnet::Jsonnet;
+
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "cbor")]
+pub use self::cbor::Cbor;
+
+#[cfg(feature = "plist")]
+mod plist;
+#[cfg(feature = "plist")]
+pub use self::plist::Plist;
+
+#[cfg(feature = "lua")]
+mod lua;
+#[cfg(feature = "lua")]
+pub use self::lua::Lua;
+
+#[cfg(feature = "rhai")]
+mod rhai;
+#[cfg(feature = "rhai")]
+pub use self::rhai::Rhai;
+
+#[cfg(feature = "starlark")]
+mod starlark;
+#[cfg(feature = "starlark")]
+pub use self::starlark::Starlark;
+
+#[cfg(feature = "verify")]
+mod verified;
+#[cfg(feature = "verify")]
+pub use self::verified::Verified;
+
+#[cfg(feature = "template")]
+mod templated;
+#[cfg(feature = "template")]
+pub use self::templated::Templated;
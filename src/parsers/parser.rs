@@ -1,7 +1,75 @@
-use anyhow::Result;
+use std::io;
+
+use anyhow::{anyhow, Result};
 use serde::de::DeserializeOwned;
+use serde_bridge::Value;
 
 /// Parse input bytes into specified type `T`.
 pub trait Parser {
     fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T>;
+
+    /// Parse input bytes into [`Value`] without a target type.
+    ///
+    /// Unlike [`Parser::parse`], keys unknown to the config type are kept
+    /// so the builder can decide how strict to be later.
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        self.parse(bs)
+    }
+
+    /// Parse input from reader `r` into specified type `T`.
+    ///
+    /// Reads all input into memory and calls [`Parser::parse`] by default,
+    /// formats that can be decoded incrementally like JSON should override
+    /// this to avoid buffering large payloads.
+    fn parse_reader<T: DeserializeOwned>(&mut self, r: &mut dyn io::Read) -> Result<T> {
+        let mut bs = Vec::new();
+        r.read_to_end(&mut bs)?;
+        self.parse(&bs)
+    }
+
+    /// Parse input from reader `r` into [`Value`] without a target type.
+    ///
+    /// Reads all input into memory and calls [`Parser::parse_value`] by
+    /// default.
+    fn parse_value_reader(&mut self, r: &mut dyn io::Read) -> Result<Value> {
+        let mut bs = Vec::new();
+        r.read_to_end(&mut bs)?;
+        self.parse_value(&bs)
+    }
+
+    /// Serialize [`Value`] back into bytes of this format.
+    ///
+    /// Returns error by default for formats that can't be written.
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        let _ = v;
+        Err(anyhow!("export is not supported by this parser"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{Jsonc, Toml};
+
+    #[test]
+    fn test_parse_value() {
+        let v = Toml
+            .parse_value(b"unknown = 1\n[server]\nport = 8080")
+            .expect("must success");
+        assert_eq!(
+            crate::value::flatten(&v).into_iter().collect::<Vec<_>>(),
+            vec![
+                ("server.port".to_string(), "8080".to_string()),
+                ("unknown".to_string(), "1".to_string()),
+            ]
+        );
+
+        let v = Jsonc
+            .parse_value(br#"{"unknown": "a", /* comment */}"#)
+            .expect("must success");
+        assert_eq!(
+            crate::value::get(&v, &"unknown".parse().expect("must be valid path")),
+            Some(&Value::Str("a".to_string()))
+        );
+    }
 }
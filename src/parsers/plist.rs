@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+
+use crate::Parser;
+
+/// Property list format support
+///
+/// Both XML and binary [property lists](https://developer.apple.com/documentation/foundation/propertylistserialization)
+/// are supported, the encoding is detected from the input.
+#[derive(Debug)]
+pub struct Plist;
+
+impl Parser for Plist {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        plist::from_bytes(bs).map_err(|err| anyhow!("parse plist: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>name</key>
+    <string>serfig</string>
+    <key>ports</key>
+    <array>
+        <integer>8001</integer>
+        <integer>8002</integer>
+    </array>
+</dict>
+</plist>
+"#;
+        let expected = TestStruct {
+            name: "serfig".to_string(),
+            ports: vec![8001, 8002],
+        };
+
+        let t: TestStruct = Plist.parse(content.as_bytes()).expect("must success");
+        assert_eq!(t, expected);
+
+        let mut bs = Vec::new();
+        plist::to_writer_binary(&mut bs, &expected).expect("encode binary plist");
+        let t: TestStruct = Plist.parse(&bs).expect("must success");
+        assert_eq!(t, expected);
+    }
+}
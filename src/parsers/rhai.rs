@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+use rhai::{Dynamic, Engine};
+use serde::de::DeserializeOwned;
+
+use crate::Parser;
+
+/// Rhai script support
+///
+/// The input is evaluated as a [Rhai](https://rhai.rs) script and the
+/// returned object map is deserialized. Rhai has no access to files or
+/// processes, and evaluation is aborted after too many operations so a
+/// buggy loop can't hang the loading.
+///
+/// ```rhai
+/// let ports = [];
+/// for i in 1..=2 { ports.push(8000 + i); }
+/// #{ name: "serfig", ports: ports }
+/// ```
+#[derive(Debug)]
+pub struct Rhai;
+
+/// Max operations allowed while evaluating a config script.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+impl Parser for Rhai {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let value: Dynamic = engine
+            .eval(s)
+            .map_err(|err| anyhow!("evaluate rhai: {err}"))?;
+        rhai::serde::from_dynamic(&value).map_err(|err| anyhow!("deserialize rhai value: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        verbose: bool,
+        ports: Vec<u64>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+let env = "prod";
+let ports = [];
+for i in 1..=2 {
+    ports.push(8000 + i);
+}
+#{ name: "serfig", verbose: if env == "prod" { false } else { true }, ports: ports }
+"#;
+
+        let t: TestStruct = Rhai.parse(content.as_bytes()).expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                verbose: false,
+                ports: vec![8001, 8002],
+            }
+        );
+
+        assert!(Rhai.parse::<TestStruct>(b"loop {}").is_err());
+    }
+}
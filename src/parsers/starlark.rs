@@ -0,0 +1,177 @@
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+use starlark::environment::{Globals, LibraryExtension, Module};
+use starlark::eval::Evaluator;
+use starlark::syntax::{AstModule, Dialect};
+use starlark::values::dict::DictRef;
+use starlark::values::float::StarlarkFloat;
+use starlark::values::list::ListRef;
+use starlark::values::structs::StructRef;
+use starlark::values::tuple::TupleRef;
+use starlark::values::UnpackValue;
+
+use crate::{de, Parser};
+
+/// Starlark format support
+///
+/// The input is evaluated as a [Starlark](https://github.com/bazelbuild/starlark)
+/// module and its top-level variables are deserialized as fields of a
+/// struct. Variables starting with `_` and functions are not exported.
+///
+/// ```python
+/// _base = 8000
+/// name = "serfig"
+/// ports = [_base + i for i in range(1, 3)]
+/// ```
+#[derive(Debug)]
+pub struct Starlark;
+
+impl Parser for Starlark {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        Ok(de::from_value(self.parse_value(bs)?)?)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        let s = std::str::from_utf8(bs)
+            .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
+
+        let ast = AstModule::parse("config.star", s.to_string(), &Dialect::Standard)
+            .map_err(|err| anyhow!("parse starlark: {err}"))?;
+        let globals = Globals::extended_by(&[LibraryExtension::StructType]);
+
+        Module::with_temp_heap(|module| -> Result<Value> {
+            Evaluator::new(&module)
+                .eval_module(ast, &globals)
+                .map_err(|err| anyhow!("evaluate starlark: {err}"))?;
+
+            let mut fields = indexmap::IndexMap::new();
+            for name in module.names() {
+                let name = name.as_str();
+                let v = match module.get(name) {
+                    Some(v) if !name.starts_with('_') => v,
+                    _ => continue,
+                };
+                if matches!(v.get_type(), "function" | "builtin_function") {
+                    continue;
+                }
+                let v =
+                    to_value(v).map_err(|err| anyhow!("convert starlark value `{name}`: {err}"))?;
+                fields.insert(Value::Str(name.to_string()), v);
+            }
+            Ok(Value::Map(fields))
+        })
+    }
+}
+
+/// Convert starlark value `v` into [`Value`] directly, so numbers don't
+/// depend on how serde_json represents them.
+fn to_value(v: starlark::values::Value) -> Result<Value> {
+    if v.is_none() {
+        return Ok(Value::None);
+    }
+    if let Some(b) = v.unpack_bool() {
+        return Ok(Value::Bool(b));
+    }
+    if let Some(s) = v.unpack_str() {
+        return Ok(Value::Str(s.to_string()));
+    }
+    if let Some(f) = StarlarkFloat::unpack_value_opt(v) {
+        return Ok(Value::F64(f.0));
+    }
+    if v.get_type() == "int" {
+        if let Ok(Some(i)) = i64::unpack_value(v) {
+            return Ok(Value::I64(i));
+        }
+        return match u64::unpack_value(v) {
+            Ok(Some(i)) => Ok(Value::U64(i)),
+            _ => Err(anyhow!("integer {v} is out of range")),
+        };
+    }
+    if let Some(l) = ListRef::from_value(v) {
+        return Ok(Value::Seq(l.iter().map(to_value).collect::<Result<_>>()?));
+    }
+    if let Some(t) = TupleRef::from_value(v) {
+        return Ok(Value::Seq(t.iter().map(to_value).collect::<Result<_>>()?));
+    }
+    if let Some(d) = DictRef::from_value(v) {
+        return Ok(Value::Map(
+            d.iter()
+                .map(|(k, v)| Ok((to_value(k)?, to_value(v)?)))
+                .collect::<Result<_>>()?,
+        ));
+    }
+    if let Some(st) = StructRef::from_value(v) {
+        return Ok(Value::Map(
+            st.iter()
+                .map(|(k, v)| Ok((Value::Str(k.as_str().to_string()), to_value(v)?)))
+                .collect::<Result<_>>()?,
+        ));
+    }
+    bail!("value of type {} is not supported", v.get_type())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStruct {
+        name: String,
+        ports: Vec<u64>,
+        tls: TestTls,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestTls {
+        enabled: bool,
+    }
+
+    #[test]
+    fn test_parse() {
+        let content = r#"
+_base = 8000
+
+def port(i):
+    return _base + i
+
+name = "serfig"
+ports = [port(i) for i in range(1, 3)]
+tls = struct(enabled = len(ports) > 1)
+"#;
+
+        let t: TestStruct = Starlark.parse(content.as_bytes()).expect("must success");
+        assert_eq!(
+            t,
+            TestStruct {
+                name: "serfig".to_string(),
+                ports: vec![8001, 8002],
+                tls: TestTls { enabled: true },
+            }
+        );
+
+        assert!(Starlark
+            .parse::<TestStruct>(b"name = undefined_var")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let content = r#"
+ratio = 0.5
+limits = {"conns": 10}
+proxy = None
+"#;
+
+        let v = Starlark
+            .parse_value(content.as_bytes())
+            .expect("must success");
+        let get =
+            |path: &str| crate::value::get(&v, &path.parse().expect("must be valid path")).cloned();
+        assert_eq!(get("ratio"), Some(Value::F64(0.5)));
+        assert_eq!(get("limits.conns"), Some(Value::I64(10)));
+        assert_eq!(get("proxy"), Some(Value::None));
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{anyhow, Result};
+use minijinja::Environment;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::Value;
+
+use crate::Parser;
+
+/// Render input as a [MiniJinja](https://docs.rs/minijinja) template
+/// before passing it to the inner parser.
+///
+/// Loops and conditionals in templates cover cases that
+/// [`Interpolator`][crate::Interpolator] can't, like generating a list
+/// of servers from the context.
+///
+/// Requires feature `template`.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::parsers::{Templated, Toml};
+/// use serfig::Parser;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// struct TestConfig {
+///     addrs: Vec<String>,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let tpl = r#"addrs = [{% for h in hosts %}"{{ h }}:80",{% endfor %}]"#;
+///     let mut p = Templated::new(Toml, &serde_json::json!({ "hosts": ["a", "b"] }));
+///
+///     let t: TestConfig = p.parse(tpl.as_bytes())?;
+///     assert_eq!(t.addrs, vec!["a:80", "b:80"]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Templated<P: Parser> {
+    inner: P,
+    context: minijinja::Value,
+}
+
+impl<P: Parser> Templated<P> {
+    /// Wrap given parser, templates will be rendered with `context`.
+    pub fn new<C: Serialize>(inner: P, context: &C) -> Self {
+        Self {
+            inner,
+            context: minijinja::Value::from_serialize(context),
+        }
+    }
+
+    fn render(&self, bs: &[u8]) -> Result<String> {
+        let s = std::str::from_utf8(bs).map_err(|err| anyhow!("render template: {err}"))?;
+        let mut env = Environment::new();
+        env.set_undefined_behavior(minijinja::UndefinedBehavior::Strict);
+        env.render_str(s, &self.context)
+            .map_err(|err| anyhow!("render template: {err}"))
+    }
+}
+
+impl<P: Parser> Parser for Templated<P> {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        let s = self.render(bs)?;
+        self.inner.parse(s.as_bytes())
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        let s = self.render(bs)?;
+        self.inner.parse_value(s.as_bytes())
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        self.inner.export(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestConfig {
+        debug: bool,
+        addrs: Vec<String>,
+    }
+
+    #[test]
+    fn test_templated() {
+        let tpl = r#"
+debug = {% if env == "dev" %}true{% else %}false{% endif %}
+addrs = [{% for h in hosts %}"{{ h }}:80",{% endfor %}]
+"#;
+        let ctx = serde_json::json!({ "env": "dev", "hosts": ["a", "b"] });
+        let mut p = Templated::new(Toml, &ctx);
+        let t: TestConfig = p.parse(tpl.as_bytes()).expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                debug: true,
+                addrs: vec!["a:80".to_string(), "b:80".to_string()],
+            }
+        );
+
+        // Undefined variables are errors instead of empty strings.
+        let mut p = Templated::new(Toml, &serde_json::json!({}));
+        assert!(p.parse::<TestConfig>(tpl.as_bytes()).is_err());
+    }
+}
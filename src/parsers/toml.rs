@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use serde::de::DeserializeOwned;
+use serde_bridge::Value;
 
 use crate::Parser;
 
@@ -13,4 +14,8 @@ impl Parser for Toml {
             .map_err(|err| anyhow!("input value is not valid utf-8: {err:?}"))?;
         Ok(toml::from_str(s)?)
     }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        Ok(toml::to_string(v)?.into_bytes())
+    }
 }
@@ -0,0 +1,171 @@
+use anyhow::{anyhow, bail, Result};
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+use sha2::{Digest, Sha256};
+
+use crate::Parser;
+
+/// Verify input against a detached checksum or signature before passing
+/// it to the inner parser, so tampered remote payloads are rejected
+/// instead of merged.
+///
+/// Requires feature `verify`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_file;
+/// use serfig::parsers::{Toml, Verified};
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let public_key = std::fs::read("/etc/myapp/config.pub")?;
+///     let signature = std::fs::read("/srv/config/remote.toml.sig")?;
+///     let builder = Builder::default().collect(from_file(
+///         Verified::ed25519(Toml, &public_key, &signature),
+///         "/srv/config/remote.toml",
+///     ));
+///
+///     let t: TestConfig = builder.build()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Verified<P: Parser> {
+    inner: P,
+    check: Check,
+}
+
+#[derive(Debug)]
+enum Check {
+    Sha256(String),
+    Ed25519 {
+        public_key: Vec<u8>,
+        signature: Vec<u8>,
+    },
+}
+
+impl<P: Parser> Verified<P> {
+    /// Wrap given parser, input must have the sha256 digest `digest` in
+    /// hex like the output of `sha256sum`.
+    pub fn sha256(inner: P, digest: &str) -> Self {
+        Self {
+            inner,
+            check: Check::Sha256(digest.trim().to_lowercase()),
+        }
+    }
+
+    /// Wrap given parser, input must be signed with ed25519 by the key of
+    /// `public_key`.
+    ///
+    /// Both `public_key` and `signature` are raw bytes, 32 and 64 long.
+    pub fn ed25519(inner: P, public_key: &[u8], signature: &[u8]) -> Self {
+        Self {
+            inner,
+            check: Check::Ed25519 {
+                public_key: public_key.to_vec(),
+                signature: signature.to_vec(),
+            },
+        }
+    }
+
+    fn verify(&self, bs: &[u8]) -> Result<()> {
+        match &self.check {
+            Check::Sha256(expected) => {
+                let actual = hex::encode(Sha256::digest(bs));
+                if &actual != expected {
+                    bail!("sha256 mismatch, expected {expected} but got {actual}");
+                }
+            }
+            Check::Ed25519 {
+                public_key,
+                signature,
+            } => {
+                let key = <&[u8; 32]>::try_from(public_key.as_slice())
+                    .map_err(|_| anyhow!("ed25519 public key must be 32 bytes"))?;
+                let key = VerifyingKey::from_bytes(key)
+                    .map_err(|err| anyhow!("invalid ed25519 public key: {err}"))?;
+                let signature = Signature::from_slice(signature)
+                    .map_err(|err| anyhow!("invalid ed25519 signature: {err}"))?;
+                key.verify_strict(bs, &signature)
+                    .map_err(|_| anyhow!("ed25519 signature mismatch"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: Parser> Parser for Verified<P> {
+    fn parse<T: DeserializeOwned>(&mut self, bs: &[u8]) -> Result<T> {
+        self.verify(bs)
+            .map_err(|err| anyhow!("verify input: {err}, payload may be tampered"))?;
+        self.inner.parse(bs)
+    }
+
+    fn parse_value(&mut self, bs: &[u8]) -> Result<Value> {
+        self.verify(bs)
+            .map_err(|err| anyhow!("verify input: {err}, payload may be tampered"))?;
+        self.inner.parse_value(bs)
+    }
+
+    fn export(&mut self, v: &Value) -> Result<Vec<u8>> {
+        self.inner.export(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestConfig {
+        workers: usize,
+    }
+
+    #[test]
+    fn test_verified_sha256() {
+        let input = b"workers = 8\n";
+        let digest = hex::encode(Sha256::digest(input));
+
+        let mut p = Verified::sha256(Toml, &digest);
+        let t: TestConfig = p.parse(input).expect("must verify");
+        assert_eq!(t.workers, 8);
+
+        let err = p
+            .parse::<TestConfig>(b"workers = 9\n")
+            .expect_err("must fail");
+        assert!(err.to_string().contains("sha256 mismatch"), "{err}");
+    }
+
+    #[test]
+    fn test_verified_ed25519() {
+        let input = b"workers = 8\n";
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let signature = key.sign(input).to_bytes();
+        let public_key = key.verifying_key().to_bytes();
+
+        let mut p = Verified::ed25519(Toml, &public_key, &signature);
+        let t: TestConfig = p.parse(input).expect("must verify");
+        assert_eq!(t.workers, 8);
+
+        let err = p
+            .parse::<TestConfig>(b"workers = 9\n")
+            .expect_err("must fail");
+        assert!(err.to_string().contains("signature mismatch"), "{err}");
+
+        let mut p = Verified::ed25519(Toml, &public_key[..16], &signature);
+        assert!(p.parse::<TestConfig>(input).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+
+/// KeyPath addresses a value inside config like `server.tls.cert` or
+/// `servers[2].addr`.
+///
+/// Keys are separated by `.`, and `[N]` indexes into sequences. A `*`
+/// key in patterns matches any single key or index, see
+/// [`KeyPath::matches`].
+///
+/// # Examples
+///
+/// ```
+/// use serfig::KeyPath;
+///
+/// fn main() -> anyhow::Result<()> {
+///     let path: KeyPath = "servers[2].addr".parse()?;
+///     assert_eq!(path.to_string(), "servers[2].addr");
+///     assert!(path.matches(&"servers.*.addr".parse()?));
+///
+///     assert!("servers..addr".parse::<KeyPath>().is_err());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeyPath {
+    segments: Vec<Segment>,
+}
+
+/// Segment is a single step of [`KeyPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Segment {
+    /// Field of a struct or key of a map.
+    Key(String),
+    /// Index of a sequence.
+    Index(usize),
+}
+
+impl KeyPath {
+    /// Segments of this path.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Check if this path matches `pattern`, `*` in pattern matches any
+    /// single key or index.
+    pub fn matches(&self, pattern: &KeyPath) -> bool {
+        self.segments.len() == pattern.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(&pattern.segments)
+                .all(|(s, p)| matches!(p, Segment::Key(k) if k == "*") || s == p)
+    }
+}
+
+impl FromStr for KeyPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |reason: &str| anyhow!("invalid key path `{s}`: {reason}");
+        if s.is_empty() {
+            return Err(invalid("path is empty"));
+        }
+
+        let mut segments = Vec::new();
+        for (idx, part) in s.split('.').enumerate() {
+            let (key, mut indexes) = part.split_at(part.find('[').unwrap_or(part.len()));
+            if key.contains(']') {
+                return Err(invalid("unexpected `]`"));
+            }
+            match (key.is_empty(), idx, indexes.is_empty()) {
+                (false, _, _) => segments.push(Segment::Key(key.to_string())),
+                // Paths can start with an index like `[0].addr`.
+                (true, 0, false) => {}
+                _ => return Err(invalid("key is empty")),
+            }
+
+            while !indexes.is_empty() {
+                let end = indexes
+                    .find(']')
+                    .ok_or_else(|| invalid("`[` is not closed"))?;
+                let index = indexes[1..end]
+                    .parse()
+                    .map_err(|_| invalid("index must be a non-negative integer"))?;
+                segments.push(Segment::Index(index));
+
+                indexes = &indexes[end + 1..];
+                if !indexes.is_empty() && !indexes.starts_with('[') {
+                    return Err(invalid("unexpected characters after index"));
+                }
+            }
+        }
+        Ok(Self { segments })
+    }
+}
+
+impl TryFrom<&str> for KeyPath {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for KeyPath {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl Display for KeyPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (idx, s) in self.segments.iter().enumerate() {
+            match s {
+                Segment::Key(k) if idx == 0 => write!(f, "{k}")?,
+                Segment::Key(k) => write!(f, ".{k}")?,
+                Segment::Index(i) => write!(f, "[{i}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let cases = vec![
+            ("a", vec![Segment::Key("a".to_string())]),
+            (
+                "a.b[2].c",
+                vec![
+                    Segment::Key("a".to_string()),
+                    Segment::Key("b".to_string()),
+                    Segment::Index(2),
+                    Segment::Key("c".to_string()),
+                ],
+            ),
+            ("[0][1]", vec![Segment::Index(0), Segment::Index(1)]),
+        ];
+        for (input, expected) in cases {
+            let path: KeyPath = input.parse().expect("must success");
+            assert_eq!(path.segments(), expected, "{input}");
+            assert_eq!(path.to_string(), input);
+        }
+
+        for input in [
+            "", "a..b", "a.", ".a", "a[", "a[x]", "a[-1]", "a[0]b", "a]", "a.[0]",
+        ] {
+            assert!(input.parse::<KeyPath>().is_err(), "{input} must fail");
+        }
+    }
+}
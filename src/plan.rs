@@ -0,0 +1,79 @@
+use std::fmt::{self, Display, Formatter};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::collectors::{SourceDescriptor, Trust};
+use crate::constraint::Annotation;
+use crate::MergeConfig;
+
+/// Plan of a builder returned by [`Builder::describe`][crate::Builder::describe].
+///
+/// Nothing is collected while describing, so plans are cheap to log at
+/// startup and compare across environments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildPlan {
+    /// Sources in the order they will be merged.
+    pub sources: Vec<PlannedSource>,
+    /// How layers will be merged.
+    pub merge: MergeConfig,
+    /// Paths whose values are converted by [`Builder::coerce`][crate::Builder::coerce].
+    pub coercions: Vec<String>,
+    /// Paths resolved by [`Builder::resolve_paths`][crate::Builder::resolve_paths].
+    pub resolve_paths: Vec<String>,
+    /// Paths declared by [`Builder::explicit`][crate::Builder::explicit].
+    pub explicit_paths: Vec<String>,
+    /// Paths declared by [`Builder::require_trust`][crate::Builder::require_trust].
+    pub trust_policies: Vec<TrustPolicy>,
+    /// Rules declared by [`Builder::range`][crate::Builder::range] and
+    /// [`Builder::one_of`][crate::Builder::one_of].
+    pub rules: Vec<Annotation>,
+    /// Count of all constraints including rules.
+    pub constraints: usize,
+    /// Whether values will be interpolated.
+    pub interpolate: bool,
+    /// Whether unknown and deprecated keys will be audited.
+    pub audit_keys: bool,
+}
+
+/// A source in [`BuildPlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedSource {
+    /// Source of the layer.
+    pub source: SourceDescriptor,
+    /// Trust of the source.
+    pub trust: Trust,
+}
+
+/// A trust policy in [`BuildPlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrustPolicy {
+    /// Dot separated path like `exec_path`.
+    pub path: String,
+    /// The lowest trust allowed to set the path.
+    pub trust: Trust,
+}
+
+impl BuildPlan {
+    /// Render plan as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl Display for BuildPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (idx, s) in self.sources.iter().enumerate() {
+            writeln!(f, "{}. {} ({})", idx + 1, s.source, s.trust)?;
+        }
+        writeln!(f, "merge: {}", self.merge)?;
+        for p in &self.trust_policies {
+            writeln!(f, "require {} trust: {}", p.trust, p.path)?;
+        }
+        for a in &self.rules {
+            writeln!(f, "rule {}: {}", a.path, a.rule)?;
+        }
+        writeln!(f, "constraints: {}", self.constraints)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! Probe the shape of a type via its `Deserialize` implementation.
+//!
+//! Probe will feed placeholder values into the target type so that every
+//! field can be visited without real input.
+
+use std::fmt::Display;
+
+use serde::de::value::{Error, StrDeserializer};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Max depth to probe, used to stop on recursive types.
+const MAX_DEPTH: usize = 32;
+
+/// Enum field with dot separated path and its variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EnumField {
+    pub path: String,
+    pub name: &'static str,
+    pub variants: &'static [&'static str],
+}
+
+/// Collect all enum fields inside `V`.
+pub(crate) fn enum_fields<V: DeserializeOwned>() -> Vec<EnumField> {
+    let mut fields = Vec::new();
+    // Probe could fail on types that can't accept placeholder values,
+    // fields visited before the failure are still useful.
+    let _ = V::deserialize(Probe {
+        path: String::new(),
+        depth: 0,
+        fields: &mut fields,
+    });
+    fields
+}
+
+struct Probe<'a> {
+    path: String,
+    depth: usize,
+    fields: &'a mut Vec<EnumField>,
+}
+
+impl<'a> Probe<'a> {
+    fn child(&mut self, key: impl Display) -> Result<Probe<'_>, Error> {
+        if self.depth >= MAX_DEPTH {
+            return Err(de::Error::custom("probe reached max depth"));
+        }
+
+        let path = if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        Ok(Probe {
+            path,
+            depth: self.depth + 1,
+            fields: self.fields,
+        })
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Probe<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_unit()
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_bool(false)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_i64(0)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_u64(0)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_char(' ')
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_str("")
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_bytes(&[])
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        vis.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_seq(de::value::SeqDeserializer::<_, Error>::new(
+            std::iter::empty::<()>(),
+        ))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_map(de::value::MapDeserializer::<_, Error>::new(
+            std::iter::empty::<((), ())>(),
+        ))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        vis.visit_map(StructProbe {
+            probe: self,
+            fields: fields.iter(),
+            current: "",
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        self.fields.push(EnumField {
+            path: self.path.clone(),
+            name,
+            variants,
+        });
+        vis.visit_enum(EnumProbe {
+            probe: self,
+            variants,
+        })
+    }
+
+    forward_to_deserialize_any! {
+        unit_struct tuple tuple_struct identifier ignored_any
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_i128(0)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_u128(0)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_i8(0)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_i16(0)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_i32(0)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_u8(0)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_u16(0)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_u32(0)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_f32(0.0)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_string(String::new())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, Error> {
+        vis.visit_byte_buf(Vec::new())
+    }
+}
+
+struct StructProbe<'a> {
+    probe: Probe<'a>,
+    fields: std::slice::Iter<'static, &'static str>,
+    current: &'static str,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for StructProbe<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.fields.next() {
+            None => Ok(None),
+            Some(field) => {
+                self.current = field;
+                let key: StrDeserializer<Error> = field.into_deserializer();
+                seed.deserialize(key).map(Some)
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(self.probe.child(self.current)?)
+    }
+}
+
+struct EnumProbe<'a> {
+    probe: Probe<'a>,
+    variants: &'static [&'static str],
+}
+
+impl<'de, 'a> de::EnumAccess<'de> for EnumProbe<'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = self
+            .variants
+            .first()
+            .ok_or_else(|| de::Error::custom("enum has no variants"))?;
+        let key: StrDeserializer<Error> = variant.into_deserializer();
+        Ok((seed.deserialize(key)?, self))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for EnumProbe<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.probe)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, vis: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self.probe, vis)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        vis: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_struct(self.probe, "", fields, vis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    #[allow(dead_code)]
+    enum TestLevel {
+        Debug,
+        Info,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct TestLog {
+        level: TestLevel,
+        file: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct TestStruct {
+        name: String,
+        log: TestLog,
+        levels: Vec<TestLevel>,
+    }
+
+    #[test]
+    fn test_enum_fields() {
+        assert_eq!(
+            enum_fields::<TestStruct>(),
+            vec![EnumField {
+                path: "log.level".to_string(),
+                name: "TestLevel",
+                variants: &["debug", "info"],
+            }]
+        )
+    }
+}
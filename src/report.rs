@@ -0,0 +1,76 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::collectors::{Skipped, SourceDescriptor, SourceVersion};
+
+/// Report of a build returned by [`Builder::build_with_report`][crate::Builder::build_with_report].
+///
+/// The report can be rendered as JSON via [`BuildReport::to_json`] to be
+/// uploaded by CI or shown in an admin UI.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BuildReport {
+    /// Input entries skipped by collectors.
+    pub skipped: Vec<Skipped>,
+    /// Versions of sources recorded before they were collected.
+    pub sources: Vec<SourceVersion>,
+    /// Keys that don't exist in the config type, only recorded with
+    /// [`Builder::audit_keys`][crate::Builder::audit_keys].
+    pub unknown_keys: Vec<ReportedKey>,
+    /// Keys only accepted via `#[serde(alias)]`, only recorded with
+    /// [`Builder::audit_keys`][crate::Builder::audit_keys].
+    pub deprecated_keys: Vec<ReportedKey>,
+    /// Merge statistics of every collected layer in order.
+    pub layers: Vec<LayerStats>,
+}
+
+/// LayerStats counts how fields of a layer are merged.
+///
+/// A layer whose fields are all skipped as default usually means the
+/// source is misnamed or doesn't set what it was expected to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayerStats {
+    /// Source of the layer.
+    pub source: SourceDescriptor,
+    /// Number of fields that differ from default and have been merged.
+    pub applied: usize,
+    /// Number of fields that equal to default and have been skipped.
+    pub skipped_as_default: usize,
+}
+
+impl LayerStats {
+    /// Returns `true` if the layer has fields but all of them are
+    /// skipped as default.
+    pub fn is_all_default(&self) -> bool {
+        self.applied == 0 && self.skipped_as_default > 0
+    }
+}
+
+/// ReportedKey is a key used by a source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportedKey {
+    /// Source of the key.
+    pub source: SourceDescriptor,
+    /// Dot separated path of the key like `tls.cert`.
+    pub key: String,
+}
+
+impl BuildReport {
+    /// Returns `true` if nothing has been skipped and no unknown or
+    /// deprecated keys are used.
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.unknown_keys.is_empty() && self.deprecated_keys.is_empty()
+    }
+
+    /// Check if any source has changed since the build without reloading.
+    ///
+    /// Useful to warn that config changed on disk and a restart is
+    /// required to apply it.
+    pub fn sources_changed(&self) -> bool {
+        self.sources.iter().any(|s| s.changed())
+    }
+
+    /// Render report as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
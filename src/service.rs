@@ -0,0 +1,829 @@
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, bail, Result};
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_bridge::{FromValue, Value};
+
+use crate::collectors::{IntoCollector, SourceVersion};
+use crate::history::History;
+use crate::report::BuildReport;
+use crate::value::{merge, to_value, MergeConfig};
+use crate::Template;
+
+type Validator<V> = (Arc<dyn Fn(&V) -> bool + Send + Sync>, String);
+type Observer<V> = Arc<dyn Fn(&V) + Send + Sync>;
+
+/// Overlays keeps the loaded value and the temporary layers on top of it.
+struct Overlays<V> {
+    base: Arc<V>,
+    layers: Vec<(u64, Value)>,
+    next_id: u64,
+}
+
+/// ConfigHealth describes the state of config loading, suitable for
+/// embedding into health or readiness endpoints.
+///
+/// Returned by [`ConfigHandle::health`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigHealth {
+    /// When the last build was attempted, `None` if never.
+    pub last_attempt: Option<SystemTime>,
+    /// When the last build succeeded, `None` if never.
+    pub last_success: Option<SystemTime>,
+    /// Error of the last build, `None` if it succeeded.
+    pub last_error: Option<String>,
+    /// Versions of sources the current value is loaded from.
+    pub sources: Vec<SourceVersion>,
+    /// Whether any source has changed since the current value is loaded.
+    pub stale: bool,
+}
+
+impl ConfigHealth {
+    /// Returns `true` if the last build succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.last_success.is_some() && self.last_error.is_none()
+    }
+
+    /// Render health as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Status of builds recorded by the handle.
+#[derive(Default)]
+struct Status {
+    last_attempt: Option<SystemTime>,
+    last_success: Option<SystemTime>,
+    last_error: Option<String>,
+}
+
+/// ConfigHandle gives access to the latest config value of a [`ConfigService`].
+///
+/// Handles are cheap to clone and can be shared between threads.
+pub struct ConfigHandle<V> {
+    value: Arc<RwLock<Arc<V>>>,
+    overlays: Arc<Mutex<Overlays<V>>>,
+    history: Arc<Mutex<History>>,
+    sources: Arc<Mutex<Vec<SourceVersion>>>,
+    status: Arc<Mutex<Status>>,
+}
+
+impl<V> Clone for ConfigHandle<V> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            overlays: self.overlays.clone(),
+            history: self.history.clone(),
+            sources: self.sources.clone(),
+            status: self.status.clone(),
+        }
+    }
+}
+
+impl<V> ConfigHandle<V> {
+    fn new(v: V) -> Self {
+        let v = Arc::new(v);
+        Self {
+            value: Arc::new(RwLock::new(v.clone())),
+            overlays: Arc::new(Mutex::new(Overlays {
+                base: v,
+                layers: Vec::new(),
+                next_id: 0,
+            })),
+            history: Arc::new(Mutex::new(History::default())),
+            sources: Arc::new(Mutex::new(Vec::new())),
+            status: Arc::new(Mutex::new(Status::default())),
+        }
+    }
+
+    /// Get the current config value.
+    pub fn get(&self) -> Arc<V> {
+        self.value
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the loaded value without overlays.
+    fn base(&self) -> Arc<V> {
+        self.overlays
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .base
+            .clone()
+    }
+
+    /// Number of retained values before the current one.
+    pub fn history_len(&self) -> usize {
+        self.history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    fn set_sources(&self, sources: Vec<SourceVersion>) {
+        *self.sources.lock().unwrap_or_else(PoisonError::into_inner) = sources;
+    }
+
+    /// Record the result of a build attempt.
+    fn record(&self, result: Result<(), &anyhow::Error>) {
+        let mut status = self.status.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = SystemTime::now();
+        status.last_attempt = Some(now);
+        match result {
+            Ok(()) => {
+                status.last_success = Some(now);
+                status.last_error = None;
+            }
+            Err(err) => status.last_error = Some(format!("{err:#}")),
+        }
+    }
+
+    /// Get the state of config loading like the last build time and
+    /// error, see [`ConfigHealth`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::from_file;
+    /// use serfig::parsers::Toml;
+    /// use serfig::{ConfigService, Template};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     workers: usize,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let template = Template::<TestConfig>::new().collect(|| from_file(Toml, "config.toml"));
+    ///     let mut service = ConfigService::new(template);
+    ///     service.start()?;
+    ///
+    ///     // Served by the health endpoint of the application.
+    ///     let health = service.handle().health();
+    ///     println!("healthy: {}, {}", health.is_healthy(), health.to_json()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn health(&self) -> ConfigHealth {
+        let sources = self
+            .sources
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let status = self.status.lock().unwrap_or_else(PoisonError::into_inner);
+        ConfigHealth {
+            last_attempt: status.last_attempt,
+            last_success: status.last_success,
+            last_error: status.last_error.clone(),
+            stale: sources.iter().any(|s| s.changed()),
+            sources,
+        }
+    }
+
+    /// Check if any source of the current value has changed since it was
+    /// loaded, without reloading.
+    ///
+    /// Useful for services without poller to warn that config changed on
+    /// disk and a restart is required to apply it.
+    pub fn is_stale(&self) -> bool {
+        self.sources
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .any(|s| s.changed())
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> ConfigHandle<V> {
+    /// Get the value stored `n` versions before the current one.
+    ///
+    /// Only changes are retained in history, the snapshot is rebuilt on
+    /// demand. Returns `None` if the version is not retained.
+    pub fn snapshot(&self, n: usize) -> Result<Option<V>> {
+        let v = self
+            .history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(n);
+        Ok(v.map(V::from_value).transpose()?)
+    }
+}
+
+impl<V: DeserializeOwned + Serialize + Default> ConfigHandle<V> {
+    /// Push a temporary layer collected from `c` on top of all other
+    /// layers until the returned guard is dropped.
+    ///
+    /// Useful for session overrides in REPLs or admin consoles. Overlays
+    /// are kept across reloads, later overlays take precedence over
+    /// earlier ones. Observers are not notified on push or pop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// use serde::{Deserialize, Serialize};
+    /// use serfig::collectors::{from_file, from_map};
+    /// use serfig::parsers::Toml;
+    /// use serfig::{ConfigService, Template};
+    ///
+    /// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    /// #[serde(default)]
+    /// struct TestConfig {
+    ///     workers: usize,
+    /// }
+    ///
+    /// fn main() -> anyhow::Result<()> {
+    ///     let template = Template::<TestConfig>::new().collect(|| from_file(Toml, "config.toml"));
+    ///     let mut service = ConfigService::new(template);
+    ///     service.start()?;
+    ///     let handle = service.handle();
+    ///
+    ///     let overrides = HashMap::from([("workers".to_string(), "1".to_string())]);
+    ///     let guard = handle.push_overlay(from_map(overrides))?;
+    ///     assert_eq!(handle.get().workers, 1);
+    ///
+    ///     // Reverted to the loaded value.
+    ///     drop(guard);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn push_overlay(&self, c: impl IntoCollector<V>) -> Result<OverlayGuard<V>> {
+        let layer = c
+            .into_collector()
+            .collect()
+            .map_err(|err| anyhow!("collect overlay: {err}"))?;
+
+        let mut overlays = self.overlays.lock().unwrap_or_else(PoisonError::into_inner);
+        let id = overlays.next_id;
+        overlays.next_id += 1;
+        overlays.layers.push((id, layer));
+        if let Err(err) = self.apply(&overlays) {
+            overlays.layers.pop();
+            return Err(anyhow!("apply overlay: {err}"));
+        }
+
+        Ok(OverlayGuard {
+            handle: self.clone(),
+            id,
+        })
+    }
+
+    /// Number of active overlays.
+    pub fn overlay_len(&self) -> usize {
+        self.overlays
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .layers
+            .len()
+    }
+
+    fn pop_overlay(&self, id: u64) {
+        let mut overlays = self.overlays.lock().unwrap_or_else(PoisonError::into_inner);
+        overlays.layers.retain(|(i, _)| *i != id);
+        if let Err(err) = self.apply(&overlays) {
+            warn!("remove config overlay failed, keep the current value: {err}");
+        }
+    }
+
+    /// Recompute the current value by merging overlays into the base.
+    fn apply(&self, overlays: &Overlays<V>) -> Result<()> {
+        let v = if overlays.layers.is_empty() {
+            overlays.base.clone()
+        } else {
+            let cfg = MergeConfig::default();
+            let default = to_value(&V::default())?;
+            let mut merged = to_value(&*overlays.base)?;
+            for (_, layer) in &overlays.layers {
+                merge(&cfg, &default, &mut merged, layer.clone());
+            }
+            Arc::new(V::from_value(merged)?)
+        };
+        *self.value.write().unwrap_or_else(PoisonError::into_inner) = v;
+        Ok(())
+    }
+
+    fn store(&self, v: V) {
+        match to_value(&v) {
+            Ok(value) => self
+                .history
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(value),
+            Err(err) => warn!("record config history failed: {err}"),
+        }
+
+        let mut overlays = self.overlays.lock().unwrap_or_else(PoisonError::into_inner);
+        overlays.base = Arc::new(v);
+        if let Err(err) = self.apply(&overlays) {
+            warn!("apply config overlays failed, ignored: {err}");
+            *self.value.write().unwrap_or_else(PoisonError::into_inner) = overlays.base.clone();
+        }
+    }
+}
+
+/// OverlayGuard removes its overlay from the [`ConfigHandle`] when dropped.
+///
+/// Created by [`ConfigHandle::push_overlay`].
+#[must_use = "the overlay is removed as soon as the guard is dropped"]
+pub struct OverlayGuard<V: DeserializeOwned + Serialize + Default> {
+    handle: ConfigHandle<V>,
+    id: u64,
+}
+
+impl<V: DeserializeOwned + Serialize + Default> Drop for OverlayGuard<V> {
+    fn drop(&mut self) {
+        self.handle.pop_overlay(self.id)
+    }
+}
+
+struct Inner<V: DeserializeOwned + Serialize> {
+    template: Arc<Template<V>>,
+    validators: Vec<Validator<V>>,
+    observers: Vec<Observer<V>>,
+}
+
+impl<V: DeserializeOwned + Serialize> Clone for Inner<V> {
+    fn clone(&self) -> Self {
+        Self {
+            template: self.template.clone(),
+            validators: self.validators.clone(),
+            observers: self.observers.clone(),
+        }
+    }
+}
+
+impl<V> Inner<V>
+where
+    V: DeserializeOwned + Serialize + Default + 'static,
+{
+    fn load(&self) -> Result<(V, BuildReport)> {
+        self.validators
+            .iter()
+            .fold(self.template.builder(), |b, (f, msg)| {
+                let f = f.clone();
+                b.constraint(move |v| f(v), msg)
+            })
+            .build_with_report(V::default())
+    }
+
+    fn notify(&self, v: &V) {
+        for f in &self.observers {
+            f(v)
+        }
+    }
+
+    /// Reload config and returns `true` if the value has been changed.
+    fn reload(&self, handle: &ConfigHandle<V>) -> Result<bool> {
+        let loaded = self.load();
+        handle.record(loaded.as_ref().map(|_| ()));
+        let (v, report) = loaded?;
+        handle.set_sources(report.sources);
+        if to_value(&v)? == to_value(&*handle.base())? {
+            return Ok(false);
+        }
+        handle.store(v);
+        self.notify(&handle.get());
+        Ok(true)
+    }
+}
+
+/// ConfigService composes serfig into the application lifecycle.
+///
+/// It owns the [`Template`] to build from, the [`ConfigHandle`] to read
+/// the latest value, an optional poller to reload periodically, the
+/// validators that every value must pass and the observers to be notified
+/// on changes.
+///
+/// Invalid values during reload will be logged and ignored, the handle
+/// keeps the last valid value.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_file};
+/// use serfig::parsers::Toml;
+/// use serfig::{ConfigService, Template};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     workers: usize,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let template = Template::new()
+///         .collect(|| from_file(Toml, "config.toml"))
+///         .collect(from_env);
+///     let mut service = ConfigService::new(template)
+///         .validate(|c: &TestConfig| c.workers > 0, "workers must be positive")
+///         .on_change(|c| println!("config changed: {c:?}"))
+///         .poll_interval(Duration::from_secs(30));
+///
+///     service.start()?;
+///     let handle = service.handle();
+///     println!("workers: {}", handle.get().workers);
+///     service.stop();
+///     Ok(())
+/// }
+/// ```
+pub struct ConfigService<V: DeserializeOwned + Serialize> {
+    inner: Inner<V>,
+    handle: ConfigHandle<V>,
+    interval: Option<Duration>,
+    poller: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+impl<V> ConfigService<V>
+where
+    V: DeserializeOwned + Serialize + Default + Send + Sync + 'static,
+{
+    /// Create a new service that builds values from `template`.
+    pub fn new(template: Template<V>) -> Self {
+        Self {
+            inner: Inner {
+                template: Arc::new(template),
+                validators: Vec::new(),
+                observers: Vec::new(),
+            },
+            handle: ConfigHandle::new(V::default()),
+            interval: None,
+            poller: None,
+        }
+    }
+
+    /// Add a validator that every value must pass.
+    pub fn validate(mut self, f: impl Fn(&V) -> bool + Send + Sync + 'static, msg: &str) -> Self {
+        self.inner.validators.push((Arc::new(f), msg.to_string()));
+        self
+    }
+
+    /// Add an observer that will be called with the new value after it
+    /// has been loaded or changed.
+    pub fn on_change(mut self, f: impl Fn(&V) + Send + Sync + 'static) -> Self {
+        self.inner.observers.push(Arc::new(f));
+        self
+    }
+
+    /// Reload config every `interval` after started.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Retain up to `n` previous values for [`ConfigHandle::snapshot`]
+    /// and [`ConfigService::rollback`].
+    pub fn keep_history(self, n: usize) -> Self {
+        *self
+            .handle
+            .history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = History::new(n);
+        self
+    }
+
+    /// Get the handle to read the latest value.
+    ///
+    /// Handle returns `V::default()` before the service started.
+    pub fn handle(&self) -> ConfigHandle<V> {
+        self.handle.clone()
+    }
+
+    /// Load the initial value and start the poller if configured.
+    ///
+    /// Returns error if the initial value can't be loaded or is invalid.
+    pub fn start(&mut self) -> Result<()> {
+        if self.poller.is_some() {
+            bail!("config service has already been started");
+        }
+
+        let loaded = self.inner.load();
+        self.handle.record(loaded.as_ref().map(|_| ()));
+        let (v, report) = loaded.map_err(|err| anyhow!("load config: {err}"))?;
+        self.handle.set_sources(report.sources);
+        self.handle.store(v);
+        self.inner.notify(&self.handle.get());
+
+        if let Some(interval) = self.interval {
+            let (tx, rx) = mpsc::channel();
+            let (handle, poller) = (self.handle.clone(), self.inner.clone());
+            let join = thread::spawn(move || loop {
+                match rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => match poller.reload(&handle) {
+                        Ok(changed) => debug!("config reloaded, changed: {changed}"),
+                        Err(err) => warn!("reload config failed, keep the last value: {err}"),
+                    },
+                    _ => return,
+                }
+            });
+            self.poller = Some((tx, join));
+        }
+        Ok(())
+    }
+
+    /// Restore the value stored `n` versions before the current one.
+    ///
+    /// The restored value is stored as a new version and observers will
+    /// be notified. It will be overwritten by the next reload that
+    /// changes the value.
+    pub fn rollback(&self, n: usize) -> Result<()> {
+        let v = self
+            .handle
+            .snapshot(n)?
+            .ok_or_else(|| anyhow!("config version {n} before current is not retained"))?;
+        self.handle.store(v);
+        self.inner.notify(&self.handle.get());
+        Ok(())
+    }
+
+    /// Stop the poller and wait for it to exit.
+    ///
+    /// The handle is still valid after stopped, and the service can be
+    /// started again.
+    pub fn stop(&mut self) {
+        if let Some((tx, join)) = self.poller.take() {
+            drop(tx);
+            if join.join().is_err() {
+                warn!("config poller panicked");
+            }
+        }
+    }
+}
+
+impl<V: DeserializeOwned + Serialize> Drop for ConfigService<V> {
+    fn drop(&mut self) {
+        if let Some((tx, join)) = self.poller.take() {
+            drop(tx);
+            let _ = join.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::collectors::{from_file, from_map};
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        workers: usize,
+    }
+
+    fn wait_for(f: impl Fn() -> bool) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            if f() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    }
+
+    #[test]
+    fn test_service() -> Result<()> {
+        let _ = env_logger::try_init();
+
+        let path = std::env::temp_dir().join("serfig-service.toml");
+        fs::write(&path, "workers = 4")?;
+        let path_str = path.to_str().unwrap().to_string();
+
+        let changes = Arc::new(AtomicUsize::new(0));
+        let counter = changes.clone();
+        let mut service =
+            ConfigService::new(Template::new().collect(move || from_file(Toml, &path_str)))
+                .validate(|c: &TestConfig| c.workers > 0, "workers must be positive")
+                .on_change(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                })
+                .poll_interval(Duration::from_millis(10));
+
+        let handle = service.handle();
+        assert_eq!(handle.get().workers, 0);
+
+        service.start()?;
+        assert_eq!(handle.get().workers, 4);
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+        assert!(service.start().is_err());
+
+        fs::write(&path, "workers = 8")?;
+        assert!(wait_for(|| handle.get().workers == 8));
+        assert!(wait_for(|| changes.load(Ordering::SeqCst) == 2));
+
+        // Invalid value will be ignored.
+        fs::write(&path, "workers = 0")?;
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(handle.get().workers, 8);
+
+        service.stop();
+        fs::write(&path, "workers = 16")?;
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(handle.get().workers, 8);
+
+        service.start()?;
+        assert_eq!(handle.get().workers, 16);
+        service.stop();
+        Ok(())
+    }
+
+    #[test]
+    fn test_service_history() -> Result<()> {
+        let path = std::env::temp_dir().join("serfig-service-history.toml");
+        fs::write(&path, "workers = 1")?;
+        let path_str = path.to_str().unwrap().to_string();
+
+        let mut service = ConfigService::new(
+            Template::<TestConfig>::new().collect(move || from_file(Toml, &path_str)),
+        )
+        .keep_history(1);
+        let handle = service.handle();
+
+        service.start()?;
+        fs::write(&path, "workers = 2")?;
+        service.stop();
+        service.start()?;
+        assert_eq!(handle.get().workers, 2);
+        assert_eq!(handle.history_len(), 1);
+        assert_eq!(handle.snapshot(1)?, Some(TestConfig { workers: 1 }));
+        assert!(service.rollback(2).is_err());
+
+        service.rollback(1)?;
+        assert_eq!(handle.get().workers, 1);
+        assert_eq!(handle.snapshot(1)?, Some(TestConfig { workers: 2 }));
+        service.stop();
+        Ok(())
+    }
+
+    #[test]
+    fn test_service_overlay() -> Result<()> {
+        let path = std::env::temp_dir().join("serfig-service-overlay.toml");
+        fs::write(&path, "workers = 1")?;
+        let path_str = path.to_str().unwrap().to_string();
+
+        let mut service = ConfigService::new(
+            Template::<TestConfig>::new().collect(move || from_file(Toml, &path_str)),
+        );
+        let handle = service.handle();
+        service.start()?;
+
+        let overlay = |workers: &str| from_map(HashMap::from([("workers".into(), workers.into())]));
+        let outer = handle.push_overlay(overlay("8"))?;
+        assert_eq!(handle.get().workers, 8);
+        let inner = handle.push_overlay(overlay("16"))?;
+        assert_eq!(handle.get().workers, 16);
+        assert_eq!(handle.overlay_len(), 2);
+        assert!(handle.push_overlay(overlay("x")).is_err());
+
+        // Overlays are kept across reloads.
+        fs::write(&path, "workers = 2")?;
+        service.stop();
+        service.start()?;
+        assert_eq!(handle.get().workers, 16);
+
+        drop(outer);
+        assert_eq!(handle.get().workers, 16);
+        drop(inner);
+        assert_eq!(handle.get().workers, 2);
+        assert_eq!(handle.overlay_len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_service_health() -> Result<()> {
+        let path = std::env::temp_dir().join("serfig-service-health.toml");
+        fs::write(&path, "workers = 1")?;
+        let path_str = path.to_str().unwrap().to_string();
+
+        let mut service = ConfigService::new(
+            Template::<TestConfig>::new().collect(move || from_file(Toml, &path_str)),
+        )
+        .validate(|c| c.workers > 0, "workers must be positive");
+        let handle = service.handle();
+        assert_eq!(handle.health(), ConfigHealth::default());
+        assert!(!handle.health().is_healthy());
+
+        service.start()?;
+        let health = handle.health();
+        assert!(health.is_healthy());
+        assert_eq!(health.last_attempt, health.last_success);
+        assert_eq!(health.sources.len(), 1);
+        assert!(!health.stale);
+        service.stop();
+
+        fs::write(&path, "workers = 0")?;
+        assert!(service.start().is_err());
+        let failed = handle.health();
+        assert!(!failed.is_healthy());
+        assert!(failed.last_attempt > health.last_attempt);
+        assert_eq!(failed.last_success, health.last_success);
+        assert!(
+            failed
+                .last_error
+                .as_deref()
+                .is_some_and(|e| e.contains("workers must be positive")),
+            "{failed:?}"
+        );
+        assert!(failed.to_json()?.contains("last_error"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_service_stale() -> Result<()> {
+        let path = std::env::temp_dir().join("serfig-service-stale.toml");
+        fs::write(&path, "workers = 1")?;
+        let path_str = path.to_str().unwrap().to_string();
+
+        let mut service = ConfigService::new(
+            Template::<TestConfig>::new().collect(move || from_file(Toml, &path_str)),
+        );
+        let handle = service.handle();
+        service.start()?;
+        assert!(!handle.is_stale());
+
+        fs::write(&path, "workers = 2")?;
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(std::time::SystemTime::now() + Duration::from_secs(60))?;
+        assert!(handle.is_stale());
+        assert_eq!(handle.get().workers, 1);
+        service.stop();
+        Ok(())
+    }
+
+    /// Simulate the atomic update of Kubernetes ConfigMap volumes, where
+    /// `app.toml -> ..data/app.toml` and `..data` is a symlink swapped to
+    /// a new timestamped directory.
+    #[cfg(unix)]
+    #[test]
+    fn test_service_symlink_swap() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let dir = std::env::temp_dir().join("serfig-service-symlink");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("..v1"))?;
+        fs::create_dir_all(dir.join("..v2"))?;
+        fs::write(dir.join("..v1/app.toml"), "workers = 1")?;
+        fs::write(dir.join("..v2/app.toml"), "workers = 2")?;
+        // Same modified time for both versions, only the target changes.
+        let mtime = fs::metadata(dir.join("..v1/app.toml"))?.modified()?;
+        fs::File::options()
+            .write(true)
+            .open(dir.join("..v2/app.toml"))?
+            .set_modified(mtime)?;
+        symlink("..v1", dir.join("..data"))?;
+        symlink("..data/app.toml", dir.join("app.toml"))?;
+
+        let path_str = dir.join("app.toml").to_str().unwrap().to_string();
+        let mut service = ConfigService::new(
+            Template::<TestConfig>::new().collect(move || from_file(Toml, &path_str)),
+        )
+        .poll_interval(Duration::from_millis(10));
+        let handle = service.handle();
+        service.start()?;
+        assert_eq!(handle.get().workers, 1);
+        assert!(!handle.is_stale());
+
+        // Stop polling to check staleness before reloaded.
+        service.stop();
+        symlink("..v2", dir.join("..data_tmp"))?;
+        fs::rename(dir.join("..data_tmp"), dir.join("..data"))?;
+        assert!(handle.is_stale());
+
+        service.start()?;
+        assert_eq!(handle.get().workers, 2);
+        assert!(!handle.is_stale());
+
+        // Rotated file with the same modified time.
+        fs::write(dir.join("..v2/app.toml.new"), "workers = 3")?;
+        fs::File::options()
+            .write(true)
+            .open(dir.join("..v2/app.toml.new"))?
+            .set_modified(mtime)?;
+        fs::rename(dir.join("..v2/app.toml.new"), dir.join("..v2/app.toml"))?;
+        assert!(wait_for(|| handle.get().workers == 3));
+        service.stop();
+        Ok(())
+    }
+}
@@ -0,0 +1,455 @@
+//! Match struct fields in snake_case for env-style sources.
+//!
+//! `serde-env` lowercases keys and looks up fields by their serialized
+//! names, so fields renamed by `#[serde(rename_all = "camelCase")]` like
+//! `maxWorkers` can never match `MAX_WORKERS` and are dropped silently.
+//! This module wraps the target type to ask for `max_workers` instead and
+//! maps the keys back to the serialized names.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use serde::de::{
+    self, Deserialize, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+
+type Fields = &'static [&'static str];
+
+/// Deserialize `T` from env-style pairs via `serde_env::from_iter`.
+pub(crate) fn from_iter<I, S, T>(pairs: I) -> Result<T, serde_env::Error>
+where
+    I: IntoIterator<Item = (S, S)>,
+    S: AsRef<str>,
+    T: DeserializeOwned,
+{
+    serde_env::from_iter::<_, _, SnakeCase<T>>(pairs).map(|v| v.0)
+}
+
+struct SnakeCase<T>(T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SnakeCase<T> {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        T::deserialize(Wrap(d)).map(SnakeCase)
+    }
+}
+
+/// Convert `maxWorkers`, `MaxWorkers` or `max-workers` into `max_workers`.
+pub(crate) fn to_snake_case(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len() + 4);
+    for (idx, &c) in chars.iter().enumerate() {
+        if c == '-' {
+            out.push('_');
+            continue;
+        }
+        if c.is_uppercase() && idx > 0 {
+            let prev = chars.get(idx - 1).copied().unwrap_or('_');
+            let next_lower = chars.get(idx + 1).is_some_and(|c| c.is_lowercase());
+            // Split `maxWorkers` and the `S` in `HTTPServer`.
+            if prev.is_alphanumeric() && (!prev.is_uppercase() || next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// Get snake_case names of `fields`, returns `fields` itself if all of
+/// them are already in snake_case.
+///
+/// Converted names are leaked and cached, there is one entry per struct
+/// type at most.
+fn snake_fields(fields: Fields) -> Fields {
+    if fields.iter().all(|f| to_snake_case(f) == *f) {
+        return fields;
+    }
+
+    static CACHE: OnceLock<Mutex<HashMap<usize, Fields>>> = OnceLock::new();
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    cache.entry(fields.as_ptr() as usize).or_insert_with(|| {
+        let names: Vec<&'static str> = fields
+            .iter()
+            .map(|f| &*Box::leak(to_snake_case(f).into_boxed_str()))
+            .collect();
+        Box::leak(names.into_boxed_slice())
+    })
+}
+
+/// Wrap deserializers, visitors and accessors to rename fields of all
+/// nested structs.
+struct Wrap<T>(T);
+
+macro_rules! forward_deserialize {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, vis: V) -> Result<V::Value, D::Error> {
+                self.0.$method(Wrap(vis))
+            }
+        )*
+    };
+}
+
+impl<'de, D: de::Deserializer<'de>> de::Deserializer<'de> for Wrap<D> {
+    type Error = D::Error;
+
+    forward_deserialize!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_seq,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any
+    );
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        vis: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_unit_struct(name, Wrap(vis))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        vis: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_newtype_struct(name, Wrap(vis))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, vis: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple(len, Wrap(vis))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        vis: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_tuple_struct(name, len, Wrap(vis))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: Fields,
+        vis: V,
+    ) -> Result<V::Value, D::Error> {
+        let snake = snake_fields(fields);
+        self.0
+            .deserialize_struct(name, snake, Renamed::new(vis, fields, snake))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: Fields,
+        vis: V,
+    ) -> Result<V::Value, D::Error> {
+        self.0.deserialize_enum(name, variants, Wrap(vis))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Wrap<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    forward_visit!(
+        visit_bool: bool,
+        visit_i8: i8,
+        visit_i16: i16,
+        visit_i32: i32,
+        visit_i64: i64,
+        visit_i128: i128,
+        visit_u8: u8,
+        visit_u16: u16,
+        visit_u32: u32,
+        visit_u64: u64,
+        visit_u128: u128,
+        visit_f32: f32,
+        visit_f64: f64,
+        visit_char: char,
+        visit_str: &str,
+        visit_borrowed_str: &'de str,
+        visit_string: String,
+        visit_bytes: &[u8],
+        visit_borrowed_bytes: &'de [u8],
+        visit_byte_buf: Vec<u8>
+    );
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        self.0.visit_some(Wrap(d))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+        self.0.visit_newtype_struct(Wrap(d))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.0.visit_seq(Wrap(seq))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.0.visit_map(Wrap(map))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.0.visit_enum(Wrap(data))
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Wrap<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, d: D) -> Result<S::Value, D::Error> {
+        self.0.deserialize(Wrap(d))
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error> {
+        self.0.next_element_seed(Wrap(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.0.next_key_seed(Wrap(seed))
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.next_value_seed(Wrap(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+    type Variant = Wrap<A::Variant>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Self::Variant), A::Error> {
+        self.0
+            .variant_seed(Wrap(seed))
+            .map(|(v, variant)| (v, Wrap(variant)))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Wrap<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.0.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, A::Error> {
+        self.0.newtype_variant_seed(Wrap(seed))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, vis: V) -> Result<V::Value, A::Error> {
+        self.0.tuple_variant(len, Wrap(vis))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: Fields, vis: V) -> Result<V::Value, A::Error> {
+        let snake = snake_fields(fields);
+        self.0
+            .struct_variant(snake, Renamed::new(vis, fields, snake))
+    }
+}
+
+/// Visitor of structs that maps snake_case keys back to field names.
+struct Renamed<V> {
+    inner: V,
+    fields: Fields,
+    snake: Fields,
+}
+
+impl<V> Renamed<V> {
+    fn new(inner: V, fields: Fields, snake: Fields) -> Self {
+        Self {
+            inner,
+            fields,
+            snake,
+        }
+    }
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Renamed<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_map(Renamed {
+            inner: map,
+            fields: self.fields,
+            snake: self.snake,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_seq(Wrap(seq))
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Renamed<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        let Some(key) = self.inner.next_key_seed(PhantomData::<String>)? else {
+            return Ok(None);
+        };
+        let key = self
+            .snake
+            .iter()
+            .position(|f| *f == key)
+            .and_then(|idx| self.fields.get(idx))
+            .map_or(key, |f| f.to_string());
+        let key: de::value::StringDeserializer<A::Error> = key.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        self.inner.next_value_seed(Wrap(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq, Default)]
+    #[serde(default, rename_all = "camelCase")]
+    struct TestServer {
+        listen_port: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    enum TestMode {
+        SingleNode,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Default)]
+    #[serde(default, rename_all = "camelCase")]
+    struct TestConfig {
+        max_workers: usize,
+        http_server: TestServer,
+        mode: Option<TestMode>,
+        #[serde(rename = "TLSEnabled")]
+        tls_enabled: bool,
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        for (input, expected) in [
+            ("max_workers", "max_workers"),
+            ("maxWorkers", "max_workers"),
+            ("MaxWorkers", "max_workers"),
+            ("max-workers", "max_workers"),
+            ("MAX_WORKERS", "max_workers"),
+            ("HTTPServer", "http_server"),
+            ("server.listenPort", "server.listen_port"),
+        ] {
+            assert_eq!(to_snake_case(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let t: TestConfig = from_iter([
+            ("MAX_WORKERS", "8"),
+            ("HTTP_SERVER_LISTEN_PORT", "80"),
+            ("TLS_ENABLED", "true"),
+            ("MODE", "singleNode"),
+        ])
+        .expect("must success");
+        assert_eq!(
+            t,
+            TestConfig {
+                max_workers: 8,
+                http_server: TestServer { listen_port: 80 },
+                mode: Some(TestMode::SingleNode),
+                tls_enabled: true,
+            }
+        );
+    }
+}
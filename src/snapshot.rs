@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde_bridge::Value;
+
+use crate::collectors::{SourceDescriptor, Trust};
+use crate::diff::{diff_values, ConfigDiff};
+use crate::report::BuildReport;
+use crate::usage::Tracked;
+use crate::value::{flatten, is_sensitive, REDACTED};
+use crate::Parser;
+
+/// Snapshot of a build returned by [`Builder::build_snapshot`][crate::Builder::build_snapshot].
+///
+/// It bundles the typed config with the merged [`Value`], where every
+/// field comes from and the [`BuildReport`], so it can be exported,
+/// redacted and compared without rebuilding.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_str;
+/// use serfig::parsers::Toml;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     addr: String,
+///     password: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default()
+///         .collect(from_str(Toml, "addr = \"127.0.0.1\"\npassword = \"foo\""));
+///
+///     let snapshot = builder.build_snapshot(TestConfig::default())?;
+///     assert_eq!(snapshot.value().addr, "127.0.0.1");
+///
+///     let exported = snapshot.redacted().export(Toml)?;
+///     assert!(String::from_utf8(exported)?.contains("<redacted>"));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Snapshot<V> {
+    pub(crate) value: V,
+    pub(crate) merged: Value,
+    pub(crate) sources: BTreeMap<String, SourceDescriptor>,
+    pub(crate) taints: BTreeMap<String, Trust>,
+    pub(crate) report: BuildReport,
+}
+
+impl<V> Snapshot<V> {
+    /// Returns the typed config.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Consume the snapshot and returns the typed config.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+
+    /// Returns the merged value of all layers.
+    pub fn merged(&self) -> &Value {
+        &self.merged
+    }
+
+    /// Returns the last source that set the leaf at dot separated `path`
+    /// like `tls.cert`, `None` means default.
+    pub fn source(&self, path: &str) -> Option<&SourceDescriptor> {
+        self.sources.get(path)
+    }
+
+    /// Returns the lowest trust of all sources that set the leaf at dot
+    /// separated `path`, `None` means default.
+    pub fn trust(&self, path: &str) -> Option<Trust> {
+        self.taints.get(path).copied()
+    }
+
+    /// Returns the report of this build.
+    pub fn report(&self) -> &BuildReport {
+        &self.report
+    }
+
+    /// Track which fields of the merged value are read by the application.
+    pub fn track_usage(&self) -> Tracked {
+        Tracked::new(self.merged.clone())
+    }
+
+    /// Replace values of sensitive keys like `password` or `token` in the
+    /// merged value with `<redacted>`.
+    ///
+    /// The typed config is kept as is.
+    pub fn redacted(mut self) -> Self {
+        redact(&mut self.merged);
+        self
+    }
+
+    /// Serialize the merged value via `parser`.
+    pub fn export<P: Parser>(&self, mut parser: P) -> Result<Vec<u8>> {
+        parser.export(&self.merged)
+    }
+
+    /// Compare with an `old` snapshot, values of sensitive keys are redacted.
+    pub fn diff(&self, old: &Snapshot<V>) -> ConfigDiff {
+        diff_values(&old.merged, &self.merged)
+    }
+
+    /// Returns a stable fingerprint of the merged value as hex string.
+    ///
+    /// The fingerprint is the same across runs and platforms, so it can be
+    /// used to check if two processes are running with the same config.
+    pub fn fingerprint(&self) -> String {
+        // FNV-1a, std's hasher is not guaranteed to be stable.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for (k, v) in flatten(&self.merged) {
+            for b in k.bytes().chain([b'=']).chain(v.bytes()).chain([b'\n']) {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+        format!("{hash:016x}")
+    }
+}
+
+/// Replace values of sensitive keys in place.
+fn redact(v: &mut Value) {
+    match v {
+        Value::Some(v) | Value::NewtypeStruct(_, v) => redact(v),
+        Value::Struct(_, fields) => {
+            for (k, v) in fields.iter_mut() {
+                if is_sensitive(k) {
+                    *v = Value::Str(REDACTED.to_string());
+                } else {
+                    redact(v)
+                }
+            }
+        }
+        Value::Map(m) => {
+            for (k, v) in m.iter_mut() {
+                match k {
+                    Value::Str(k) if is_sensitive(k) => *v = Value::Str(REDACTED.to_string()),
+                    _ => redact(v),
+                }
+            }
+        }
+        Value::Seq(vs) | Value::Tuple(vs) | Value::TupleStruct(_, vs) => {
+            vs.iter_mut().for_each(redact)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::collectors::from_str;
+    use crate::parsers::{Jsonc, Toml};
+    use crate::Builder;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfigTls {
+        cert: String,
+        password: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        port: u16,
+        tls: TestConfigTls,
+    }
+
+    fn build(layers: &[&'static str]) -> super::Snapshot<TestConfig> {
+        layers
+            .iter()
+            .fold(Builder::default(), |b, s| b.collect(from_str(Toml, s)))
+            .build_snapshot(TestConfig::default())
+            .expect("build snapshot")
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let old = build(&["port = 80", "tls = { cert = \"a\", password = \"foo\" }"]);
+        assert_eq!(old.value().port, 80);
+        assert_eq!(
+            old.source("port").map(|s| s.to_string()),
+            Some("str".into())
+        );
+        assert_eq!(
+            old.source("tls.cert").map(|s| s.to_string()),
+            Some("str".into())
+        );
+
+        let new = build(&["port = 80", "tls = { cert = \"b\", password = \"bar\" }"]);
+        assert_eq!(
+            new.diff(&old).to_string(),
+            "~ tls.cert = \"a\" -> \"b\"\n~ tls.password = <redacted> -> <redacted>\n"
+        );
+        assert_ne!(old.fingerprint(), new.fingerprint());
+        assert_eq!(
+            old.fingerprint(),
+            build(&["port = 80", "tls = { cert = \"a\", password = \"foo\" }"]).fingerprint()
+        );
+
+        let exported = new.redacted().export(Jsonc).expect("export");
+        let exported: serde_json::Value = serde_json::from_slice(&exported).expect("parse");
+        assert_eq!(exported["tls"]["password"], "<redacted>");
+        assert_eq!(exported["tls"]["cert"], "b");
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::collectors::{Collector, IntoCollector};
+use crate::Builder;
+
+type Factory<V> = Box<dyn Fn() -> Box<dyn Collector<V>> + Send + Sync>;
+
+/// Template is a reusable [`Builder`] that can be shared between threads.
+///
+/// Template holds collector factories instead of collectors, every build
+/// creates its own collector instances, so concurrent builds from the same
+/// template are safe.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::{from_env, from_str};
+/// use serfig::parsers::Toml;
+/// use serfig::Template;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+///     b: String,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let template = Arc::new(
+///         Template::new()
+///             .collect(|| from_str(Toml, r#"a = "base""#))
+///             .collect(from_env),
+///     );
+///
+///     let t = template.clone();
+///     let handle = std::thread::spawn(move || -> anyhow::Result<TestConfig> {
+///         t.builder().collect(from_str(Toml, r#"b = "tenant""#)).build()
+///     });
+///     let t = handle.join().unwrap()?;
+///     assert_eq!(t.b, "tenant");
+///     Ok(())
+/// }
+/// ```
+pub struct Template<V: DeserializeOwned + Serialize> {
+    factories: Vec<Factory<V>>,
+}
+
+impl<V: DeserializeOwned + Serialize> Default for Template<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Template<V>
+where
+    V: DeserializeOwned + Serialize,
+{
+    /// Create a new template.
+    pub fn new() -> Self {
+        Self {
+            factories: Vec::new(),
+        }
+    }
+
+    /// Add a collector factory into template.
+    ///
+    /// The factory will be called once for every build.
+    pub fn collect<F, C>(mut self, f: F) -> Self
+    where
+        F: Fn() -> C + Send + Sync + 'static,
+        C: IntoCollector<V>,
+    {
+        self.factories.push(Box::new(move || f().into_collector()));
+        self
+    }
+
+    /// Create a new builder with fresh collectors from this template.
+    ///
+    /// Callers can add more layers or constraints on the returned builder.
+    pub fn builder(&self) -> Builder<V> {
+        self.factories
+            .iter()
+            .fold(Builder::new(), |b, f| b.collect(f()))
+    }
+
+    /// Build value with input `default` as the default value.
+    ///
+    /// See [`Builder::build_with`] for details.
+    pub fn build_with(&self, default: V) -> Result<V> {
+        self.builder().build_with(default)
+    }
+}
+
+impl<V> Template<V>
+where
+    V: DeserializeOwned + Serialize + Default,
+{
+    /// Build value with `V::default()` as the default value.
+    ///
+    /// See [`Builder::build`] for details.
+    pub fn build(&self) -> Result<V> {
+        self.builder().build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::collectors::{from_reader, from_str};
+    use crate::parsers::Toml;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        addr: String,
+        tenant: String,
+        generation: usize,
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_concurrent_build() {
+        assert_send_sync::<Template<TestConfig>>();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let template = Template::new()
+            .collect(|| from_str(Toml, r#"addr = "127.0.0.1""#))
+            .collect(move || {
+                let generation = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                from_reader(Toml, Cursor::new(format!("generation = {generation}")))
+            });
+
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    let template = &template;
+                    s.spawn(move || {
+                        template
+                            .builder()
+                            .collect(from_reader(
+                                Toml,
+                                Cursor::new(format!(r#"tenant = "tenant-{i}""#)),
+                            ))
+                            .build()
+                            .expect("must success")
+                    })
+                })
+                .collect();
+
+            let mut generations = Vec::new();
+            for (i, h) in handles.into_iter().enumerate() {
+                let t: TestConfig = h.join().expect("thread must not panic");
+                assert_eq!(t.addr, "127.0.0.1");
+                assert_eq!(t.tenant, format!("tenant-{i}"));
+                generations.push(t.generation);
+            }
+            generations.sort();
+            assert_eq!(generations, (1..=8).collect::<Vec<_>>());
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 8);
+    }
+}
@@ -0,0 +1,132 @@
+//! Helpers for testing configs in downstream applications.
+
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::collectors::from_uri;
+use crate::diff::diff_values;
+use crate::value::to_value;
+use crate::Builder;
+
+/// Run a golden test for config type `V` with fixtures in `dir`.
+///
+/// The directory contains source fixtures and an expected fixture:
+///
+/// - Files named `expected.*` like `expected.toml` hold the expected
+///   value.
+/// - All other files are sources, which are loaded as layers in the
+///   order of their file names like `01-base.toml`, `02-override.env`.
+///
+/// Formats are decided by file extensions like
+/// [`from_uri`][crate::collectors::from_uri]. Returns an error with the
+/// diff from expected to actual value if they don't match.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     a: String,
+/// }
+///
+/// #[test]
+/// fn test_config_golden() -> anyhow::Result<()> {
+///     serfig::testing::golden::<TestConfig>("tests/fixtures/prod")
+/// }
+/// ```
+pub fn golden<V>(dir: impl AsRef<Path>) -> Result<()>
+where
+    V: DeserializeOwned + Serialize + Debug + Default + 'static,
+{
+    let dir = dir.as_ref();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|err| anyhow!("read dir {}: {err}", dir.display()))? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+
+    let (expected, sources): (Vec<PathBuf>, Vec<PathBuf>) = files
+        .into_iter()
+        .partition(|p| p.file_stem().and_then(|v| v.to_str()) == Some("expected"));
+    let expected = match expected.as_slice() {
+        [expected] => expected,
+        [] => bail!("golden test {}: expected fixture not found", dir.display()),
+        _ => bail!("golden test {}: multiple expected fixtures", dir.display()),
+    };
+
+    let load = |paths: &[PathBuf]| -> Result<V> {
+        paths
+            .iter()
+            .try_fold(Builder::default(), |b, p| {
+                Ok::<_, anyhow::Error>(b.collect(from_uri(&format!("file://{}", p.display()))?))
+            })?
+            .build()
+    };
+    let actual = load(&sources)?;
+    let expected = load(std::slice::from_ref(expected))?;
+
+    let diff = diff_values(&to_value(&expected)?, &to_value(&actual)?);
+    if !diff.is_empty() {
+        bail!(
+            "golden test {} failed, diff from expected to actual:\n{diff}",
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    struct TestConfig {
+        addr: String,
+        port: u16,
+        workers: u16,
+    }
+
+    #[test]
+    fn test_golden() {
+        let dir = std::env::temp_dir().join("serfig-golden");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create dir");
+        fs::write(
+            dir.join("01-base.toml"),
+            "addr = \"127.0.0.1\"\nport = 8080\nworkers = 4\n",
+        )
+        .expect("write base");
+        fs::write(dir.join("02-override.env"), "port=9090\n").expect("write override");
+        fs::write(
+            dir.join("expected.toml"),
+            "addr = \"127.0.0.1\"\nport = 9090\nworkers = 4\n",
+        )
+        .expect("write expected");
+
+        golden::<TestConfig>(&dir).expect("must success");
+
+        fs::write(dir.join("03-override.toml"), "workers = 8\n").expect("write override");
+        let err = golden::<TestConfig>(&dir).expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "golden test {} failed, diff from expected to actual:\n~ workers = 4 -> 8\n",
+                dir.display()
+            )
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde_bridge::Value;
+
+use crate::de;
+use crate::path::KeyPath;
+use crate::value::{flatten, get};
+
+/// Dynamic accessor over a built config that records which fields have
+/// been read, created by [`Snapshot::track_usage`][crate::Snapshot::track_usage].
+///
+/// Fields never read by the application are reported by
+/// [`Tracked::unread`], which helps to prune dead configuration.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serfig::collectors::from_str;
+/// use serfig::parsers::Toml;
+/// use serfig::Builder;
+///
+/// #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+/// #[serde(default)]
+/// struct TestConfig {
+///     addr: String,
+///     legacy_mode: bool,
+/// }
+///
+/// fn main() -> anyhow::Result<()> {
+///     let builder = Builder::default().collect(from_str(Toml, r#"addr = "127.0.0.1""#));
+///     let tracked = builder
+///         .build_snapshot(TestConfig::default())?
+///         .track_usage();
+///
+///     let addr: String = tracked.get("addr")?;
+///     assert_eq!(addr, "127.0.0.1");
+///     assert_eq!(tracked.unread(), vec!["legacy_mode"]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Tracked {
+    value: Value,
+    read: Mutex<BTreeSet<String>>,
+}
+
+impl Tracked {
+    /// Track reads of given value.
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            read: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Read the value at `path` like `tls.cert`, all fields under it are
+    /// marked as read.
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let key: KeyPath = path.parse()?;
+        let v = get(&self.value, &key).ok_or_else(|| anyhow!("`{key}` is not found"))?;
+
+        let prefix = key.to_string();
+        let mut read = self.read.lock().unwrap_or_else(PoisonError::into_inner);
+        for leaf in flatten(v).into_keys() {
+            read.insert(match leaf.chars().next() {
+                None => prefix.clone(),
+                Some('[') => format!("{prefix}{leaf}"),
+                Some(_) if prefix.is_empty() => leaf,
+                Some(_) => format!("{prefix}.{leaf}"),
+            });
+        }
+        drop(read);
+
+        de::from_value(v.clone()).map_err(|err| anyhow!("deserialize `{key}`: {err}"))
+    }
+
+    /// Returns dot separated paths of fields that have been read.
+    pub fn read(&self) -> Vec<String> {
+        let read = self.read.lock().unwrap_or_else(PoisonError::into_inner);
+        read.iter().cloned().collect()
+    }
+
+    /// Returns dot separated paths of fields that have never been read,
+    /// sorted by path.
+    pub fn unread(&self) -> Vec<String> {
+        let read = self.read.lock().unwrap_or_else(PoisonError::into_inner);
+        flatten(&self.value)
+            .into_keys()
+            .filter(|path| !read.contains(path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::value::to_value;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    struct TestConfigTls {
+        cert: String,
+        key: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+    struct TestConfig {
+        port: u16,
+        tags: Vec<String>,
+        tls: TestConfigTls,
+    }
+
+    #[test]
+    fn test_tracked() {
+        let t = Tracked::new(
+            to_value(&TestConfig {
+                port: 80,
+                tags: vec!["a".to_string()],
+                ..Default::default()
+            })
+            .expect("to value"),
+        );
+
+        assert_eq!(t.get::<u16>("port").expect("get port"), 80);
+        assert_eq!(
+            t.get::<TestConfigTls>("tls").expect("get tls"),
+            TestConfigTls::default()
+        );
+        assert!(t.get::<u16>("not_exist").is_err());
+
+        assert_eq!(t.read(), vec!["port", "tls.cert", "tls.key"]);
+        assert_eq!(t.unread(), vec!["tags[0]"]);
+    }
+}
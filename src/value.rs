@@ -1,120 +1,589 @@
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
+use std::sync::{Mutex, OnceLock, PoisonError};
 
+use anyhow::{anyhow, Result};
 use indexmap::IndexMap;
-use serde_bridge::Value;
+use serde::Serialize;
+use serde_bridge::{into_value, Value};
 
-fn merge_map_with_default<K: Hash + Eq>(
-    mut d: IndexMap<K, Value>,
-    r: IndexMap<K, Value>,
-) -> IndexMap<K, Value> {
-    for (k, rv) in r {
-        match d.remove(&k) {
-            Some(lv) => {
-                d.insert(k, merge_with_default(lv, rv));
-            }
-            None => {
-                d.insert(k, rv);
+use crate::defaultness::{self, type_name, Predicate, TypeOverride};
+use crate::path::{KeyPath, Segment};
+
+/// Keys whose values must not show up in reports.
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "credential",
+    "private_key",
+    "access_key",
+];
+
+/// Placeholder for redacted values.
+pub const REDACTED: &str = "<redacted>";
+
+/// Convert `v` into [`Value`], errors contain the type name and the path
+/// of the offending field if possible.
+pub fn to_value<T: Serialize + ?Sized>(v: &T) -> Result<Value> {
+    let err = match into_value(v) {
+        Ok(v) => return Ok(v),
+        Err(err) => err.to_string(),
+    };
+    let ty = std::any::type_name::<T>();
+
+    // serde-bridge doesn't track paths, replay with serde_json and trust
+    // its path only if it fails in the same way.
+    match serde_path_to_error::serialize(v, serde_json::value::Serializer) {
+        Err(e) if e.path().iter().next().is_some() && e.inner().to_string() == err => {
+            Err(anyhow!("serialize `{ty}` at `{}`: {err}", e.path()))
+        }
+        _ => Err(anyhow!("serialize `{ty}`: {err}")),
+    }
+}
+
+/// Serialize `V::default()` once per type and return clones of it
+/// afterwards.
+pub fn default_value<V: Serialize + Default + 'static>() -> Result<Value> {
+    static CACHE: OnceLock<Mutex<HashMap<TypeId, Value>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(v) = cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&TypeId::of::<V>())
+    {
+        return Ok(v.clone());
+    }
+    // Serialize without holding the lock, racing threads produce the
+    // same value anyway.
+    let v = to_value(&V::default())?;
+    cache
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(TypeId::of::<V>(), v.clone());
+    Ok(v)
+}
+
+/// MergeConfig controls how layers are merged by [`Builder`][crate::Builder].
+///
+/// The default config deep merges structs and maps, and replaces
+/// sequences by the later layer. Map keys are matched after stringifying
+/// numbers and trimming strings, so `8080` from one format overrides
+/// `"8080"` from another.
+///
+/// # Examples
+///
+/// ```
+/// use serfig::{MergeConfig, MergeStrategy, SeqPolicy};
+///
+/// let cfg = MergeConfig::default()
+///     .strategy(MergeStrategy::Deep)
+///     .seq_policy(SeqPolicy::Append)
+///     .fold_case(true);
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MergeConfig {
+    strategy: MergeStrategy,
+    seq_policy: SeqPolicy,
+    fold_case: bool,
+    #[serde(skip)]
+    predicate: Option<Predicate>,
+    #[serde(skip)]
+    type_overrides: Vec<(&'static str, TypeOverride)>,
+}
+
+impl MergeConfig {
+    /// Set how a layer is merged into the previous ones.
+    pub fn strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set how sequences from different layers are merged.
+    pub fn seq_policy(mut self, policy: SeqPolicy) -> Self {
+        self.seq_policy = policy;
+        self
+    }
+
+    /// Match string map keys case insensitively, so `Foo` in a later
+    /// layer overrides `foo` instead of adding a new key.
+    pub fn fold_case(mut self, fold: bool) -> Self {
+        self.fold_case = fold;
+        self
+    }
+
+    /// Consult `p` for every value before comparing with defaults, see
+    /// [`defaultness`][crate::defaultness] for the rules.
+    pub fn default_predicate(mut self, p: Predicate) -> Self {
+        self.predicate = Some(p);
+        self
+    }
+
+    /// Decide whether values of the struct or enum named `name` are
+    /// default with `f`, like treating a sentinel variant as unset.
+    ///
+    /// `name` is the type name seen by serde, which is the Rust type name
+    /// unless `#[serde(rename)]` is used on the type.
+    pub fn override_type(mut self, name: &'static str, f: TypeOverride) -> Self {
+        self.type_overrides.retain(|(n, _)| *n != name);
+        self.type_overrides.push((name, f));
+        self
+    }
+
+    /// Check whether `v` is default and must not override previous
+    /// layers, `d` is the value in `V::default()` at the same path if
+    /// there is one.
+    pub fn is_default(&self, v: &Value, d: Option<&Value>) -> bool {
+        if let Some(name) = type_name(v) {
+            if let Some((_, f)) = self.type_overrides.iter().find(|(n, _)| *n == name) {
+                return f(v);
             }
+        }
+        if let Some(decided) = self.predicate.and_then(|p| p(v)) {
+            return decided;
+        }
+        defaultness::is_default(v, d)
+    }
+}
+
+/// Predicates are functions that can't be compared reliably, configs
+/// are equal if they customize defaultness for the same types.
+impl PartialEq for MergeConfig {
+    fn eq(&self, other: &Self) -> bool {
+        let names = |cfg: &Self| -> Vec<&'static str> {
+            cfg.type_overrides.iter().map(|(n, _)| *n).collect()
         };
+        self.strategy == other.strategy
+            && self.seq_policy == other.seq_policy
+            && self.fold_case == other.fold_case
+            && self.predicate.is_some() == other.predicate.is_some()
+            && names(self) == names(other)
     }
-    d
 }
 
-pub fn merge_with_default(d: Value, r: Value) -> Value {
-    use Value::*;
+impl Eq for MergeConfig {}
+
+impl Display for MergeConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let strategy = match self.strategy {
+            MergeStrategy::Deep => "deep",
+            MergeStrategy::Replace => "replace",
+        };
+        let seq_policy = match self.seq_policy {
+            SeqPolicy::Replace => "replace",
+            SeqPolicy::Append => "append",
+        };
+        write!(f, "strategy={strategy} seq_policy={seq_policy}")?;
+        if self.fold_case {
+            write!(f, " fold_case")?;
+        }
+        if self.predicate.is_some() {
+            write!(f, " default_predicate")?;
+        }
+        for (name, _) in &self.type_overrides {
+            write!(f, " override_type={name}")?;
+        }
+        Ok(())
+    }
+}
+
+/// MergeStrategy decides how a layer is merged into the previous ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Merge structs and maps field by field.
+    #[default]
+    Deep,
+    /// Replace the whole value if the layer differs from default.
+    Replace,
+}
+
+/// SeqPolicy decides how sequences from different layers are merged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeqPolicy {
+    /// Take the sequence from the later layer.
+    #[default]
+    Replace,
+    /// Append the sequence from the later layer.
+    Append,
+}
+
+/// Merge `r` into `l` in place by taking the last non-default value.
+///
+/// `d` is the default value which is used to decide whether a value has
+/// been set by user input: values in `r` that are default will not
+/// overwrite `l`, see [`defaultness`][crate::defaultness] for the rules.
+pub fn merge(cfg: &MergeConfig, d: &Value, l: &mut Value, r: Value) {
+    if cfg.strategy == MergeStrategy::Replace {
+        if !cfg.is_default(&r, Some(d)) {
+            *l = r;
+        }
+        return;
+    }
+    merge_value(cfg, Some(d), l, r)
+}
 
-    match (d, r) {
-        (Map(l), Map(r)) => Value::Map(merge_map_with_default(l, r)),
-        (Struct(ln, lv), Struct(rn, rv)) if ln == rn => {
-            Value::Struct(ln, merge_map_with_default(lv, rv))
+fn merge_value(cfg: &MergeConfig, d: Option<&Value>, l: &mut Value, r: Value) {
+    // `r` is default, keep `l` as it is.
+    if cfg.is_default(&r, d) {
+        return;
+    }
+    if let Some(d) = d {
+        // `l` is the same as default, take `r` directly. Maps and structs
+        // are still merged key by key so map entries from default are kept.
+        let mergeable = matches!(
+            (&*l, &r),
+            (Value::Map(_), Value::Map(_))
+                | (Value::Struct(..), Value::Struct(..))
+                | (Value::StructVariant { .. }, Value::StructVariant { .. })
+        );
+        if d == l && !mergeable {
+            *l = r;
+            return;
+        }
+    }
+
+    match (d, l, r) {
+        (d, Value::Map(lm), Value::Map(rm)) => {
+            let dm = match d {
+                Some(Value::Map(dm)) => Some(dm),
+                _ => None,
+            };
+            let rm = align_keys(cfg, lm, rm);
+            merge_map(cfg, dm, lm, rm)
+        }
+        (_, Value::Seq(ls), Value::Seq(rs)) if cfg.seq_policy == SeqPolicy::Append => ls.extend(rs),
+        (d, Value::Struct(ln, lm), Value::Struct(rn, rm)) if *ln == rn => {
+            let dm = match d {
+                Some(Value::Struct(dn, dm)) if dn == ln => Some(dm),
+                _ => None,
+            };
+            merge_map(cfg, dm, lm, rm)
         }
         (
-            StructVariant {
+            d,
+            Value::StructVariant {
                 name: ln,
                 variant_index: lvi,
                 variant: lv,
-                fields: lf,
+                fields: lm,
             },
-            StructVariant {
+            Value::StructVariant {
                 name: rn,
                 variant_index: rvi,
                 variant: rv,
-                fields: rf,
+                fields: rm,
             },
-        ) if ln == rn && lvi == rvi && lv == rv => Value::StructVariant {
-            name: ln,
-            variant_index: lvi,
-            variant: lv,
-            fields: merge_map_with_default(lf, rf),
-        },
-        // Return `other` value if they are not merge-able
-        (_, r) => r,
+        ) if *ln == rn && *lvi == rvi && *lv == rv => {
+            let dm = match d {
+                Some(Value::StructVariant {
+                    name: dn,
+                    variant_index: dvi,
+                    variant: dv,
+                    fields: dm,
+                }) if dn == ln && dvi == lvi && dv == lv => Some(dm),
+                _ => None,
+            };
+            merge_map(cfg, dm, lm, rm)
+        }
+        // Take `r` if they are not merge-able
+        (_, l, r) => *l = r,
     }
 }
 
 fn merge_map<K: Hash + Eq>(
-    mut d: IndexMap<K, Value>,
-    mut l: IndexMap<K, Value>,
+    cfg: &MergeConfig,
+    d: Option<&IndexMap<K, Value>>,
+    l: &mut IndexMap<K, Value>,
     r: IndexMap<K, Value>,
-) -> IndexMap<K, Value> {
+) {
     for (k, rv) in r {
-        let dv = d.remove(&k).expect("default must contain key");
-
-        match l.remove(&k) {
-            Some(lv) => {
-                let v = match (dv == lv, dv == rv) {
-                    (true, false) => rv,
-                    (true, true) => dv,
-                    (false, true) => lv,
-                    (false, false) => merge(dv, lv, rv),
-                };
-                l.insert(k, v);
-            }
+        let dv = d.and_then(|d| d.get(&k));
+
+        match l.get_mut(&k) {
+            Some(lv) => merge_value(cfg, dv, lv, rv),
             None => {
                 l.insert(k, rv);
             }
-        };
+        }
     }
-    l
 }
 
-pub fn merge(d: Value, l: Value, r: Value) -> Value {
-    use Value::*;
+/// Rename keys in `r` to the existing keys in `l` that represent the
+/// same logical key, so layers from different formats merge onto the
+/// same entries.
+///
+/// Keys are compared by [`normalize_key`], and case insensitively if
+/// `fold_case` is set.
+fn align_keys(
+    cfg: &MergeConfig,
+    l: &IndexMap<Value, Value>,
+    r: IndexMap<Value, Value>,
+) -> IndexMap<Value, Value> {
+    let normalize = |k: &Value| {
+        let k = normalize_key(k)?;
+        Some(if cfg.fold_case {
+            k.to_ascii_lowercase()
+        } else {
+            k
+        })
+    };
+    // Index keys of `l` once, the first key wins like lookups in order.
+    let mut index: Option<HashMap<String, &Value>> = None;
+    r.into_iter()
+        .map(|(k, v)| {
+            if l.contains_key(&k) {
+                return (k, v);
+            }
+            let index = index.get_or_insert_with(|| {
+                let mut index = HashMap::with_capacity(l.len());
+                for lk in l.keys() {
+                    if let Some(nk) = normalize(lk) {
+                        index.entry(nk).or_insert(lk);
+                    }
+                }
+                index
+            });
+            let aligned = normalize(&k).and_then(|rk| index.get(&rk).copied());
+            (aligned.cloned().unwrap_or(k), v)
+        })
+        .collect()
+}
 
-    match (d, l, r) {
-        (Map(d), Map(l), Map(r)) => Value::Map(merge_map(d, l, r)),
-        (Struct(dn, dv), Struct(ln, lv), Struct(rn, rv)) if dn == ln && ln == rn => {
-            Value::Struct(ln, merge_map(dv, lv, rv))
+/// Stringify numeric keys and trim string keys, returns `None` for keys
+/// that can't be compared as strings.
+fn normalize_key(k: &Value) -> Option<String> {
+    Some(match k {
+        Value::Str(v) => v.trim().to_string(),
+        Value::Char(v) => v.to_string(),
+        Value::I8(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::I128(v) => v.to_string(),
+        Value::U8(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::U128(v) => v.to_string(),
+        _ => return None,
+    })
+}
+
+/// Convert a [`serde_json::Value`] into [`Value`].
+///
+/// Numbers are converted explicitly instead of going through serde, which
+/// turns them into maps once serde_json's `arbitrary_precision` feature is
+/// enabled by any crate in the dependency graph.
+pub fn from_json(v: serde_json::Value) -> Value {
+    match v {
+        serde_json::Value::Null => Value::Unit,
+        serde_json::Value::Bool(v) => Value::Bool(v),
+        serde_json::Value::Number(n) => match (n.as_u64(), n.as_i64(), n.as_f64()) {
+            (Some(v), _, _) => Value::U64(v),
+            (_, Some(v), _) => Value::I64(v),
+            (_, _, Some(v)) => Value::F64(v),
+            // Only possible for numbers out of range with
+            // `arbitrary_precision`, keep their text.
+            _ => Value::Str(n.to_string()),
+        },
+        serde_json::Value::String(v) => Value::Str(v),
+        serde_json::Value::Array(vs) => Value::Seq(vs.into_iter().map(from_json).collect()),
+        serde_json::Value::Object(m) => Value::Map(
+            m.into_iter()
+                .map(|(k, v)| (Value::Str(k), from_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Get the value at `path` like `tls.cert`.
+pub fn get<'a>(v: &'a Value, path: &KeyPath) -> Option<&'a Value> {
+    path.segments().iter().try_fold(v, get_segment)
+}
+
+fn get_segment<'a>(v: &'a Value, s: &Segment) -> Option<&'a Value> {
+    match (v, s) {
+        (Value::Some(v) | Value::NewtypeStruct(_, v), s) => get_segment(v, s),
+        (Value::Struct(_, m) | Value::StructVariant { fields: m, .. }, Segment::Key(k)) => {
+            m.get(k.as_str())
         }
+        (Value::Map(m), Segment::Key(k)) => m.get(&Value::Str(k.clone())),
         (
-            StructVariant {
-                name: dn,
-                variant_index: dvi,
-                variant: dv,
-                fields: df,
-            },
-            StructVariant {
-                name: ln,
-                variant_index: lvi,
-                variant: lv,
-                fields: lf,
-            },
-            StructVariant {
-                name: rn,
-                variant_index: rvi,
-                variant: rv,
-                fields: rf,
-            },
-        ) if ln == rn && lvi == rvi && lv == rv && ln == dn && lvi == dvi && lv == dv => {
-            Value::StructVariant {
-                name: ln,
-                variant_index: lvi,
-                variant: lv,
-                fields: merge_map(df, lf, rf),
+            Value::Seq(vs)
+            | Value::Tuple(vs)
+            | Value::TupleStruct(_, vs)
+            | Value::TupleVariant { fields: vs, .. },
+            Segment::Index(i),
+        ) => vs.get(*i),
+        _ => None,
+    }
+}
+
+/// Get the mutable value at `path` like `tls.cert`.
+pub fn get_mut<'a>(v: &'a mut Value, path: &KeyPath) -> Option<&'a mut Value> {
+    path.segments().iter().try_fold(v, get_segment_mut)
+}
+
+fn get_segment_mut<'a>(v: &'a mut Value, s: &Segment) -> Option<&'a mut Value> {
+    match (v, s) {
+        (Value::Some(v) | Value::NewtypeStruct(_, v), s) => get_segment_mut(v, s),
+        (Value::Struct(_, m) | Value::StructVariant { fields: m, .. }, Segment::Key(k)) => {
+            m.get_mut(k.as_str())
+        }
+        (Value::Map(m), Segment::Key(k)) => m.get_mut(&Value::Str(k.clone())),
+        (
+            Value::Seq(vs)
+            | Value::Tuple(vs)
+            | Value::TupleStruct(_, vs)
+            | Value::TupleVariant { fields: vs, .. },
+            Segment::Index(i),
+        ) => vs.get_mut(*i),
+        _ => None,
+    }
+}
+
+/// Parse `s` as the type of `target`, which is the default value of the
+/// field `s` is set to.
+///
+/// Sequences are split by `,`. Types are inferred from `s` if `target`
+/// doesn't tell, like `None` or empty sequences.
+pub(crate) fn parse_str(s: &str, target: Option<&Value>) -> Result<Value> {
+    Ok(match target {
+        Some(Value::Some(v) | Value::NewtypeStruct(_, v)) => parse_str(s, Some(v))?,
+        Some(Value::Bool(_)) => Value::Bool(s.parse()?),
+        Some(Value::I8(_)) => Value::I8(s.parse()?),
+        Some(Value::I16(_)) => Value::I16(s.parse()?),
+        Some(Value::I32(_)) => Value::I32(s.parse()?),
+        Some(Value::I64(_)) => Value::I64(s.parse()?),
+        Some(Value::I128(_)) => Value::I128(s.parse()?),
+        Some(Value::U8(_)) => Value::U8(s.parse()?),
+        Some(Value::U16(_)) => Value::U16(s.parse()?),
+        Some(Value::U32(_)) => Value::U32(s.parse()?),
+        Some(Value::U64(_)) => Value::U64(s.parse()?),
+        Some(Value::U128(_)) => Value::U128(s.parse()?),
+        Some(Value::F32(_)) => Value::F32(s.parse()?),
+        Some(Value::F64(_)) => Value::F64(s.parse()?),
+        Some(Value::Char(_)) => Value::Char(s.parse()?),
+        // Unit variants of enums are deserialized from their names.
+        Some(Value::Str(_) | Value::UnitVariant { .. }) => Value::Str(s.to_string()),
+        Some(Value::Seq(vs)) => Value::Seq(
+            s.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| parse_str(s, vs.first()))
+                .collect::<Result<_>>()?,
+        ),
+        Some(Value::Map(_) | Value::Struct(..)) => {
+            return Err(anyhow!("tables can't be set from a string"))
+        }
+        _ => {
+            if let Ok(v) = s.parse() {
+                Value::Bool(v)
+            } else if let Ok(v) = s.parse() {
+                Value::I64(v)
+            } else if let Ok(v) = s.parse() {
+                Value::F64(v)
+            } else {
+                Value::Str(s.to_string())
             }
         }
-        // Return `other` value if they are not merge-able
-        (_, _, r) => r,
+    })
+}
+
+/// Check if the value at `path` is sensitive like passwords.
+pub fn is_sensitive(path: &str) -> bool {
+    let key = path.rsplit('.').next().unwrap_or(path).to_lowercase();
+    SENSITIVE_KEYS.iter().any(|k| key.contains(k))
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Flatten value into leaf paths and their rendered values.
+///
+/// Paths are sorted so the output is stable.
+pub fn flatten(v: &Value) -> BTreeMap<String, String> {
+    let mut out = BTreeMap::new();
+    flatten_value(v, "", &mut out);
+    out
+}
+
+fn flatten_value(v: &Value, prefix: &str, out: &mut BTreeMap<String, String>) {
+    match v {
+        Value::Some(v) | Value::NewtypeStruct(_, v) => flatten_value(v, prefix, out),
+        Value::Struct(_, fields) => {
+            for (k, v) in fields {
+                flatten_value(v, &join(prefix, k), out)
+            }
+        }
+        Value::Map(m) => {
+            for (k, v) in m {
+                let k = match k {
+                    Value::Str(k) => k.clone(),
+                    k => render(k),
+                };
+                flatten_value(v, &join(prefix, &k), out)
+            }
+        }
+        Value::Seq(vs) | Value::Tuple(vs) | Value::TupleStruct(_, vs) => {
+            for (idx, v) in vs.iter().enumerate() {
+                flatten_value(v, &format!("{prefix}[{idx}]"), out)
+            }
+        }
+        Value::NewtypeVariant { variant, value, .. } => {
+            flatten_value(value, &join(prefix, variant), out)
+        }
+        Value::TupleVariant {
+            variant, fields, ..
+        } => {
+            for (idx, v) in fields.iter().enumerate() {
+                flatten_value(v, &format!("{}[{idx}]", join(prefix, variant)), out)
+            }
+        }
+        Value::StructVariant {
+            variant, fields, ..
+        } => {
+            for (k, v) in fields {
+                flatten_value(v, &join(&join(prefix, variant), k), out)
+            }
+        }
+        v => {
+            out.insert(prefix.to_string(), render(v));
+        }
+    }
+}
+
+pub(crate) fn render(v: &Value) -> String {
+    match v {
+        Value::Bool(v) => v.to_string(),
+        Value::I8(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::I128(v) => v.to_string(),
+        Value::U8(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::U128(v) => v.to_string(),
+        Value::F32(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::Char(v) => format!("{:?}", v.to_string()),
+        Value::Str(v) => format!("{v:?}"),
+        Value::None | Value::Unit => "null".to_string(),
+        Value::UnitStruct(name) => name.to_string(),
+        Value::UnitVariant { variant, .. } => variant.to_string(),
+        v => format!("{v:?}"),
     }
 }
 
@@ -165,6 +634,244 @@ mod tests {
             })
         });
 
-        assert_eq!(merge(d, l, r), expect)
+        let mut l = l;
+        merge(&MergeConfig::default(), &d, &mut l, r);
+        assert_eq!(l, expect)
+    }
+
+    #[test]
+    fn test_merge_key_not_in_default() {
+        let d = Map(indexmap! {});
+        let mut l = Map(indexmap! {
+            Str("a".to_string()) => I64(1),
+        });
+        let r = Map(indexmap! {
+            Str("a".to_string()) => I64(2),
+            Str("b".to_string()) => I64(3),
+        });
+
+        merge(&MergeConfig::default(), &d, &mut l, r);
+        assert_eq!(
+            l,
+            Map(indexmap! {
+                Str("a".to_string()) => I64(2),
+                Str("b".to_string()) => I64(3),
+            })
+        )
+    }
+
+    #[test]
+    fn test_merge_map_with_default_entries() {
+        let d = Struct(
+            "config",
+            indexmap! {
+                "labels" => Map(indexmap! {
+                    Str("a".to_string()) => I64(1),
+                }),
+            },
+        );
+        let mut l = d.clone();
+        let r = Struct(
+            "config",
+            indexmap! {
+                "labels" => Map(indexmap! {
+                    Str("b".to_string()) => I64(2),
+                }),
+            },
+        );
+
+        merge(&MergeConfig::default(), &d, &mut l, r);
+        assert_eq!(
+            l,
+            Struct(
+                "config",
+                indexmap! {
+                    "labels" => Map(indexmap! {
+                        Str("a".to_string()) => I64(1),
+                        Str("b".to_string()) => I64(2),
+                    }),
+                }
+            )
+        )
+    }
+
+    #[test]
+    fn test_default_value() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Serialize)]
+        struct Counted {
+            a: u64,
+        }
+
+        impl Default for Counted {
+            fn default() -> Self {
+                CALLS.fetch_add(1, Ordering::SeqCst);
+                Counted { a: 1 }
+            }
+        }
+
+        let v = default_value::<Counted>().expect("default value");
+        assert_eq!(v, Struct("Counted", indexmap! { "a" => U64(1) }));
+        assert_eq!(default_value::<Counted>().expect("default value"), v);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_merge_entry_not_in_default() {
+        // Entries only exist in layers, so later layers always win even
+        // with values like `false` or `0`.
+        let d = Map(indexmap! {});
+        let mut l = Map(indexmap! {
+            Str("features".to_string()) => Map(indexmap! {
+                Str("x".to_string()) => Bool(true),
+            }),
+            Str("limits".to_string()) => Map(indexmap! {
+                Str("conns".to_string()) => U32(10),
+            }),
+        });
+        let r = Map(indexmap! {
+            Str("features".to_string()) => Map(indexmap! {
+                Str("x".to_string()) => Bool(false),
+            }),
+            Str("limits".to_string()) => Map(indexmap! {
+                Str("conns".to_string()) => U32(0),
+            }),
+        });
+
+        merge(&MergeConfig::default(), &d, &mut l, r.clone());
+        assert_eq!(l, r);
+    }
+
+    #[test]
+    fn test_merge_config() {
+        let d = Map(indexmap! {
+            Str("seq".to_string()) => Seq(vec![]),
+        });
+        let l = Map(indexmap! {
+            Str("seq".to_string()) => Seq(vec![I64(1)]),
+            Str("key".to_string()) => I64(1),
+        });
+        let r = Map(indexmap! {
+            Str("seq".to_string()) => Seq(vec![I64(2)]),
+            Str("KEY".to_string()) => I64(2),
+        });
+
+        let cfg = MergeConfig::default()
+            .seq_policy(SeqPolicy::Append)
+            .fold_case(true);
+        let mut v = l.clone();
+        merge(&cfg, &d, &mut v, r.clone());
+        assert_eq!(
+            v,
+            Map(indexmap! {
+                Str("seq".to_string()) => Seq(vec![I64(1), I64(2)]),
+                Str("key".to_string()) => I64(2),
+            })
+        );
+
+        let cfg = MergeConfig::default().strategy(MergeStrategy::Replace);
+        let mut v = l;
+        merge(&cfg, &d, &mut v, r.clone());
+        assert_eq!(v, r);
+    }
+
+    #[test]
+    fn test_merge_normalize_keys() {
+        let d = Map(indexmap! {});
+        let mut v = Map(indexmap! {
+            Str("8080".to_string()) => Str("http".to_string()),
+            I64(1) => Str("one".to_string()),
+        });
+        let r = Map(indexmap! {
+            U16(8080) => Str("https".to_string()),
+            Str(" 1 ".to_string()) => Str("uno".to_string()),
+        });
+
+        merge(&MergeConfig::default(), &d, &mut v, r);
+        assert_eq!(
+            v,
+            Map(indexmap! {
+                Str("8080".to_string()) => Str("https".to_string()),
+                I64(1) => Str("uno".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_value() {
+        use serde::Serializer;
+
+        fn fail<S: Serializer>(_: &u64, _: S) -> std::result::Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("port is not serializable"))
+        }
+
+        #[derive(serde::Serialize)]
+        struct Server {
+            #[serde(serialize_with = "fail")]
+            port: u64,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Config {
+            servers: Vec<Server>,
+        }
+
+        let err = to_value(&Config {
+            servers: vec![Server { port: 80 }],
+        })
+        .expect_err("must fail");
+        assert_eq!(
+            err.to_string(),
+            "serialize `serfig::value::tests::test_to_value::Config` at `servers[0].port`: \
+            port is not serializable"
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let v = Struct(
+            "test",
+            indexmap! {
+                "tls" => Some(Box::new(Struct("tls", indexmap! {
+                    "cert" => Str("cert".to_string()),
+                }))),
+                "map" => Map(indexmap! {
+                    Str("key".to_string()) => I64(1),
+                }),
+                "seq" => Seq(vec![I64(2)]),
+            },
+        );
+
+        let get = |path: &str| get(&v, &path.parse().expect("must be valid path"));
+        assert_eq!(get("tls.cert"), Option::Some(&Str("cert".to_string())));
+        assert_eq!(get("map.key"), Option::Some(&I64(1)));
+        assert_eq!(get("seq[0]"), Option::Some(&I64(2)));
+        assert_eq!(get("tls.key"), Option::None);
+        assert_eq!(get("seq[1]"), Option::None);
+    }
+
+    #[test]
+    fn test_from_json() {
+        let v = from_json(serde_json::json!({
+            "port": 8080,
+            "offset": -1,
+            "ratio": 0.5,
+            "tags": ["a"],
+            "proxy": null,
+        }));
+
+        assert_eq!(
+            v,
+            Map(indexmap! {
+                Str("port".to_string()) => U64(8080),
+                Str("offset".to_string()) => I64(-1),
+                Str("ratio".to_string()) => F64(0.5),
+                Str("tags".to_string()) => Seq(vec![Str("a".to_string())]),
+                Str("proxy".to_string()) => Unit,
+            })
+        );
     }
 }